@@ -0,0 +1,862 @@
+use crate::{ClientConfig, ClientError, Event, Request, Transport};
+use bytes::Bytes;
+use lib::gate::crypto::{KeyExchange, PUBLIC_KEY_LEN};
+use lib::gate::kcp::{self, Kcp};
+use lib::gate::packet::{Codec, CodecOptions, Packet, Type};
+use message_io::network::{self, Endpoint, NetEvent, SendStatus};
+use message_io::node::{self, NodeEvent, NodeHandler, NodeTask};
+use prost::Message;
+use protocol::base_cmd::{
+    Compression, Encryption, ErrorRsp, HandshakeAck, HandshakeReq, KickNtf, ResumeReq, ResumeRsp,
+};
+use std::collections::HashMap;
+use std::io;
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::{mpsc, oneshot};
+
+//服务端没有下发心跳间隔时使用
+const DEFAULT_HEARTBEAT_INTERVAL: u64 = 5000;
+
+enum Signal {
+    //检查心跳,服务端的心跳回包中断时主动补发
+    Heartbeat(Endpoint),
+    //驱动udp可靠会话的重传和确认
+    KcpTick(Endpoint),
+}
+
+//握手应答在网络线程处理,保证之后的包用协商后的Codec解码
+struct Handshaking {
+    //临时密钥和固定的网关公钥
+    exchange: Option<(KeyExchange, [u8; PUBLIC_KEY_LEN])>,
+    tx: oneshot::Sender<Result<HandshakeAck, ClientError>>,
+}
+
+type Pending = HashMap<u32, oneshot::Sender<Result<Packet, ClientError>>>;
+
+//共用节点上的连接,网络事件和信号按Endpoint分发
+#[derive(Default)]
+struct Router {
+    clients: Mutex<HashMap<Endpoint, Arc<Shared>>>,
+    //等待连接结果
+    connecting: Mutex<HashMap<Endpoint, oneshot::Sender<bool>>>,
+}
+
+impl Router {
+    fn on_event(&self, event: NodeEvent<Signal>) {
+        let endpoint = match &event {
+            NodeEvent::Network(NetEvent::Connected(endpoint, established)) => {
+                if let Some(tx) = self.connecting.lock().unwrap().remove(endpoint) {
+                    let _ = tx.send(*established);
+                }
+                return;
+            }
+            NodeEvent::Network(NetEvent::Accepted(..)) => return,
+            NodeEvent::Network(NetEvent::Message(endpoint, _))
+            | NodeEvent::Network(NetEvent::Disconnected(endpoint))
+            | NodeEvent::Signal(Signal::Heartbeat(endpoint))
+            | NodeEvent::Signal(Signal::KcpTick(endpoint)) => *endpoint,
+        };
+        //处理时可能关闭连接并从表中移除,先释放锁
+        let shared = self.clients.lock().unwrap().get(&endpoint).cloned();
+        if let Some(shared) = shared {
+            shared.on_event(event);
+        }
+    }
+}
+
+//停止网络线程,最后一个引用释放时执行
+struct DriverTask {
+    handler: NodeHandler<Signal>,
+    task: Option<NodeTask>,
+}
+
+impl Drop for DriverTask {
+    fn drop(&mut self) {
+        self.handler.stop();
+        drop(self.task.take());
+    }
+}
+
+//多个客户端共用的message-io节点和网络线程,压测时不用每个连接一个线程
+#[derive(Clone)]
+pub struct Driver {
+    handler: NodeHandler<Signal>,
+    router: Arc<Router>,
+    _task: Arc<DriverTask>,
+}
+
+impl Default for Driver {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Driver {
+    pub fn new() -> Self {
+        let (handler, listener) = node::split::<Signal>();
+        let router = Arc::new(Router::default());
+        let task = {
+            let router = router.clone();
+            listener.for_each_async(move |event| router.on_event(event))
+        };
+        Driver {
+            handler: handler.clone(),
+            router,
+            _task: Arc::new(DriverTask {
+                handler,
+                task: Some(task),
+            }),
+        }
+    }
+
+    //连接不阻塞,结果由网络线程通知
+    async fn connect(&self, config: &ClientConfig) -> Result<Endpoint, ClientError> {
+        let transport = match config.transport {
+            Transport::Tcp => network::Transport::FramedTcp,
+            Transport::Ws => network::Transport::Ws,
+            Transport::Udp => network::Transport::Udp,
+        };
+        let (tx, rx) = oneshot::channel();
+        let endpoint = {
+            //登记完成前网络线程等待锁,不会漏掉连接结果
+            let mut connecting = self.router.connecting.lock().unwrap();
+            let (endpoint, _) = self
+                .handler
+                .network()
+                .connect(transport, config.addr.clone())?;
+            connecting.insert(endpoint, tx);
+            endpoint
+        };
+        let kind = match tokio::time::timeout(config.handshake_timeout, rx).await {
+            Ok(Ok(true)) => return Ok(endpoint),
+            Ok(_) => io::ErrorKind::ConnectionRefused,
+            Err(_) => io::ErrorKind::TimedOut,
+        };
+        self.router.connecting.lock().unwrap().remove(&endpoint);
+        self.handler.network().remove(endpoint.resource_id());
+        Err(io::Error::new(kind, format!("connect {} failed", config.addr)).into())
+    }
+}
+
+//调用方和网络线程共用的连接状态
+struct Shared {
+    handler: NodeHandler<Signal>,
+    router: Arc<Router>,
+    endpoint: Endpoint,
+    codec: Mutex<Codec>,
+    kcp: Option<Mutex<Kcp>>,
+    kcp_interval: Duration,
+    handshaking: Mutex<Option<Handshaking>>,
+    //关闭后为None,等待中的请求都返回关闭原因
+    pending: Mutex<Option<Pending>>,
+    events: mpsc::UnboundedSender<Event>,
+    seq: AtomicU32,
+    last_push_seq: AtomicU32,
+    heartbeat_interval: AtomicU64,
+    last_heartbeat: AtomicU64,
+}
+
+fn now_ms() -> u64 {
+    common::time::now_ms() as u64
+}
+
+impl Shared {
+    fn on_event(&self, event: NodeEvent<Signal>) {
+        match event {
+            NodeEvent::Network(NetEvent::Message(_, data)) => self.receive(data),
+            NodeEvent::Network(NetEvent::Disconnected(_)) => {
+                tracing::info!("endpoint:{} disconnected", self.endpoint);
+                self.shutdown(None);
+            }
+            NodeEvent::Network(_) => {}
+            NodeEvent::Signal(Signal::Heartbeat(_)) => self.check_heartbeat(),
+            NodeEvent::Signal(Signal::KcpTick(_)) => self.kcp_tick(),
+        }
+    }
+
+    fn is_closed(&self) -> bool {
+        self.pending.lock().unwrap().is_none()
+    }
+
+    fn next_seq(&self) -> u32 {
+        //0表示没有seq,回绕时跳过
+        loop {
+            let seq = self.seq.fetch_add(1, Ordering::Relaxed).wrapping_add(1);
+            if seq != 0 {
+                return seq;
+            }
+        }
+    }
+
+    //编码和发送在同一个锁内,保证加密计数与发送顺序一致
+    fn send(&self, packet: Packet) -> Result<(), ClientError> {
+        let mut codec = self.codec.lock().unwrap();
+        let bytes = codec.encode(packet);
+        match &self.kcp {
+            Some(kcp) => {
+                let mut kcp = kcp.lock().unwrap();
+                kcp.send(&bytes)
+                    .map_err(|e| ClientError::Send(e.to_string()))?;
+                kcp.flush(common::time::now_ms() as u32);
+                self.send_datagrams(&mut kcp)
+            }
+            None => self.send_raw(&bytes),
+        }
+    }
+
+    fn send_raw(&self, data: &[u8]) -> Result<(), ClientError> {
+        match self.handler.network().send(self.endpoint, data) {
+            SendStatus::Sent => Ok(()),
+            status => Err(ClientError::Send(format!("{:?}", status))),
+        }
+    }
+
+    fn send_datagrams(&self, kcp: &mut Kcp) -> Result<(), ClientError> {
+        for datagram in kcp.take_output() {
+            self.send_raw(&datagram)?;
+        }
+        Ok(())
+    }
+
+    fn send_heartbeat(&self) {
+        self.last_heartbeat.store(now_ms(), Ordering::Relaxed);
+        let packet = Packet::new_control(Type::Heartbeat)
+            .with_seq(self.last_push_seq.load(Ordering::Relaxed));
+        if let Err(e) = self.send(packet) {
+            tracing::debug!("endpoint:{} heartbeat failed:{}", self.endpoint, e);
+        }
+    }
+
+    //服务端延迟一个间隔回心跳,收到即回;超过两个间隔没有收到时主动补发
+    fn check_heartbeat(&self) {
+        if self.is_closed() {
+            return;
+        }
+        let interval = self.heartbeat_interval.load(Ordering::Relaxed);
+        let elapsed = now_ms().saturating_sub(self.last_heartbeat.load(Ordering::Relaxed));
+        if elapsed >= interval * 2 {
+            self.send_heartbeat();
+        }
+        self.handler.signals().send_with_timer(
+            Signal::Heartbeat(self.endpoint),
+            Duration::from_millis(interval),
+        );
+    }
+
+    fn kcp_tick(&self) {
+        let Some(kcp) = &self.kcp else {
+            return;
+        };
+        if self.is_closed() {
+            return;
+        }
+        let dead = {
+            let mut kcp = kcp.lock().unwrap();
+            kcp.update(common::time::now_ms() as u32);
+            if let Err(e) = self.send_datagrams(&mut kcp) {
+                tracing::debug!("endpoint:{} kcp send failed:{}", self.endpoint, e);
+            }
+            kcp.is_dead()
+        };
+        if dead {
+            tracing::info!("endpoint:{} kcp dead link", self.endpoint);
+            self.shutdown(None);
+            return;
+        }
+        self.handler
+            .signals()
+            .send_with_timer(Signal::KcpTick(self.endpoint), self.kcp_interval);
+    }
+
+    fn receive(&self, data: &[u8]) {
+        let Some(kcp) = &self.kcp else {
+            self.on_frame(data);
+            return;
+        };
+        //处理帧时会发送心跳,先释放kcp的锁
+        let (frames, fin) = {
+            let mut kcp = kcp.lock().unwrap();
+            if let Err(e) = kcp.input(data, common::time::now_ms() as u32) {
+                tracing::warn!("endpoint:{} kcp input error:{}", self.endpoint, e);
+                return;
+            }
+            let mut frames = vec![];
+            while let Some(frame) = kcp.recv() {
+                frames.push(frame);
+            }
+            (frames, kcp.is_fin())
+        };
+        for frame in frames {
+            self.on_frame(&frame);
+        }
+        if fin {
+            self.shutdown(None);
+        }
+    }
+
+    fn on_frame(&self, data: &[u8]) {
+        let packet = match self.codec.lock().unwrap().decode(data) {
+            Ok(packet) => packet,
+            Err(e) => {
+                tracing::warn!("endpoint:{} decoding failed err:{}", self.endpoint, e);
+                self.shutdown(None);
+                return;
+            }
+        };
+        match packet.r#type {
+            Type::HandshakeAck => self.on_handshake_ack(packet.data),
+            Type::Heartbeat => self.send_heartbeat(),
+            Type::Response => {
+                let tx = self
+                    .pending
+                    .lock()
+                    .unwrap()
+                    .as_mut()
+                    .and_then(|x| x.remove(&packet.seq));
+                match tx {
+                    Some(tx) => {
+                        let _ = tx.send(Ok(packet));
+                    }
+                    None => tracing::debug!(
+                        "endpoint:{} cmd:{} seq:{} response without request",
+                        self.endpoint,
+                        packet.cmd,
+                        packet.seq
+                    ),
+                }
+            }
+            Type::Push => {
+                if self.ack_push(packet.seq) {
+                    let _ = self.events.send(Event::Push {
+                        cmd: packet.cmd,
+                        seq: packet.seq,
+                        data: packet.data,
+                    });
+                }
+            }
+            Type::Kick => {
+                let kick = KickNtf::decode(packet.data).unwrap_or_else(|e| KickNtf {
+                    message: e.to_string(),
+                    ..Default::default()
+                });
+                tracing::info!(
+                    "endpoint:{} kicked reason:{} msg:{}",
+                    self.endpoint,
+                    kick.reason,
+                    kick.message
+                );
+                self.shutdown(Some(kick));
+            }
+            typ => tracing::debug!("endpoint:{} unexpected packet:{}", self.endpoint, typ),
+        }
+    }
+
+    //重连补发的推送可能与已收到的重复,只接收更新的序号
+    fn ack_push(&self, seq: u32) -> bool {
+        let last = self.last_push_seq.load(Ordering::Relaxed);
+        if last != 0 && (seq.wrapping_sub(last) as i32) <= 0 {
+            return false;
+        }
+        self.last_push_seq.store(seq, Ordering::Relaxed);
+        true
+    }
+
+    fn on_handshake_ack(&self, data: Bytes) {
+        let Some(Handshaking { exchange, tx }) = self.handshaking.lock().unwrap().take() else {
+            tracing::warn!("endpoint:{} unexpected handshake ack", self.endpoint);
+            return;
+        };
+        let _ = tx.send(self.negotiate(exchange, data));
+    }
+
+    fn negotiate(
+        &self,
+        exchange: Option<(KeyExchange, [u8; PUBLIC_KEY_LEN])>,
+        data: Bytes,
+    ) -> Result<HandshakeAck, ClientError> {
+        let ack = HandshakeAck::decode(data)?;
+        let cipher = match (ack.encryption(), exchange) {
+            (Encryption::EncryptionNone, _) => None,
+            (Encryption::EncryptionX25519Chacha20Poly1305, Some((exchange, server_key))) => Some(
+                exchange
+                    .finish_client(&ack.public_key, &server_key)
+                    .map_err(|e| ClientError::Handshake(e.to_string()))?,
+            ),
+            (encryption, None) => {
+                return Err(ClientError::Handshake(format!(
+                    "encryption not offered:{:?}",
+                    encryption
+                )));
+            }
+        };
+        //客户端不压缩请求,只解压
+        *self.codec.lock().unwrap() = Codec::new(CodecOptions::default(), cipher);
+        Ok(ack)
+    }
+
+    //关闭连接,等待中的请求和握手返回关闭原因
+    fn shutdown(&self, kick: Option<KickNtf>) {
+        let Some(pending) = self.pending.lock().unwrap().take() else {
+            return;
+        };
+        let error = || match &kick {
+            Some(kick) => ClientError::Kicked(kick.clone()),
+            None => ClientError::Closed,
+        };
+        for (_, tx) in pending {
+            let _ = tx.send(Err(error()));
+        }
+        if let Some(handshaking) = self.handshaking.lock().unwrap().take() {
+            let _ = handshaking.tx.send(Err(error()));
+        }
+        if let Some(kick) = kick {
+            let _ = self.events.send(Event::Kick(kick));
+        }
+        let _ = self.events.send(Event::Closed);
+        self.handler.network().remove(self.endpoint.resource_id());
+        self.router.clients.lock().unwrap().remove(&self.endpoint);
+    }
+}
+
+//网关客户端,drop时关闭连接
+pub struct Client {
+    shared: Arc<Shared>,
+    config: ClientConfig,
+    ack: HandshakeAck,
+    resume: ResumeRsp,
+    _driver: Driver,
+}
+
+impl Client {
+    //使用单独的网络线程建立连接并完成握手,返回客户端和推送等事件的接收端
+    pub async fn connect(
+        config: ClientConfig,
+    ) -> Result<(Client, mpsc::UnboundedReceiver<Event>), ClientError> {
+        Self::connect_with(&Driver::new(), config).await
+    }
+
+    //在共用的网络线程上建立连接并完成握手
+    pub async fn connect_with(
+        driver: &Driver,
+        config: ClientConfig,
+    ) -> Result<(Client, mpsc::UnboundedReceiver<Event>), ClientError> {
+        let endpoint = driver.connect(&config).await?;
+        let kcp = config
+            .kcp
+            .as_ref()
+            .filter(|_| config.transport == Transport::Udp)
+            .map(|x| Mutex::new(Kcp::new(x, kcp::new_conv())));
+        let (events, receiver) = mpsc::unbounded_channel();
+        let shared = Arc::new(Shared {
+            handler: driver.handler.clone(),
+            router: driver.router.clone(),
+            endpoint,
+            codec: Mutex::new(Codec::default()),
+            kcp,
+            kcp_interval: Duration::from_millis(
+                config.kcp.as_ref().map_or(10, |x| x.interval.max(1)) as u64,
+            ),
+            handshaking: Mutex::new(None),
+            pending: Mutex::new(Some(HashMap::new())),
+            events,
+            seq: AtomicU32::new(0),
+            last_push_seq: AtomicU32::new(config.last_push_seq),
+            heartbeat_interval: AtomicU64::new(DEFAULT_HEARTBEAT_INTERVAL),
+            last_heartbeat: AtomicU64::new(now_ms()),
+        });
+        driver
+            .router
+            .clients
+            .lock()
+            .unwrap()
+            .insert(endpoint, shared.clone());
+        if shared.kcp.is_some() {
+            shared.handler.signals().send(Signal::KcpTick(endpoint));
+        }
+        //握手失败时drop关闭连接
+        let mut client = Client {
+            shared,
+            config,
+            ack: HandshakeAck::default(),
+            resume: ResumeRsp::default(),
+            _driver: driver.clone(),
+        };
+        client.ack = client.handshake().await?;
+        let interval = match client.ack.heartbeat_interval {
+            0 => DEFAULT_HEARTBEAT_INTERVAL,
+            x => x as u64,
+        };
+        client
+            .shared
+            .heartbeat_interval
+            .store(interval, Ordering::Relaxed);
+        client.shared.send_heartbeat();
+        client.shared.handler.signals().send_with_timer(
+            Signal::Heartbeat(client.shared.endpoint),
+            Duration::from_millis(interval),
+        );
+        //重连凭证在握手之后按协商结果收发,加密时不会明文出现
+        client.resume = client
+            .request(ResumeReq {
+                resume_token: client.config.resume_token.clone(),
+                last_push_seq: client.config.last_push_seq,
+            })
+            .await?;
+        tracing::info!(
+            "endpoint:{} connected session:{} resumed:{}",
+            client.shared.endpoint,
+            client.ack.session_id,
+            client.resume.resumed
+        );
+        Ok((client, receiver))
+    }
+
+    async fn handshake(&self) -> Result<HandshakeAck, ClientError> {
+        let config = &self.config;
+        let exchange = match (config.encryption, config.server_key) {
+            (true, Some(server_key)) => Some((KeyExchange::new(), server_key)),
+            (true, None) => {
+                return Err(ClientError::Handshake(
+                    "server key required for encryption".to_string(),
+                ));
+            }
+            (false, _) => None,
+        };
+        let req = HandshakeReq {
+            client_version: config.client_version.clone(),
+            platform: config.platform as i32,
+            compressions: match config.compression {
+                true => vec![Compression::CompressionLz4 as i32],
+                false => vec![],
+            },
+            encryptions: match exchange {
+                Some(_) => vec![Encryption::EncryptionX25519Chacha20Poly1305 as i32],
+                None => vec![],
+            },
+            public_key: exchange
+                .as_ref()
+                .map(|(x, _)| Bytes::copy_from_slice(&x.public_key()))
+                .unwrap_or_default(),
+        };
+        let (tx, rx) = oneshot::channel();
+        *self.shared.handshaking.lock().unwrap() = Some(Handshaking { exchange, tx });
+        self.shared.send(Packet::new_data(
+            Type::Handshake,
+            HandshakeReq::CMD,
+            req.encode_to_vec().into(),
+        ))?;
+        match tokio::time::timeout(config.handshake_timeout, rx).await {
+            Ok(Ok(result)) => result,
+            Ok(Err(_)) => Err(ClientError::Closed),
+            Err(_) => Err(ClientError::HandshakeTimeout),
+        }
+    }
+
+    //发送请求并等待对应的响应
+    pub async fn request<R: Request>(&self, req: R) -> Result<R::Response, ClientError> {
+        let (cmd, data) = self.call(R::CMD, req.encode_to_vec().into()).await?;
+        if cmd != R::RSP_CMD {
+            return Err(ClientError::UnexpectedCmd {
+                expected: R::RSP_CMD,
+                actual: cmd,
+            });
+        }
+        Ok(R::Response::decode(data)?)
+    }
+
+    //发送已编码的请求,返回响应的cmd和负载
+    pub async fn call(&self, cmd: i32, data: Bytes) -> Result<(i32, Bytes), ClientError> {
+        let seq = self.shared.next_seq();
+        let (tx, rx) = oneshot::channel();
+        match self.shared.pending.lock().unwrap().as_mut() {
+            Some(pending) => pending.insert(seq, tx),
+            None => return Err(ClientError::Closed),
+        };
+        if let Err(e) = self
+            .shared
+            .send(Packet::new_data(Type::Request, cmd, data).with_seq(seq))
+        {
+            self.remove_pending(seq);
+            return Err(e);
+        }
+        let packet = match tokio::time::timeout(self.config.request_timeout, rx).await {
+            Ok(Ok(result)) => result?,
+            Ok(Err(_)) => return Err(ClientError::Closed),
+            Err(_) => {
+                self.remove_pending(seq);
+                return Err(ClientError::Timeout { cmd, seq });
+            }
+        };
+        if packet.cmd == ErrorRsp::CMD {
+            let rsp = ErrorRsp::decode(packet.data)?;
+            return Err(ClientError::Rsp {
+                cmd: rsp.cmd,
+                code: rsp.code,
+                message: rsp.message,
+            });
+        }
+        Ok((packet.cmd, packet.data))
+    }
+
+    fn remove_pending(&self, seq: u32) {
+        if let Some(pending) = self.shared.pending.lock().unwrap().as_mut() {
+            pending.remove(&seq);
+        }
+    }
+
+    //发送通知,没有响应
+    pub fn notify<M: Message>(&self, cmd: i32, msg: M) -> Result<(), ClientError> {
+        if self.shared.is_closed() {
+            return Err(ClientError::Closed);
+        }
+        self.shared.send(Packet::new_data(
+            Type::Notify,
+            cmd,
+            msg.encode_to_vec().into(),
+        ))
+    }
+
+    //握手应答,包含会话id和协商结果
+    pub fn ack(&self) -> &HandshakeAck {
+        &self.ack
+    }
+
+    //重连应答,包含下次重连使用的凭证和是否恢复了之前的会话
+    pub fn resume(&self) -> &ResumeRsp {
+        &self.resume
+    }
+
+    //收到的最后一个推送序号,重连时与重连凭证一起带上
+    pub fn last_push_seq(&self) -> u32 {
+        self.shared.last_push_seq.load(Ordering::Relaxed)
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.shared.is_closed()
+    }
+
+    //可靠会话先发FIN,再关闭连接
+    pub fn close(&self) {
+        if self.shared.is_closed() {
+            return;
+        }
+        if let Some(kcp) = &self.shared.kcp {
+            let fin = kcp.lock().unwrap().fin();
+            let _ = self.shared.send_raw(&fin);
+        }
+        self.shared.shutdown(None);
+    }
+}
+
+impl Drop for Client {
+    fn drop(&mut self) {
+        self.close();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ClientConfig;
+    use common::config::GateServerConfig;
+    use lib::gate::crypto::ServerKey;
+    use lib::gate::handshake::negotiate;
+    use message_io::network::Transport as NetTransport;
+    use protocol::base_cmd::BaseError::ErrorUnknownCommand;
+    use protocol::base_cmd::KickReason;
+    use protocol::login_cmd::{LoginReq, LoginRsp};
+    use protocol::store_cmd::StoreInfoReq;
+
+    const SERVER_KEY: [u8; PUBLIC_KEY_LEN] = [7; PUBLIC_KEY_LEN];
+
+    //只处理一个连接的简易网关
+    fn serve() -> (NodeHandler<()>, NodeTask, String) {
+        let (handler, listener) = node::split::<()>();
+        let (_, addr) = handler
+            .network()
+            .listen(NetTransport::FramedTcp, "127.0.0.1:0")
+            .unwrap();
+        let config = GateServerConfig {
+            compress_threshold: Some(64),
+            ..Default::default()
+        };
+        let server_key = ServerKey::new(SERVER_KEY);
+        let network = handler.clone();
+        let mut codec = Codec::default();
+        let task = listener.for_each_async(move |event| {
+            let NodeEvent::Network(NetEvent::Message(endpoint, data)) = event else {
+                return;
+            };
+            let packet = codec.decode(data).unwrap();
+            let send = |codec: &mut Codec, packet: Packet| {
+                network.network().send(endpoint, &codec.encode(packet));
+            };
+            match packet.r#type {
+                Type::Handshake => {
+                    let req = HandshakeReq::decode(packet.data).unwrap();
+                    let (negotiated, cipher) = negotiate(&config, req, Some(&server_key)).unwrap();
+                    let ack = negotiated.ack().encode_to_vec();
+                    send(
+                        &mut codec,
+                        Packet::new_data(Type::HandshakeAck, HandshakeAck::CMD, ack.into()),
+                    );
+                    codec = Codec::new(negotiated.codec_options(&config), cipher);
+                }
+                Type::Heartbeat => send(&mut codec, Packet::new_control(Type::Heartbeat)),
+                Type::Request if packet.cmd == ResumeReq::CMD => {
+                    let rsp = ResumeRsp {
+                        resume_token: "token".to_string(),
+                        resumed: true,
+                    };
+                    send(
+                        &mut codec,
+                        Packet::new_data(
+                            Type::Response,
+                            ResumeRsp::CMD,
+                            rsp.encode_to_vec().into(),
+                        )
+                        .with_seq(packet.seq),
+                    );
+                    //重连应答后补发推送,重复的推送应被丢弃
+                    let data = Bytes::from(vec![7u8; 1024]);
+                    for seq in [1, 2, 2] {
+                        send(
+                            &mut codec,
+                            Packet::new_data(Type::Push, 9001, data.clone()).with_seq(seq),
+                        );
+                    }
+                }
+                Type::Request if packet.cmd == LoginReq::CMD => {
+                    let req = LoginReq::decode(packet.data).unwrap();
+                    let rsp = LoginRsp {
+                        player_id: req.server_id as i64 * 100,
+                    };
+                    send(
+                        &mut codec,
+                        Packet::new_data(Type::Response, LoginRsp::CMD, rsp.encode_to_vec().into())
+                            .with_seq(packet.seq),
+                    );
+                }
+                Type::Request => {
+                    let rsp = ErrorRsp {
+                        cmd: packet.cmd,
+                        code: ErrorUnknownCommand as i32,
+                        message: "unknown".to_string(),
+                    };
+                    send(
+                        &mut codec,
+                        Packet::new_data(Type::Response, ErrorRsp::CMD, rsp.encode_to_vec().into())
+                            .with_seq(packet.seq),
+                    );
+                }
+                Type::Notify => {
+                    let kick = KickNtf {
+                        reason: KickReason::KickMaintenance as i32,
+                        ..Default::default()
+                    };
+                    send(
+                        &mut codec,
+                        Packet::new_data(Type::Kick, KickNtf::CMD, kick.encode_to_vec().into()),
+                    );
+                }
+                _ => {}
+            }
+        });
+        (handler, task, addr.to_string())
+    }
+
+    #[tokio::test]
+    async fn request_push_and_kick() {
+        let (handler, _task, addr) = serve();
+        let mut config = ClientConfig::new(addr, Transport::Tcp);
+        config.encryption = true;
+        //未设置网关公钥时不能加密
+        assert!(matches!(
+            Client::connect(config.clone()).await,
+            Err(ClientError::Handshake(_))
+        ));
+        config.server_key = Some(ServerKey::new(SERVER_KEY).public_key());
+        let (client, mut events) = Client::connect(config).await.unwrap();
+        assert_eq!(
+            client.ack().encryption(),
+            Encryption::EncryptionX25519Chacha20Poly1305
+        );
+        assert_eq!(client.ack().compression(), Compression::CompressionLz4);
+        assert_eq!(client.resume().resume_token, "token");
+        assert!(client.resume().resumed);
+
+        let rsp = client
+            .request(LoginReq {
+                server_id: 3,
+                account: "test".to_string(),
+            })
+            .await
+            .unwrap();
+        assert_eq!(rsp.player_id, 300);
+        match client.request(StoreInfoReq { store_id: 1 }).await {
+            Err(ClientError::Rsp { cmd, code, .. }) => {
+                assert_eq!(cmd, StoreInfoReq::CMD);
+                assert_eq!(code, ErrorUnknownCommand as i32);
+            }
+            other => panic!("unexpected:{:?}", other),
+        }
+
+        for seq in [1, 2] {
+            match events.recv().await.unwrap() {
+                Event::Push { cmd, seq: s, data } => {
+                    assert_eq!((cmd, s, data.len()), (9001, seq, 1024));
+                }
+                other => panic!("unexpected:{:?}", other),
+            }
+        }
+        assert_eq!(client.last_push_seq(), 2);
+
+        client.notify(1, LoginReq::default()).unwrap();
+        match events.recv().await.unwrap() {
+            Event::Kick(kick) => assert_eq!(kick.reason(), KickReason::KickMaintenance),
+            other => panic!("unexpected:{:?}", other),
+        }
+        assert!(matches!(events.recv().await, Some(Event::Closed)));
+        assert!(matches!(
+            client.request(LoginReq::default()).await,
+            Err(ClientError::Closed)
+        ));
+        handler.stop();
+    }
+
+    //共用网络线程的连接按Endpoint分发,关闭一个不影响其他连接
+    #[tokio::test]
+    async fn shared_driver() {
+        let driver = Driver::new();
+        let servers = [serve(), serve()];
+        let mut clients = vec![];
+        for (_, _, addr) in &servers {
+            let mut config = ClientConfig::new(addr.clone(), Transport::Tcp);
+            config.encryption = true;
+            config.server_key = Some(ServerKey::new(SERVER_KEY).public_key());
+            clients.push(Client::connect_with(&driver, config).await.unwrap());
+        }
+        for (i, (client, _)) in clients.iter().enumerate() {
+            let req = LoginReq {
+                server_id: i as i32 + 1,
+                ..Default::default()
+            };
+            assert_eq!(
+                client.request(req).await.unwrap().player_id,
+                (i as i64 + 1) * 100
+            );
+        }
+        let (closed, _) = clients.remove(0);
+        closed.close();
+        let (client, _) = &clients[0];
+        assert_eq!(
+            client.request(LoginReq::default()).await.unwrap().player_id,
+            0
+        );
+        for (handler, _, _) in servers {
+            handler.stop();
+        }
+    }
+}
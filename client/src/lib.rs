@@ -0,0 +1,105 @@
+//! 网关协议的客户端,用于测试和压测
+//!
+//! - 支持 TCP/WS/UDP,UDP 可以开启可靠会话,帧格式见 [`lib::gate::packet`]
+//! - 连接后先握手,按服务端的心跳间隔自动维持心跳,心跳带回收到的最后一个推送序号
+//! - 请求按 seq 对应响应,[`Client::request`] 返回对应的响应类型,错误响应转为 [`ClientError::Rsp`]
+//! - 推送、踢下线和断开通过 [`Event`] 通道交给调用方
+//! - [`Client::connect`] 每个连接一个网络线程,大量连接时用 [`Client::connect_with`] 共用一个 [`Driver`]
+use bytes::Bytes;
+use common::config::KcpConfig;
+use lib::gate::crypto::PUBLIC_KEY_LEN;
+use protocol::base_cmd::{KickNtf, Platform};
+use std::time::Duration;
+use thiserror::Error;
+
+mod client;
+mod request;
+
+pub use client::{Client, Driver};
+pub use request::Request;
+
+//连接使用的传输层
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Transport {
+    Tcp,
+    Ws,
+    Udp,
+}
+
+#[derive(Debug, Clone)]
+pub struct ClientConfig {
+    pub addr: String,
+    pub transport: Transport,
+    //udp可靠会话,需要与网关的配置一致
+    pub kcp: Option<KcpConfig>,
+    pub client_version: String,
+    pub platform: Platform,
+    pub compression: bool,
+    pub encryption: bool,
+    //网关的长期公钥,开启加密时必须设置,握手时用来验证网关
+    pub server_key: Option<[u8; PUBLIC_KEY_LEN]>,
+    //断线重连凭证和收到的最后一个推送序号,首次连接为空
+    pub resume_token: String,
+    pub last_push_seq: u32,
+    pub handshake_timeout: Duration,
+    pub request_timeout: Duration,
+}
+
+impl ClientConfig {
+    pub fn new(addr: impl Into<String>, transport: Transport) -> Self {
+        Self {
+            addr: addr.into(),
+            transport,
+            kcp: None,
+            client_version: "1.0.0".to_string(),
+            platform: Platform::PlatformLinux,
+            compression: true,
+            encryption: false,
+            server_key: None,
+            resume_token: String::new(),
+            last_push_seq: 0,
+            handshake_timeout: Duration::from_secs(5),
+            request_timeout: Duration::from_secs(10),
+        }
+    }
+}
+
+//服务端发来的非响应消息
+#[derive(Debug, Clone)]
+pub enum Event {
+    Push { cmd: i32, seq: u32, data: Bytes },
+    //被踢下线,之后连接关闭
+    Kick(KickNtf),
+    Closed,
+}
+
+#[derive(Error, Debug)]
+pub enum ClientError {
+    #[error("io error:{0}")]
+    Io(#[from] std::io::Error),
+    #[error("send failed:{0}")]
+    Send(String),
+    #[error("decode error:{0}")]
+    Decode(#[from] lib::gate::packet::DecodeError),
+    #[error("protobuf decode error:{0}")]
+    Protobuf(#[from] prost::DecodeError),
+    #[error("handshake failed:{0}")]
+    Handshake(String),
+    #[error("handshake timeout")]
+    HandshakeTimeout,
+    #[error("cmd:{cmd} seq:{seq} timeout")]
+    Timeout { cmd: i32, seq: u32 },
+    //服务端返回的ErrorRsp
+    #[error("cmd:{cmd} error code:{code} msg:{message}")]
+    Rsp {
+        cmd: i32,
+        code: i32,
+        message: String,
+    },
+    #[error("unexpected response cmd expected:{expected} actual:{actual}")]
+    UnexpectedCmd { expected: i32, actual: i32 },
+    #[error("kicked reason:{} msg:{}", .0.reason, .0.message)]
+    Kicked(KickNtf),
+    #[error("connection closed")]
+    Closed,
+}
@@ -0,0 +1,31 @@
+use prost::Message;
+use protocol::base_cmd::{ResumeReq, ResumeRsp};
+use protocol::login_cmd::{LoginReq, LoginRsp, LogoutReq, LogoutRsp, RegisterReq, RegisterRsp};
+use protocol::store_cmd::{StoreInfoReq, StoreInfoRsp};
+
+//请求和对应的响应,cmd使用协议生成的常量
+pub trait Request: Message + Default {
+    const CMD: i32;
+    const RSP_CMD: i32;
+    type Response: Message + Default;
+}
+
+macro_rules! impl_request {
+    ($($req:ty => $rsp:ty),* $(,)?) => {
+        $(
+            impl Request for $req {
+                const CMD: i32 = <$req>::CMD;
+                const RSP_CMD: i32 = <$rsp>::CMD;
+                type Response = $rsp;
+            }
+        )*
+    };
+}
+
+impl_request!(
+    LoginReq => LoginRsp,
+    RegisterReq => RegisterRsp,
+    LogoutReq => LogoutRsp,
+    StoreInfoReq => StoreInfoRsp,
+    ResumeReq => ResumeRsp,
+);
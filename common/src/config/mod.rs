@@ -55,17 +55,23 @@ pub struct GlobalConfig {
     world: Vec<WorldServerConfig>,
     game: Vec<GameServerConfig>,
     center_in_address:String,
+    //停服流程的等待时间
+    #[serde(default)]
+    pub shutdown: ShutdownConfig,
 }
 
 impl From<&Args> for anyhow::Result<GlobalConfig> {
     fn from(value: &Args) -> Self {
         tracing::info!("conf file is : {}", &value.config);
         let config = fs::read_to_string(&value.config)?;
-        let result: GlobalConfig = toml::from_str(&config)?;
-        Ok(result)
+        GlobalConfig::from_toml(&config)
     }
 }
 impl GlobalConfig {
+    pub fn from_toml(content: &str) -> anyhow::Result<GlobalConfig> {
+        let result: GlobalConfig = toml::from_str(content)?;
+        Ok(result)
+    }
     pub fn find_gate_config(&self, id: u32) -> Option<GateServerConfig> {
         return self.gate.iter().find(|g| g.id == id).cloned();
     }
@@ -141,6 +147,15 @@ pub struct RedisConfig {
 pub struct DataConfig {
     pub r#type: ConfigType,
     pub source_type: ConfigSourceType,
+    //从source_type下载的文件缓存目录,下载失败时使用缓存
+    #[serde(default)]
+    pub cache_dir: Option<String>,
+    //下载失败且没有缓存时从本地目录读取
+    #[serde(default)]
+    pub local_dir: Option<String>,
+    //内存中保留的配置表版本数,包括当前版本,用于回滚
+    #[serde(default = "default_table_history")]
+    pub history: usize,
 }
 #[derive(Debug, Clone, Deserialize)]
 pub enum ConfigType {
@@ -188,6 +203,183 @@ pub struct GateServerConfig {
     pub out_tcp_port: Option<u16>,
     pub out_ws_port: Option<u16>,
     pub out_udp_port: Option<u16>,
+    //心跳超时时间(秒),超时未收到客户端消息则踢下线
+    #[serde(default = "default_heartbeat_timeout")]
+    pub heartbeat_timeout: u64,
+    //允许连接的最低客户端版本,不配置则不限制
+    pub min_client_version: Option<String>,
+    //负载达到该长度时压缩,不配置则不支持压缩
+    pub compress_threshold: Option<usize>,
+    //按监听开启加密,开启后客户端必须协商加密
+    #[serde(default)]
+    pub encrypt: EncryptConfig,
+    //udp监听的可靠传输,不开启时一个datagram就是一帧
+    #[serde(default)]
+    pub kcp: KcpConfig,
+    //断线后保留会话的时间(秒),期间客户端可凭resume token恢复,0表示不保留
+    #[serde(default = "default_resume_grace")]
+    pub resume_grace: u64,
+    //每个会话最多缓存的未确认推送数量
+    #[serde(default = "default_resume_buffer")]
+    pub resume_buffer: usize,
+    //单个连接的限流和单个ip的连接数限制
+    #[serde(default)]
+    pub rate_limit: RateLimitConfig,
+    //每个连接的消息队列
+    #[serde(default)]
+    pub mailbox: MailboxConfig,
+}
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct EncryptConfig {
+    #[serde(default)]
+    pub tcp: bool,
+    #[serde(default)]
+    pub ws: bool,
+    #[serde(default)]
+    pub udp: bool,
+    //网关的长期私钥,十六进制的32字节,例如用openssl rand -hex 32生成
+    //有监听开启加密时必须配置,客户端固定对应的公钥以验证网关
+    pub key: Option<String>,
+}
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct KcpConfig {
+    pub enable: bool,
+    //一个datagram的最大长度
+    pub mtu: usize,
+    //收发窗口(segment数)
+    pub snd_wnd: u16,
+    pub rcv_wnd: u16,
+    //刷新间隔(毫秒)
+    pub interval: u32,
+    //最小重传超时(毫秒)
+    pub min_rto: u32,
+    //被跳过几次ack后快速重传,0表示不快速重传
+    pub fast_resend: u32,
+    //关闭拥塞控制,只受收发窗口限制
+    pub no_cwnd: bool,
+    //一个segment重传多少次认为断线
+    pub dead_link: u32,
+    //等待发送和确认的segment上限,超过时按mailbox.overflow处理
+    pub snd_queue: usize,
+}
+impl Default for KcpConfig {
+    fn default() -> Self {
+        Self {
+            enable: false,
+            mtu: 1400,
+            snd_wnd: 128,
+            rcv_wnd: 128,
+            interval: 10,
+            min_rto: 30,
+            fast_resend: 2,
+            no_cwnd: false,
+            dead_link: 10,
+            snd_queue: 1024,
+        }
+    }
+}
+//速率为0表示不限制,burst为0时等于速率
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct RateLimitConfig {
+    pub policy: RateLimitPolicy,
+    pub packets_per_sec: u32,
+    pub packet_burst: u32,
+    pub bytes_per_sec: u32,
+    pub byte_burst: u32,
+    //按cmd单独限制
+    pub cmds: Vec<CmdRateLimit>,
+    //单个ip的最大连接数,0表示不限制
+    pub max_connections_per_ip: u32,
+    //Throttle策略延后处理和登录中排队的包的上限,超过时丢弃新收到的包
+    pub max_pending: usize,
+}
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        Self {
+            policy: RateLimitPolicy::Drop,
+            packets_per_sec: 0,
+            packet_burst: 0,
+            bytes_per_sec: 0,
+            byte_burst: 0,
+            cmds: vec![],
+            max_connections_per_ip: 0,
+            max_pending: 64,
+        }
+    }
+}
+#[derive(Debug, Clone, Deserialize)]
+pub struct CmdRateLimit {
+    pub cmd: i32,
+    pub per_sec: u32,
+    #[serde(default)]
+    pub burst: u32,
+}
+//超出限制时的处理
+#[derive(Debug, Clone, Copy, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum RateLimitPolicy {
+    //丢弃,请求返回ErrorRateLimited
+    #[default]
+    Drop,
+    //延后处理
+    Throttle,
+    //踢下线
+    Kick,
+}
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct MailboxConfig {
+    //队列长度,处理不过来时按overflow处理
+    pub size: usize,
+    pub overflow: MailboxOverflow,
+}
+impl Default for MailboxConfig {
+    fn default() -> Self {
+        Self {
+            size: 256,
+            overflow: MailboxOverflow::Drop,
+        }
+    }
+}
+#[derive(Debug, Clone, Copy, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum MailboxOverflow {
+    //丢弃新收到的包
+    #[default]
+    Drop,
+    //断开连接
+    Close,
+}
+//停服时先注销并踢下线玩家,等待drain_wait秒后保存数据并停止actor
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct ShutdownConfig {
+    //踢下线后等待进行中的请求处理完的时间(秒)
+    pub drain_wait: u64,
+    //等待actor处理完队列中消息的时间(秒),超时后强制停止
+    pub stop_timeout: u64,
+}
+impl Default for ShutdownConfig {
+    fn default() -> Self {
+        Self {
+            drain_wait: 3,
+            stop_timeout: 10,
+        }
+    }
+}
+fn default_heartbeat_timeout() -> u64 {
+    30
+}
+fn default_resume_grace() -> u64 {
+    60
+}
+fn default_resume_buffer() -> usize {
+    256
+}
+fn default_table_history() -> usize {
+    5
 }
 impl GateServerConfig {
     pub fn unique_name(&self) -> String {
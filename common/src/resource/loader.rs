@@ -0,0 +1,210 @@
+use super::s3::S3Client;
+use crate::config::{ConfigSourceType, DataConfig};
+use cfg::LubanError;
+use luban_lib::ByteBuf;
+use std::path::{Path, PathBuf};
+
+//按 S3 -> 缓存目录 -> 本地目录 的顺序读取 <table>.bytes
+pub struct TableLoader {
+    s3: Option<S3Client>,
+    cache_dir: Option<PathBuf>,
+    local_dir: Option<PathBuf>,
+}
+
+impl TableLoader {
+    pub fn new(config: &DataConfig) -> Self {
+        let s3 = match &config.source_type {
+            //没有配置endpoint时只读本地
+            ConfigSourceType::Aws { endpoint, .. } if endpoint.is_empty() => None,
+            ConfigSourceType::Aws {
+                access_key_id,
+                secret_access_key,
+                region,
+                bucket,
+                endpoint,
+            } => match S3Client::new(endpoint, bucket, region, access_key_id, secret_access_key) {
+                Ok(client) => Some(client),
+                Err(e) => {
+                    tracing::error!("config source disabled:{}", e);
+                    None
+                }
+            },
+        };
+        Self {
+            s3,
+            cache_dir: config.cache_dir.as_ref().map(PathBuf::from),
+            local_dir: config.local_dir.as_ref().map(PathBuf::from),
+        }
+    }
+
+    pub fn load(&self, name: &str) -> Result<ByteBuf, LubanError> {
+        let file = format!("{}.bytes", name);
+        let mut errors = vec![];
+        if let Some(s3) = &self.s3 {
+            match s3.get_object(&file) {
+                Ok(bytes) => {
+                    if let Some(dir) = &self.cache_dir {
+                        write_cache(dir, &file, &bytes);
+                    }
+                    return Ok(ByteBuf::new(bytes));
+                }
+                Err(e) => errors.push(e.to_string()),
+            }
+        }
+        for dir in [&self.cache_dir, &self.local_dir].into_iter().flatten() {
+            let path = dir.join(&file);
+            match std::fs::read(&path) {
+                Ok(bytes) => {
+                    if !errors.is_empty() {
+                        tracing::warn!(
+                            "{} loaded from {}, {}",
+                            file,
+                            path.display(),
+                            errors.join("; ")
+                        );
+                    }
+                    return Ok(ByteBuf::new(bytes));
+                }
+                Err(e) => errors.push(format!("{}: {}", path.display(), e)),
+            }
+        }
+        if errors.is_empty() {
+            errors.push("no source configured".to_string());
+        }
+        Err(LubanError::Loader(format!(
+            "{} {}",
+            file,
+            errors.join("; ")
+        )))
+    }
+}
+
+//先写临时文件再改名,读缓存时不会读到写了一半的文件
+fn write_cache(dir: &Path, file: &str, bytes: &[u8]) {
+    let tmp = dir.join(format!("{}.tmp", file));
+    let result = std::fs::create_dir_all(dir)
+        .and_then(|_| std::fs::write(&tmp, bytes))
+        .and_then(|_| std::fs::rename(&tmp, dir.join(file)));
+    if let Err(e) = result {
+        tracing::warn!("{} write cache to {} failed:{}", file, dir.display(), e);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::config::ConfigType;
+    use std::collections::HashMap;
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+
+    //本地的S3替身,检查签名头,按路径返回对象,truncated中的对象只发出一半
+    fn s3_stand_in(
+        objects: HashMap<&'static str, &'static [u8]>,
+        truncated: &'static str,
+    ) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let endpoint = format!("http://{}", listener.local_addr().unwrap());
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let mut request = vec![];
+                let mut buf = [0; 4096];
+                while !request.ends_with(b"\r\n\r\n") {
+                    let n = stream.read(&mut buf).unwrap();
+                    if n == 0 {
+                        break;
+                    }
+                    request.extend_from_slice(&buf[..n]);
+                }
+                let request = String::from_utf8_lossy(&request).to_string();
+                let path = request.split_whitespace().nth(1).unwrap_or_default();
+                let signed = request.lines().any(|x| {
+                    x.to_ascii_lowercase()
+                        .starts_with("authorization: aws4-hmac-sha256 credential=test-key/")
+                        && x.contains("/garage/s3/aws4_request")
+                        && x.contains("SignedHeaders=host;x-amz-content-sha256;x-amz-date")
+                });
+                let response = match objects.get(path) {
+                    Some(body) if signed => {
+                        let mut response =
+                            format!("HTTP/1.1 200 OK\r\nContent-Length: {}\r\n\r\n", body.len())
+                                .into_bytes();
+                        match path == truncated {
+                            true => response.extend_from_slice(&body[..body.len() / 2]),
+                            false => response.extend_from_slice(body),
+                        }
+                        response
+                    }
+                    Some(_) => {
+                        b"HTTP/1.1 403 Forbidden\r\n\r\n<Error><Code>AccessDenied</Code></Error>"
+                            .to_vec()
+                    }
+                    None => b"HTTP/1.1 404 Not Found\r\n\r\n<Error><Code>NoSuchKey</Code></Error>"
+                        .to_vec(),
+                };
+                stream.write_all(&response).unwrap();
+            }
+        });
+        endpoint
+    }
+
+    fn data_config(endpoint: String, dir: &Path) -> DataConfig {
+        DataConfig {
+            r#type: ConfigType::Bin,
+            source_type: ConfigSourceType::Aws {
+                access_key_id: "test-key".to_string(),
+                secret_access_key: "test-secret".to_string(),
+                region: "garage".to_string(),
+                bucket: "cfg".to_string(),
+                endpoint,
+            },
+            cache_dir: Some(dir.join("cache").to_string_lossy().to_string()),
+            local_dir: Some(dir.join("local").to_string_lossy().to_string()),
+            history: 5,
+        }
+    }
+
+    #[test]
+    fn load_with_fallback() {
+        let dir = std::env::temp_dir().join(format!("table_loader_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(dir.join("local")).unwrap();
+        std::fs::write(dir.join("local/item_tbitem.bytes"), b"local").unwrap();
+        std::fs::write(dir.join("local/local_only.bytes"), b"local only").unwrap();
+
+        std::fs::write(dir.join("local/shop_tbshop.bytes"), b"local shop").unwrap();
+
+        let endpoint = s3_stand_in(
+            HashMap::from([
+                ("/cfg/item_tbitem.bytes", b"remote".as_slice()),
+                ("/cfg/shop_tbshop.bytes", b"remote shop".as_slice()),
+            ]),
+            "/cfg/shop_tbshop.bytes",
+        );
+        let loader = TableLoader::new(&data_config(endpoint, &dir));
+        //下载成功后写入缓存
+        assert_eq!(loader.load("item_tbitem").unwrap().bytes, b"remote");
+        assert_eq!(
+            std::fs::read(dir.join("cache/item_tbitem.bytes")).unwrap(),
+            b"remote"
+        );
+        //S3上没有时使用本地目录
+        assert_eq!(loader.load("local_only").unwrap().bytes, b"local only");
+        //截断的内容不使用也不缓存
+        assert_eq!(loader.load("shop_tbshop").unwrap().bytes, b"local shop");
+        assert!(!dir.join("cache/shop_tbshop.bytes").exists());
+        match loader.load("missing") {
+            Err(LubanError::Loader(msg)) => assert!(msg.contains("NoSuchKey"), "{}", msg),
+            other => panic!("expected loader error got:{:?}", other.map(|x| x.bytes)),
+        }
+
+        //S3不可用时缓存优先于本地目录
+        let closed = TcpListener::bind("127.0.0.1:0").unwrap();
+        let endpoint = format!("http://{}", closed.local_addr().unwrap());
+        drop(closed);
+        let loader = TableLoader::new(&data_config(endpoint, &dir));
+        assert_eq!(loader.load("item_tbitem").unwrap().bytes, b"remote");
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
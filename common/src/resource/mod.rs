@@ -1,42 +1,304 @@
-use crate::config::ConfigSourceType;
-use arc_swap::{ArcSwap, ArcSwapAny, ArcSwapOption};
-use cfg::Tables;
-use crossbeam::atomic::AtomicCell;
-use crossbeam::epoch;
-use crossbeam::epoch::{Atomic, Owned, Shared};
-use luban_lib::ByteBuf;
-use std::ops::Deref;
-use std::path::PathBuf;
-use std::ptr;
-use std::sync::atomic::{AtomicPtr, Ordering};
-use std::sync::{Arc, OnceLock, RwLock, RwLockReadGuard};
-
-static TABLES: ArcSwapOption<Tables> = ArcSwapOption::const_empty();
-
-pub fn load(aws_config: &ConfigSourceType) {
+use crate::config::DataConfig;
+use arc_swap::ArcSwapOption;
+use cfg::{LubanError, Tables};
+use loader::TableLoader;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::fmt::{Display, Formatter};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+mod loader;
+mod s3;
+
+//一组配置表的版本
+//id在进程内每次加载新内容时加1,不同进程之间用hash比较
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TableVersion {
+    pub id: u64,
+    //所有表的名字和内容的sha256
+    pub hash: String,
+}
+impl Display for TableVersion {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_fmt(format_args!("{}:{}", self.id, self.hash))
+    }
+}
+
+//版本和配置表一起替换,读到的版本和配置表总是一致的
+struct Loaded {
+    version: TableVersion,
+    tables: Arc<Tables>,
+}
+
+//最近加载过的版本,最后一个是最新加载的
+struct History<T> {
+    entries: VecDeque<(TableVersion, T)>,
+    last_id: u64,
+}
+impl<T: Clone> History<T> {
+    const fn new() -> Self {
+        Self {
+            entries: VecDeque::new(),
+            last_id: 0,
+        }
+    }
+
+    //超过capacity时丢弃最早的版本,至少保留刚加入的版本
+    fn push(&mut self, hash: String, value: T, capacity: usize) -> TableVersion {
+        self.last_id += 1;
+        let version = TableVersion {
+            id: self.last_id,
+            hash,
+        };
+        self.entries.push_back((version.clone(), value));
+        while self.entries.len() > capacity.max(1) {
+            self.entries.pop_front();
+        }
+        version
+    }
+
+    //按hash前缀查找,前缀必须只匹配一个版本
+    fn find(&self, hash: &str) -> Result<(TableVersion, T), LubanError> {
+        let mut found = self
+            .entries
+            .iter()
+            .filter(|(version, _)| !hash.is_empty() && version.hash.starts_with(hash));
+        match (found.next(), found.next()) {
+            (Some((version, value)), None) => Ok((version.clone(), value.clone())),
+            (Some(_), Some(_)) => Err(LubanError::Unknown(format!(
+                "config version:{} is ambiguous",
+                hash
+            ))),
+            (None, _) => Err(LubanError::Unknown(format!(
+                "config version:{} not in history",
+                hash
+            ))),
+        }
+    }
+
+    fn versions(&self) -> Vec<TableVersion> {
+        self.entries.iter().map(|(x, _)| x.clone()).collect()
+    }
+}
+
+static TABLES: ArcSwapOption<Loaded> = ArcSwapOption::const_empty();
+//加载和回滚都持有这个锁,保证history和TABLES一起修改
+static HISTORY: Mutex<History<Arc<Tables>>> = Mutex::new(History::new());
+//同一进程的多个节点共用一份配置,一次重新加载请求只加载一次
+static RELOAD_REQUESTED: AtomicU64 = AtomicU64::new(0);
+//最近处理的请求和结果,同一请求的其他节点直接返回这个结果
+static LAST_RELOAD: Mutex<Option<(u64, Result<ReloadStatus, LubanError>)>> = Mutex::new(None);
+
+//重新加载的结果,失败时返回LubanError
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReloadStatus {
+    //加载了新的版本
+    Reloaded(TableVersion),
+    //内容没有变化,仍是当前版本
+    Unchanged(TableVersion),
+}
+
+pub fn load(config: &DataConfig) -> Result<TableVersion, LubanError> {
     tracing::info!("load_config....");
+    let mut history = HISTORY.lock().unwrap();
+    let (tables, hash) = _load(config)?;
+    Ok(store(&mut history, config, tables, hash))
+}
 
-    let tables = _load(aws_config);
-    TABLES.store(Some(Arc::new(tables)));
+//请求重新加载,之后由各节点调用reload
+pub fn request_reload() {
+    RELOAD_REQUESTED.fetch_add(1, Ordering::SeqCst);
 }
 
-pub fn reload(aws_config: &ConfigSourceType) {
+//加载并校验全部配置表,成功后整体替换,失败时保留旧的配置表
+//同一进程的节点共用一次请求的结果
+pub fn reload(config: &DataConfig) -> Result<ReloadStatus, LubanError> {
+    let mut history = HISTORY.lock().unwrap();
+    let requested = RELOAD_REQUESTED.load(Ordering::SeqCst);
+    let mut last = LAST_RELOAD.lock().unwrap();
+    if let Some((handled, result)) = last.as_ref()
+        && *handled >= requested
+    {
+        //已经由同一进程的其他节点处理
+        return result.clone();
+    }
     tracing::info!("reload....");
-    let new_tables = _load(aws_config);
-    TABLES.swap(Some(Arc::new(new_tables)));
-    tracing::info!("reloaded");
+    let result = _load(config).map(|(tables, hash)| {
+        if let Some(current) = current().filter(|x| x.hash == hash) {
+            tracing::info!("reloaded tables unchanged, version:{}", current);
+            return ReloadStatus::Unchanged(current);
+        }
+        let version = store(&mut history, config, tables, hash);
+        tracing::info!("reloaded version:{}", version);
+        ReloadStatus::Reloaded(version)
+    });
+    *last = Some((requested, result.clone()));
+    result
+}
+
+//回滚到history中的版本,hash可以只写前缀
+pub fn rollback(hash: &str) -> Result<TableVersion, LubanError> {
+    let history = HISTORY.lock().unwrap();
+    let (version, tables) = history.find(hash)?;
+    TABLES.store(Some(Arc::new(Loaded {
+        version: version.clone(),
+        tables,
+    })));
+    tracing::info!("rollback to version:{}", version);
+    Ok(version)
+}
+
+//当前使用的版本,还没有加载时为None
+pub fn current() -> Option<TableVersion> {
+    TABLES.load().as_ref().map(|x| x.version.clone())
+}
+
+//内存中保留的版本,从旧到新
+pub fn history() -> Vec<TableVersion> {
+    HISTORY.lock().unwrap().versions()
 }
 
 pub fn get() -> Arc<Tables> {
-    TABLES.load().clone().unwrap()
+    TABLES.load().as_ref().unwrap().tables.clone()
+}
+
+fn store(
+    history: &mut History<Arc<Tables>>,
+    config: &DataConfig,
+    tables: Tables,
+    hash: String,
+) -> TableVersion {
+    let tables = Arc::new(tables);
+    let version = history.push(hash, tables.clone(), config.history);
+    TABLES.store(Some(Arc::new(Loaded {
+        version: version.clone(),
+        tables,
+    })));
+    version
 }
-fn _load(aws_config: &ConfigSourceType) -> Tables {
+
+//返回配置表和内容的hash
+fn _load(config: &DataConfig) -> Result<(Tables, String), LubanError> {
+    let loader = TableLoader::new(config);
+    let hasher = RefCell::new(Sha256::new());
+    //数据不完整或枚举值非法时返回Decode错误
     let tables = Tables::new(|name| {
-        let path = PathBuf::from(format!(
-            "/home/cc/RustroverProjects/luban_examples/Projects/GenerateDatas/bytes/{}.bytes",
-            name
-        ));
-        Ok(ByteBuf::new(std::fs::read(path).unwrap()))
-    });
-    tables.expect("luban err")
+        let buf = loader.load(name)?;
+        let mut hasher = hasher.borrow_mut();
+        hasher.update(name.as_bytes());
+        hasher.update((buf.bytes.len() as u64).to_le_bytes());
+        hasher.update(&buf.bytes);
+        Ok(buf)
+    })?;
+    Ok((tables, hex::encode(hasher.into_inner().finalize())))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn reload_keeps_old_tables_on_error() {
+        request_reload();
+        //没有配置任何来源
+        let config = DataConfig::default();
+        let err = reload(&config).unwrap_err();
+        assert!(matches!(err, LubanError::Loader(_)));
+        assert_eq!(current(), None);
+        assert!(history().is_empty());
+        //同一请求的其他节点得到同样的失败,不再重复加载
+        let config = DataConfig {
+            local_dir: Some("/nonexistent".to_string()),
+            ..Default::default()
+        };
+        let other = _load(&config).err().unwrap().to_string();
+        assert_ne!(other, err.to_string());
+        assert_eq!(reload(&config).unwrap_err().to_string(), err.to_string());
+    }
+
+    #[test]
+    fn truncated_table() {
+        let dir = std::env::temp_dir().join(format!("truncated_table_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        //1行数据,但是没有行的内容
+        std::fs::write(dir.join("ai_tbblackboard.bytes"), [1]).unwrap();
+        let config = DataConfig {
+            local_dir: Some(dir.to_string_lossy().to_string()),
+            ..Default::default()
+        };
+        match _load(&config) {
+            Err(LubanError::Decode { table, offset, .. }) => {
+                assert_eq!((table.as_str(), offset), ("ai_tbblackboard", 1));
+            }
+            other => panic!("expected decode error got:{:?}", other.map(|x| x.1)),
+        }
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn fixture_table() {
+        //不依赖Luban工具生成测试用的表
+        let mut buf = luban_lib::ByteBuf::with_capacity(64);
+        buf.write_size(2);
+        for (id, name) in [(1001, "金币"), (1002, "钻石")] {
+            buf.write_int(id);
+            buf.write_string(name);
+            buf.write_int(1); //major_type CURRENCY
+            buf.write_int(101); //minor_type DIAMOND
+            buf.write_int(9999);
+            buf.write_int(0); //quality WHITE
+            for _ in 0..4 {
+                buf.write_string("");
+            }
+            buf.write_int(id - 1000);
+        }
+        let table = cfg::item::TbItem::new(buf).unwrap();
+        assert_eq!(table.data_list.len(), 2);
+        assert_eq!(table.get(&1002).unwrap().name, "钻石");
+        assert_eq!(table.get(&1001).unwrap().show_order, 1);
+
+        //非法的枚举值返回Decode错误,offset是枚举值的位置
+        let mut buf = luban_lib::ByteBuf::with_capacity(16);
+        buf.write_size(1);
+        buf.write_int(1001);
+        buf.write_string("金币");
+        let offset = buf.size();
+        buf.write_int(99);
+        match cfg::item::TbItem::new(buf) {
+            Err(LubanError::Decode {
+                offset: x, message, ..
+            }) => {
+                assert_eq!(x, offset);
+                assert!(message.contains("EMajorType"), "{}", message);
+            }
+            other => panic!("expected decode error got:{:?}", other.err()),
+        }
+    }
+
+    #[test]
+    fn history_rollback() {
+        let mut history = History::new();
+        for (hash, value) in [("aa01", 1), ("ab02", 2), ("bb03", 3), ("cc04", 4)] {
+            history.push(hash.to_string(), value, 3);
+        }
+        //超过容量丢弃最早的版本
+        let ids: Vec<_> = history.versions().iter().map(|x| x.id).collect();
+        assert_eq!(ids, vec![2, 3, 4]);
+        assert!(history.find("aa01").is_err());
+
+        let (version, value) = history.find("ab").unwrap();
+        assert_eq!((version.id, value), (2, 2));
+        assert_eq!(history.find("cc04").unwrap().1, 4);
+        //前缀为空或者匹配多个版本时不能回滚
+        history.push("ab05".to_string(), 5, 4);
+        assert!(history.find("ab").is_err());
+        assert!(history.find("").is_err());
+        assert_eq!(history.find("ab05").unwrap().0.id, 5);
+        //容量为0时也保留当前版本
+        history.push("dd06".to_string(), 6, 0);
+        assert_eq!(history.versions().len(), 1);
+    }
 }
@@ -0,0 +1,230 @@
+use cfg::LubanError;
+use hmac::{Hmac, Mac};
+use sha2::{Digest, Sha256};
+use std::io::Read;
+use std::time::Duration;
+use time::OffsetDateTime;
+
+const TIMEOUT: Duration = Duration::from_secs(10);
+//空请求体的sha256
+const EMPTY_SHA256: &str = "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855";
+//单个对象的大小上限,配置表一般不超过几MB
+const MAX_OBJECT_SIZE: u64 = 64 * 1024 * 1024;
+
+//S3兼容存储(Garage等)的只读客户端,使用path-style地址和SigV4签名
+//没有可用的证书根,只支持内网的http地址;响应必须带Content-Length,收到的长度不一致时报错,不会缓存截断的内容
+pub struct S3Client {
+    //Host头,与endpoint中的一致,默认端口80省略
+    host: String,
+    bucket: String,
+    region: String,
+    access_key_id: String,
+    secret_access_key: String,
+}
+
+impl S3Client {
+    pub fn new(
+        endpoint: &str,
+        bucket: &str,
+        region: &str,
+        access_key_id: &str,
+        secret_access_key: &str,
+    ) -> Result<Self, LubanError> {
+        if endpoint.starts_with("https://") {
+            return Err(LubanError::Loader(format!(
+                "unsupported endpoint:{}, tls is not available, use an http endpoint",
+                endpoint
+            )));
+        }
+        let host = endpoint
+            .strip_prefix("http://")
+            .ok_or_else(|| LubanError::Loader(format!("invalid endpoint:{}", endpoint)))?
+            .split('/')
+            .next()
+            .unwrap_or_default();
+        if host.is_empty() {
+            return Err(LubanError::Loader(format!("invalid endpoint:{}", endpoint)));
+        }
+        //http客户端按url生成Host头,默认端口不带上,签名时保持一致
+        let host = host.strip_suffix(":80").unwrap_or(host).to_string();
+        Ok(Self {
+            host,
+            bucket: bucket.to_string(),
+            region: region.to_string(),
+            access_key_id: access_key_id.to_string(),
+            secret_access_key: secret_access_key.to_string(),
+        })
+    }
+
+    pub fn get_object(&self, key: &str) -> Result<Vec<u8>, LubanError> {
+        let path = format!("/{}/{}", uri_encode(&self.bucket), uri_encode(key));
+        let err = |e: String| LubanError::Loader(format!("s3 {}{}: {}", self.host, path, e));
+        //签名的请求不跟随重定向
+        let mut request = attohttpc::get(format!("http://{}{}", self.host, path))
+            .follow_redirects(false)
+            .connect_timeout(TIMEOUT)
+            .read_timeout(TIMEOUT);
+        for (name, value) in self.sign("GET", &path, OffsetDateTime::now_utc()) {
+            request = request.header(name, value);
+        }
+        let (status, headers, reader) = request.send().map_err(|e| err(e.to_string()))?.split();
+        let content_length = headers
+            .get("content-length")
+            .and_then(|x| x.to_str().ok())
+            .and_then(|x| x.trim().parse::<u64>().ok());
+        if content_length.is_some_and(|x| x > MAX_OBJECT_SIZE) {
+            return Err(err(format!(
+                "content-length:{} larger than {}",
+                content_length.unwrap_or_default(),
+                MAX_OBJECT_SIZE
+            )));
+        }
+        //多读一个字节,判断是否超过上限
+        let mut body = vec![];
+        reader
+            .take(MAX_OBJECT_SIZE + 1)
+            .read_to_end(&mut body)
+            .map_err(|e| err(e.to_string()))?;
+        if !status.is_success() {
+            return Err(err(format!(
+                "status:{} {}",
+                status.as_u16(),
+                error_code(&body)
+            )));
+        }
+        check_body(content_length, body).map_err(err)
+    }
+
+    //返回需要带上的请求头,包括Authorization
+    fn sign(&self, method: &str, path: &str, now: OffsetDateTime) -> Vec<(&'static str, String)> {
+        let date = format!("{:04}{:02}{:02}", now.year(), now.month() as u8, now.day());
+        let amz_date = format!(
+            "{}T{:02}{:02}{:02}Z",
+            date,
+            now.hour(),
+            now.minute(),
+            now.second()
+        );
+        let signed_headers = "host;x-amz-content-sha256;x-amz-date";
+        let canonical_request = format!(
+            "{}\n{}\n\nhost:{}\nx-amz-content-sha256:{}\nx-amz-date:{}\n\n{}\n{}",
+            method, path, self.host, EMPTY_SHA256, amz_date, signed_headers, EMPTY_SHA256
+        );
+        let scope = format!("{}/{}/s3/aws4_request", date, self.region);
+        let string_to_sign = format!(
+            "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+            amz_date,
+            scope,
+            hex::encode(Sha256::digest(canonical_request.as_bytes()))
+        );
+        let key = signing_key(&self.secret_access_key, &date, &self.region, "s3");
+        let signature = hex::encode(hmac(&key, string_to_sign.as_bytes()));
+        vec![
+            ("Host", self.host.clone()),
+            ("x-amz-content-sha256", EMPTY_SHA256.to_string()),
+            ("x-amz-date", amz_date),
+            (
+                "Authorization",
+                format!(
+                    "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+                    self.access_key_id, scope, signed_headers, signature
+                ),
+            ),
+        ]
+    }
+}
+
+fn hmac(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("hmac accepts any key length");
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
+
+fn signing_key(secret: &str, date: &str, region: &str, service: &str) -> Vec<u8> {
+    let key = hmac(format!("AWS4{}", secret).as_bytes(), date.as_bytes());
+    let key = hmac(&key, region.as_bytes());
+    let key = hmac(&key, service.as_bytes());
+    hmac(&key, b"aws4_request")
+}
+
+//除了unreserved字符都要编码,key中的/保留
+fn uri_encode(s: &str) -> String {
+    let mut encoded = String::with_capacity(s.len());
+    for b in s.bytes() {
+        match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' => {
+                encoded.push(b as char)
+            }
+            _ => encoded.push_str(&format!("%{:02X}", b)),
+        }
+    }
+    encoded
+}
+
+//错误信息在xml的Code中
+fn error_code(body: &[u8]) -> String {
+    let message = String::from_utf8_lossy(body);
+    message
+        .split_once("<Code>")
+        .and_then(|(_, x)| x.split_once("</Code>"))
+        .map_or("", |(x, _)| x)
+        .to_string()
+}
+
+//连接提前关闭时body会被截断,长度必须与Content-Length一致
+fn check_body(content_length: Option<u64>, body: Vec<u8>) -> Result<Vec<u8>, String> {
+    if body.len() as u64 > MAX_OBJECT_SIZE {
+        return Err(format!("object larger than {}", MAX_OBJECT_SIZE));
+    }
+    match content_length {
+        Some(len) if len == body.len() as u64 => Ok(body),
+        Some(len) => Err(format!(
+            "body truncated, content-length:{} received:{}",
+            len,
+            body.len()
+        )),
+        None => Err("missing content-length".to_string()),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn signing_key_vector() {
+        //AWS文档中的示例
+        let key = signing_key(
+            "wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY",
+            "20120215",
+            "us-east-1",
+            "iam",
+        );
+        assert_eq!(
+            hex::encode(key),
+            "f4780e2d9f65fa895f9c67b32ce1baf0b0d8a43505a000a1a9e090d414db404d"
+        );
+    }
+
+    #[test]
+    fn endpoint() {
+        let client = S3Client::new("http://localhost:3900/", "cfg", "garage", "a", "b").unwrap();
+        assert_eq!(client.host, "localhost:3900");
+        let client = S3Client::new("http://minio.local:80", "cfg", "garage", "a", "b").unwrap();
+        assert_eq!(client.host, "minio.local");
+        assert!(S3Client::new("https://s3.amazonaws.com", "cfg", "us-east-1", "a", "b").is_err());
+        assert_eq!(uri_encode("a b/c+d.bytes"), "a%20b/c%2Bd.bytes");
+    }
+
+    #[test]
+    fn body_length() {
+        assert_eq!(check_body(Some(3), b"abc".to_vec()).unwrap(), b"abc");
+        //截断或没有长度的内容不能返回
+        assert!(check_body(Some(4), b"abc".to_vec()).is_err());
+        assert!(check_body(None, b"abc".to_vec()).is_err());
+        assert_eq!(
+            error_code(b"<Error><Code>NoSuchKey</Code></Error>"),
+            "NoSuchKey"
+        );
+    }
+}
@@ -0,0 +1,88 @@
+use crate::node::{reload_config, rollback_config};
+use kameo::actor::ActorRef;
+use kameo::message::{Context, Message};
+use kameo::{Actor, RemoteActor, remote_message};
+use libp2p_identity::PeerId;
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+
+//每个进程一个,接收运维指令并转为本进程的Signal
+#[derive(RemoteActor)]
+pub struct AdminActor {
+    peer_id: PeerId,
+}
+
+impl AdminActor {
+    pub fn new(peer_id: PeerId) -> Self {
+        Self { peer_id }
+    }
+
+    //注册的名字,按进程的peer_id区分
+    pub fn name(peer_id: &PeerId) -> String {
+        format!("admin-{}", peer_id)
+    }
+}
+
+impl Actor for AdminActor {
+    type Error = AdminActorError;
+
+    async fn on_start(&mut self, actor_ref: ActorRef<Self>) -> Result<(), Self::Error> {
+        actor_ref
+            .register(&Self::name(&self.peer_id))
+            .await
+            .map_err(|e| {
+                tracing::error!("AdminActor register remote fail:{}", e);
+                AdminActorError::RegisterRemoteFail(e.to_string())
+            })?;
+        Ok(())
+    }
+}
+
+#[derive(Deserialize, Serialize)]
+pub enum AdminMessage {
+    //重新加载配置表,返回是否已通知节点,停服中不再重新加载
+    ReloadConfig,
+    //回滚到内存中保留的版本,hash可以只写前缀,返回是否回滚成功
+    RollbackConfig { hash: String },
+}
+#[remote_message("AdminMessage")]
+impl Message<AdminMessage> for AdminActor {
+    type Reply = bool;
+
+    async fn handle(
+        &mut self,
+        msg: AdminMessage,
+        _ctx: &mut Context<Self, Self::Reply>,
+    ) -> Self::Reply {
+        match msg {
+            AdminMessage::ReloadConfig => {
+                tracing::info!("admin reload config");
+                reload_config()
+            }
+            AdminMessage::RollbackConfig { hash } => match rollback_config(&hash) {
+                Ok(version) => {
+                    tracing::info!("admin rollback config to:{}", version);
+                    true
+                }
+                Err(e) => {
+                    tracing::error!("admin rollback config:{} failed:{}", hash, e);
+                    false
+                }
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum AdminActorError {
+    RegisterRemoteFail(String),
+}
+impl Display for AdminActorError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AdminActorError::RegisterRemoteFail(x) => {
+                f.write_fmt(format_args!("RegisterRemoteFail reason:{}", x))
+            }
+        }
+    }
+}
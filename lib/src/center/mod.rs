@@ -1,15 +1,18 @@
+use crate::admin::{AdminActor, AdminMessage};
 use crate::game::{GameActor, GameActorError};
 use crate::gate::GateActor;
 use crate::login::node::LoginActor;
 use crate::world::WorldActor;
 use common::config::{GameServerConfig, GlobalConfig, ServerRole, ServerRoleId};
+use common::resource::TableVersion;
 use kameo::actor::{ActorID, ActorRef, RemoteActorRef, WeakActorRef};
 use kameo::error::{ActorStopReason, RegistryError};
 use kameo::message::{Context, Message};
+use kameo::prelude::ActorSwarm;
 use kameo::{Actor, RemoteActor, remote_message};
 use libp2p_identity::PeerId;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fmt::{Display, Formatter};
 use std::marker::PhantomData;
 use std::ops::ControlFlow;
@@ -40,6 +43,8 @@ struct Node {
 pub struct CenterActor {
     _global_config: Arc<GlobalConfig>,
     node_container: NodeContainer,
+    //各节点上报的配置表版本,key为server_role_id
+    config_versions: HashMap<String, TableVersion>,
 }
 macro_rules! handle_register {
     ($ActorType:ty,$map_name:ident,$peer_id:ident,$server_role:ident,$node:ident,$self:ident,$actor_ref:ident) => {
@@ -88,6 +93,7 @@ impl CenterActor {
         Self {
             _global_config: global_config,
             node_container: NodeContainer::default(),
+            config_versions: HashMap::new(),
         }
     }
 
@@ -151,6 +157,53 @@ impl CenterActor {
             _ => {}
         }
     }
+    //每个进程只通知一次,包括Center所在的进程
+    async fn notify_admin(&self, message: &AdminMessage) {
+        let mut peers: HashSet<PeerId> = self.node_container.peer_map.keys().cloned().collect();
+        if let Some(actor_swarm) = ActorSwarm::get() {
+            peers.insert(*actor_swarm.local_peer_id());
+        }
+        for peer_id in peers {
+            let name = AdminActor::name(&peer_id);
+            match RemoteActorRef::<AdminActor>::lookup(&name).await {
+                Ok(Some(actor_ref)) => {
+                    if let Err(e) = actor_ref.tell(message).await {
+                        tracing::error!("peer_id:{} admin error:{}", peer_id, e);
+                    }
+                }
+                Ok(None) => tracing::error!("peer_id:{} admin not found", peer_id),
+                Err(e) => tracing::error!("peer_id:{} admin error:{}", peer_id, e),
+            }
+        }
+    }
+    //记录节点的配置表版本,hash不一致时报警
+    fn config_version(&mut self, server_role_id: ServerRoleId, version: Option<TableVersion>) {
+        let key = server_role_id.to_string();
+        match version {
+            Some(version) => {
+                tracing::info!("Actor:{} config version:{}", key, version);
+                self.config_versions.insert(key, version);
+            }
+            None => {
+                tracing::warn!("Actor:{} config not loaded", key);
+                self.config_versions.remove(&key);
+            }
+        }
+        let hashes: HashSet<&String> = self.config_versions.values().map(|x| &x.hash).collect();
+        if hashes.len() > 1 {
+            tracing::warn!("config skew:{:?}", self.sorted_config_versions());
+        }
+    }
+    //按节点排序
+    fn sorted_config_versions(&self) -> Vec<(String, TableVersion)> {
+        let mut versions: Vec<(String, TableVersion)> = self
+            .config_versions
+            .iter()
+            .map(|(key, version)| (key.clone(), version.clone()))
+            .collect();
+        versions.sort_by(|a, b| a.0.cmp(&b.0));
+        versions
+    }
     pub(crate) fn unregister(&mut self, server_role_id: ServerRoleId, peer_id: PeerId) {
         tracing::info!("Actor:{:?} peer_id:{} unregister", server_role_id, peer_id);
         self.node_container
@@ -158,6 +211,7 @@ impl CenterActor {
             .entry(peer_id)
             .or_insert_with(|| HashMap::new())
             .remove(&server_role_id.to_string());
+        self.config_versions.remove(&server_role_id.to_string());
         let role = server_role_id.0.clone();
         self.node_container
             .role_map
@@ -251,6 +305,17 @@ pub enum CenterMessage {
         server_role_id: ServerRoleId,
         peer_id: PeerId,
     },
+    //通知集群所有进程重新加载配置表
+    ReloadConfig,
+    //通知集群所有进程回滚到hash对应的版本
+    RollbackConfig {
+        hash: String,
+    },
+    //节点上报当前的配置表版本
+    ConfigVersion {
+        server_role_id: ServerRoleId,
+        version: Option<TableVersion>,
+    },
 }
 #[remote_message("CenterMessage")]
 impl Message<CenterMessage> for CenterActor {
@@ -275,6 +340,19 @@ impl Message<CenterMessage> for CenterActor {
             } => {
                 self.unregister(server_role_id, peer_id);
             }
+            CenterMessage::ReloadConfig => {
+                self.notify_admin(&AdminMessage::ReloadConfig).await;
+            }
+            CenterMessage::RollbackConfig { hash } => {
+                self.notify_admin(&AdminMessage::RollbackConfig { hash })
+                    .await;
+            }
+            CenterMessage::ConfigVersion {
+                server_role_id,
+                version,
+            } => {
+                self.config_version(server_role_id, version);
+            }
         };
     }
 }
@@ -282,6 +360,7 @@ impl Message<CenterMessage> for CenterActor {
 pub enum SearchServerMessage {
     Ask { server_role: ServerRole },
     AskById { server_role_id: ServerRoleId },
+    AskByKey { server_role: ServerRole, key: i64 },
 }
 #[remote_message("SearchServerMessage")]
 impl Message<SearchServerMessage> for CenterActor {
@@ -334,6 +413,61 @@ impl Message<SearchServerMessage> for CenterActor {
                     unreachable!("not support server");
                 }
             },
+            SearchServerMessage::AskByKey { server_role, key } => {
+                let option = self.node_container.role_map.get(&server_role);
+                if let Some(node_map) = option {
+                    let mut ids: Vec<u32> =
+                        node_map.values().map(|x| x.server_role_id.1).collect();
+                    if ids.is_empty() {
+                        return "".to_string();
+                    }
+                    ids.sort();
+                    let id = ids[key.rem_euclid(ids.len() as i64) as usize];
+                    return ServerRoleId(server_role, id).to_string();
+                }
+                "".to_string()
+            }
         }
     }
 }
+//查询所有节点上报的配置表版本,按节点排序
+#[derive(Deserialize, Serialize)]
+pub struct ConfigVersionsMessage;
+#[remote_message("ConfigVersionsMessage")]
+impl Message<ConfigVersionsMessage> for CenterActor {
+    type Reply = Vec<(String, TableVersion)>;
+
+    async fn handle(
+        &mut self,
+        _msg: ConfigVersionsMessage,
+        _ctx: &mut Context<Self, Self::Reply>,
+    ) -> Self::Reply {
+        self.sorted_config_versions()
+    }
+}
+//查询某类型的所有节点
+#[derive(Deserialize, Serialize)]
+pub struct SearchAllServerMessage {
+    pub server_role: ServerRole,
+}
+#[remote_message("SearchAllServerMessage")]
+impl Message<SearchAllServerMessage> for CenterActor {
+    type Reply = Vec<ServerRoleId>;
+
+    async fn handle(
+        &mut self,
+        msg: SearchAllServerMessage,
+        _ctx: &mut Context<Self, Self::Reply>,
+    ) -> Self::Reply {
+        self.node_container
+            .role_map
+            .get(&msg.server_role)
+            .map(|node_map| {
+                node_map
+                    .values()
+                    .map(|x| x.server_role_id.clone())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
@@ -1,6 +1,7 @@
 use backon::ExponentialBuilder;
+use crate::center::{CenterActor, SearchAllServerMessage, SearchServerMessage};
 use crate::game::GameActor;
-use crate::gate::GateActor;
+use crate::gate::{GateActor, GateMessage, KickTarget, PushTarget};
 use crate::login::node::LoginActor;
 use crate::world::WorldActor;
 use crate::{DataError, ServerMessage};
@@ -10,7 +11,12 @@ use common::config::{ServerRole, ServerRoleId};
 use dashmap::DashMap;
 use kameo::actor::RemoteActorRef;
 use kameo::error::RemoteSendError;
+use protocol::base_cmd::BaseError::ErrorServerNotFound;
+use protocol::base_cmd::KickReason;
+use std::str::FromStr;
+use std::sync::RwLock;
 use std::time::Duration;
+use tokio::sync::OnceCell;
 use crossbeam::queue::SegQueue;
 use kameo::Actor;
 use lazy_static::lazy_static;
@@ -38,30 +44,42 @@ pub struct NodeManager {
     world_nodes: DashMap<ServerRoleId, RemoteActorRef<WorldActor>>,
     game_nodes: DashMap<ServerRoleId, RemoteActorRef<GameActor>>,
     gate_nodes: DashMap<ServerRoleId, RemoteActorRef<GateActor>>,
+    center: OnceCell<RemoteActorRef<CenterActor>>,
+    //登录请求不绑定玩家,选中的登录节点缓存到不可达为止
+    login: RwLock<Option<ServerRoleId>>,
 }
 macro_rules! find_node {
-    ($name:ident,$ActorType:ty) => {
+    ($name:ident,$ActorType:ty,$cache:ident) => {
         impl NodeManager {
-            pub async fn $name(node_name: &str) -> anyhow::Result<RemoteActorRef<$ActorType>> {
-                Ok(RemoteActorRef::<$ActorType>::lookup(&node_name)
+            pub async fn $name(
+                &self,
+                role_id: &ServerRoleId,
+            ) -> anyhow::Result<RemoteActorRef<$ActorType>> {
+                if let Some(actor_ref) = self.$cache.get(role_id) {
+                    return Ok(actor_ref.clone());
+                }
+                //节点还没注册到集群,可以重试
+                let actor_ref = RemoteActorRef::<$ActorType>::lookup(&role_id.to_string())
                     .await?
-                    .unwrap())
+                    .ok_or_else(|| anyhow::anyhow!("retryable"))?;
+                self.$cache.insert(role_id.clone(), actor_ref.clone());
+                Ok(actor_ref)
             }
         }
     };
 }
-find_node!(find_login_node, LoginActor);
-find_node!(find_world_node, WorldActor);
-find_node!(find_game_node, GameActor);
-find_node!(find_gate_node, GateActor);
+find_node!(find_login_node, LoginActor, login_nodes);
+find_node!(find_world_node, WorldActor, world_nodes);
+find_node!(find_game_node, GameActor, game_nodes);
+find_node!(find_gate_node, GateActor, gate_nodes);
 
 macro_rules! get_with_retry {
-    ($name:ident,$role_id:ident) => {
-        (|| async { NodeManager::$name(&$role_id).await })
+    ($self:ident,$name:ident,$role_id:ident) => {
+        (|| async { $self.$name(&$role_id).await })
             .retry(ExponentialBuilder::new().with_max_delay(Duration::from_millis(500)))
             .when(|e| e.to_string() == "retryable")
             .await
-            .map_err(|e| DataError::Other(e.to_string()))?
+            .map_err(|e| DataError::Unreachable(e.to_string()))?
     };
 }
 
@@ -72,38 +90,290 @@ impl NodeManager {
             world_nodes: DashMap::new(),
             game_nodes: DashMap::new(),
             gate_nodes: DashMap::new(),
+            center: OnceCell::new(),
+            login: RwLock::new(None),
         }
     }
+
+    async fn center(&self) -> Result<&RemoteActorRef<CenterActor>, DataError> {
+        self.center
+            .get_or_try_init(|| async {
+                RemoteActorRef::<CenterActor>::lookup(&ServerRole::Center.to_string())
+                    .await
+                    .map_err(|e| DataError::Other(e.to_string()))?
+                    .ok_or_else(|| DataError::Other("center not found".to_string()))
+            })
+            .await
+    }
+
+    //向Center查询一个可用节点
+    pub async fn select(&self, server_role: ServerRole) -> Result<ServerRoleId, DataError> {
+        let role_id = self
+            .center()
+            .await?
+            .ask(&SearchServerMessage::Ask {
+                server_role: server_role.clone(),
+            })
+            .await
+            .map_err(|e| DataError::Other(e.to_string()))?;
+        if role_id.is_empty() {
+            return Err(DataError::RspError(
+                ErrorServerNotFound as i32,
+                format!("{} not found", server_role),
+            ));
+        }
+        ServerRoleId::from_str(&role_id).map_err(DataError::Other)
+    }
+
+    //登录节点,没有缓存时向Center查询
+    pub async fn select_login(&self) -> Result<ServerRoleId, DataError> {
+        if let Some(role_id) = self.login.read().unwrap().clone() {
+            return Ok(role_id);
+        }
+        let role_id = self.select(ServerRole::Login).await?;
+        *self.login.write().unwrap() = Some(role_id.clone());
+        Ok(role_id)
+    }
+
+    //按key向Center查询固定的节点,节点列表不变时同一个key总是落在同一个节点
+    pub async fn select_by_key(
+        &self,
+        server_role: ServerRole,
+        key: i64,
+    ) -> Result<ServerRoleId, DataError> {
+        let role_id = self
+            .center()
+            .await?
+            .ask(&SearchServerMessage::AskByKey {
+                server_role: server_role.clone(),
+                key,
+            })
+            .await
+            .map_err(|e| DataError::Other(e.to_string()))?;
+        if role_id.is_empty() {
+            return Err(DataError::RspError(
+                ErrorServerNotFound as i32,
+                format!("{} not found", server_role),
+            ));
+        }
+        ServerRoleId::from_str(&role_id).map_err(DataError::Other)
+    }
+
+    //向Center查询某类型的所有节点
+    pub async fn select_all(
+        &self,
+        server_role: ServerRole,
+    ) -> Result<Vec<ServerRoleId>, DataError> {
+        self.center()
+            .await?
+            .ask(&SearchAllServerMessage { server_role })
+            .await
+            .map_err(|e| DataError::Other(e.to_string()))
+    }
+
+    //通知所有网关踢掉玩家,玩家不在的网关会忽略
+    //使用ask等待网关处理完,调用方返回后旧连接已经收到踢下线
+    pub async fn kick(
+        &self,
+        target: KickTarget,
+        reason: KickReason,
+        message: String,
+        reconnect_after: u32,
+    ) -> Result<(), DataError> {
+        let msg = GateMessage::Kick {
+            target,
+            reason: reason as i32,
+            message,
+            reconnect_after,
+        };
+        for role_id in self.select_all(ServerRole::Gate).await? {
+            if let Err(e) = self.ask_gate(&role_id, &msg).await {
+                tracing::warn!("kick on {} failed:{:?}", role_id, e);
+            }
+        }
+        Ok(())
+    }
+
+    //推送给客户端,玩家所在的网关未知,发给所有网关
+    pub async fn push(&self, target: PushTarget, cmd: i32, data: Bytes) -> Result<(), DataError> {
+        self.tell_gates(&GateMessage::Push { target, cmd, data })
+            .await
+    }
+
+    pub async fn join_channel(&self, channel: String, players: Vec<i64>) -> Result<(), DataError> {
+        self.tell_gates(&GateMessage::JoinChannel { channel, players })
+            .await
+    }
+
+    pub async fn leave_channel(&self, channel: String, players: Vec<i64>) -> Result<(), DataError> {
+        self.tell_gates(&GateMessage::LeaveChannel { channel, players })
+            .await
+    }
+
+    async fn tell_gates(&self, msg: &GateMessage) -> Result<(), DataError> {
+        for role_id in self.select_all(ServerRole::Gate).await? {
+            if let Err(e) = self.tell_gate(&role_id, msg).await {
+                tracing::warn!("tell {} failed:{:?}", role_id, e);
+            }
+        }
+        Ok(())
+    }
+
+    async fn tell_gate(&self, role_id: &ServerRoleId, msg: &GateMessage) -> Result<(), DataError> {
+        let actor_ref = get_with_retry!(self, find_gate_node, role_id);
+        actor_ref.tell(msg).await.map_err(|e| {
+            self.evict(role_id);
+            DataError::Unreachable(e.to_string())
+        })
+    }
+
+    async fn ask_gate(&self, role_id: &ServerRoleId, msg: &GateMessage) -> Result<bool, DataError> {
+        let actor_ref = get_with_retry!(self, find_gate_node, role_id);
+        actor_ref.ask(msg).await.map_err(|e| {
+            self.evict(role_id);
+            DataError::Unreachable(e.to_string())
+        })
+    }
+
     pub async fn ask(
-        &mut self,
+        &self,
         server_role: ServerRoleId,
         cmd: i32,
         data: Bytes,
     ) -> Result<ServerMessage, DataError> {
-        let role_id = server_role.to_string();
+        let role_id = server_role.clone();
         let result = match server_role.0 {
             ServerRole::Login => {
-                let actor_ref = get_with_retry!(find_login_node, role_id);
+                let actor_ref = get_with_retry!(self, find_login_node, role_id);
                 actor_ref.ask(&ServerMessage { cmd, data }).await
             }
             ServerRole::Game => {
-                let actor_ref = get_with_retry!(find_game_node, role_id);
+                let actor_ref = get_with_retry!(self, find_game_node, role_id);
                 actor_ref.ask(&ServerMessage { cmd, data }).await
             }
             ServerRole::World => {
-                let actor_ref = get_with_retry!(find_world_node, role_id);
+                let actor_ref = get_with_retry!(self, find_world_node, role_id);
                 actor_ref.ask(&ServerMessage { cmd, data }).await
             }
             _ => {
-                panic!("Unsupported server role");
+                return Err(DataError::Other(format!(
+                    "unsupported server role:{}",
+                    server_role
+                )));
             }
         };
 
         result.map_err(|e| {
-            return match e {
-                RemoteSendError::HandlerError(e) => e,
-                e => DataError::Other(e.to_string()),
-            };
+            if !matches!(e, RemoteSendError::HandlerError(_)) {
+                self.evict(&server_role);
+            }
+            send_error(e)
+        })
+    }
+
+    pub async fn tell(
+        &self,
+        server_role: ServerRoleId,
+        cmd: i32,
+        data: Bytes,
+    ) -> Result<(), DataError> {
+        let role_id = server_role.clone();
+        let result = match server_role.0 {
+            ServerRole::Login => {
+                let actor_ref = get_with_retry!(self, find_login_node, role_id);
+                actor_ref.tell(&ServerMessage { cmd, data }).await
+            }
+            ServerRole::Game => {
+                let actor_ref = get_with_retry!(self, find_game_node, role_id);
+                actor_ref.tell(&ServerMessage { cmd, data }).await
+            }
+            ServerRole::World => {
+                let actor_ref = get_with_retry!(self, find_world_node, role_id);
+                actor_ref.tell(&ServerMessage { cmd, data }).await
+            }
+            _ => {
+                return Err(DataError::Other(format!(
+                    "unsupported server role:{}",
+                    server_role
+                )));
+            }
+        };
+
+        result.map_err(|e| {
+            if !matches!(e, RemoteSendError::HandlerError(_)) {
+                self.evict(&server_role);
+            }
+            send_error(e)
         })
     }
-}
\ No newline at end of file
+
+    //远程调用失败时移除缓存,下次重新查找
+    fn evict(&self, server_role: &ServerRoleId) {
+        match server_role.0 {
+            ServerRole::Login => {
+                self.login_nodes.remove(server_role);
+                let mut login = self.login.write().unwrap();
+                if login.as_ref() == Some(server_role) {
+                    *login = None;
+                }
+            }
+            ServerRole::Game => {
+                self.game_nodes.remove(server_role);
+            }
+            ServerRole::World => {
+                self.world_nodes.remove(server_role);
+            }
+            ServerRole::Gate => {
+                self.gate_nodes.remove(server_role);
+            }
+            ServerRole::Center => {}
+        }
+    }
+}
+
+//只有确定请求没有送达时返回Unreachable,调用方可以换节点重试
+//超时和连接中断时请求可能已经执行,返回Timeout
+fn send_error(e: RemoteSendError<DataError>) -> DataError {
+    match e {
+        RemoteSendError::HandlerError(e) => e,
+        RemoteSendError::ActorNotRunning
+        | RemoteSendError::UnknownActor { .. }
+        | RemoteSendError::UnknownMessage { .. }
+        | RemoteSendError::BadActorType
+        | RemoteSendError::MailboxFull
+        | RemoteSendError::SerializeMessage(_)
+        | RemoteSendError::SwarmNotBootstrapped
+        | RemoteSendError::DialFailure
+        | RemoteSendError::UnsupportedProtocols => DataError::Unreachable(e.to_string()),
+        e => DataError::Timeout(e.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn retry_only_undelivered() {
+        for e in [
+            RemoteSendError::ActorNotRunning,
+            RemoteSendError::DialFailure,
+            RemoteSendError::MailboxFull,
+            RemoteSendError::SwarmNotBootstrapped,
+        ] {
+            assert!(matches!(send_error(e), DataError::Unreachable(_)));
+        }
+        //可能已经送达的请求不能重发
+        for e in [
+            RemoteSendError::ReplyTimeout,
+            RemoteSendError::NetworkTimeout,
+            RemoteSendError::ConnectionClosed,
+            RemoteSendError::ActorStopped,
+            RemoteSendError::Io(None),
+        ] {
+            assert!(matches!(send_error(e), DataError::Timeout(_)));
+        }
+        let e = RemoteSendError::HandlerError(DataError::RspError(1, String::new()));
+        assert!(matches!(send_error(e), DataError::RspError(1, _)));
+    }
+}
@@ -1,3 +1,4 @@
+use crate::discovery::NodeManager;
 use crate::gate::{GateActor, GateActorError};
 use crate::node::Node;
 use crate::{DataError, ServerMessage};
@@ -15,6 +16,8 @@ pub struct GameActor {
     global_config: Arc<GlobalConfig>,
     role_id: ServerRoleId,
     game_server_config: GameServerConfig,
+    //推送给客户端
+    pub node_manager: NodeManager,
 }
 impl GameActor {
     pub fn new(
@@ -26,6 +29,7 @@ impl GameActor {
             global_config,
             role_id,
             game_server_config,
+            node_manager: NodeManager::new(),
         }
     }
 }
@@ -44,6 +48,19 @@ impl Actor for GameActor {
         Ok(())
     }
 }
+//停服流程中flush阶段的调用点,返回保存的玩家数量
+//game节点还没有玩家状态,保存玩家数据没有实现(见README未完成的数据保存)
+pub struct Flush;
+
+impl Message<Flush> for GameActor {
+    type Reply = Result<usize, DataError>;
+
+    async fn handle(&mut self, _msg: Flush, _ctx: &mut Context<Self, Self::Reply>) -> Self::Reply {
+        //没有需要保存的玩家,接入玩家状态后在这里通过Store保存
+        Ok(0)
+    }
+}
+
 #[derive(Debug, Clone)]
 pub enum GameActorError {
     RegisterRemoteFail(String),
@@ -1,8 +1,9 @@
-use crate::game::GameActor;
-use crate::node::Node;
+use crate::game::{Flush, GameActor};
+use crate::node::{Node, stop_actor};
 use common::config::{GlobalConfig, ServerRoleId};
 use kameo::actor::ActorRef;
 use std::sync::Arc;
+use std::time::Duration;
 
 pub struct GameNode {
     global_config: Arc<GlobalConfig>,
@@ -52,15 +53,28 @@ impl Node for GameNode {
         Ok(())
     }
 
+    async fn flush(&mut self) -> anyhow::Result<()> {
+        let Some(actor_ref) = &self.game_ref else {
+            return Ok(());
+        };
+        let count = actor_ref.ask(Flush).await?;
+        tracing::info!("GameActor:{} flush {} players", self.role_id, count);
+        Ok(())
+    }
+
     async fn stop(&mut self) -> anyhow::Result<()> {
         let actor_ref = self.game_ref.take().unwrap();
-        //停止actor
-        actor_ref.kill();
-        actor_ref.wait_for_stop().await;
+        //处理完队列中的消息后停止actor
+        let timeout = Duration::from_secs(self.global_config.shutdown.stop_timeout);
+        stop_actor(actor_ref, timeout).await;
         Ok(())
     }
 
     fn server_role_id(&self) -> ServerRoleId {
         self.role_id.clone()
     }
+
+    fn global_config(&self) -> &GlobalConfig {
+        &self.global_config
+    }
 }
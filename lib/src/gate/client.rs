@@ -1,57 +1,124 @@
-use crate::game::GameActor;
-use crate::gate::net_server::NetServerSignal;
-use crate::gate::packet::{Encoder, Packet, Type};
-use crate::gate::GateActor;
-use crate::login::node::LoginActor;
-use crate::world::WorldActor;
+use crate::discovery::NodeManager;
+use crate::gate::crypto::ServerKey;
+use crate::gate::handshake::Negotiated;
+use crate::gate::limiter::RateLimiter;
+use crate::gate::net_server::{NetServerSignal, is_udp};
+use crate::gate::packet::{Codec, Packet, SharedPayload, Type};
+use crate::gate::session::{PushBuffer, Session, SessionRegistry, Suspended};
 use crate::{DataError, ServerMessage};
 use bytes::Bytes;
-use common::config::ServerRole;
-use kameo::actor::{ActorRef, RemoteActorRef};
-use kameo::message::{Context, Message};
+use common::config::{GateServerConfig, RateLimitPolicy, ServerRole, ServerRoleId};
 use kameo::Actor;
-use message_io::network::{Endpoint, SendStatus};
+use kameo::actor::{ActorRef, WeakActorRef};
+use kameo::error::ActorStopReason;
+use kameo::message::{Context, Message};
+use message_io::network::{Endpoint, SendStatus, Transport};
 use message_io::node::NodeHandler;
-use protocol::base_cmd::BaseCmd::CmdErrorRsp;
-use protocol::base_cmd::BaseError::{ErrorFunctionNotImpliment, ErrorServerInternal};
-use protocol::base_cmd::ErrorRsp;
-use protocol::login_cmd::{LoginReq, RegisterReq};
-use std::ops::Deref;
-use std::time::Duration;
+use protocol::base_cmd::BaseError::{ErrorNotLogin, ErrorRateLimited, ErrorUnknownCommand};
+use protocol::base_cmd::{
+    ErrorRsp, HandshakeAck, HandshakeReq, KickNtf, KickReason, ResumeReq, ResumeRsp,
+};
+use protocol::login_cmd::{LoginReq, LoginRsp};
+use protocol::route::RouteTarget;
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 pub const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(5);
 
 pub struct ClientActor {
+    //最后一次收到客户端消息的时间
     last_heartbeat: u128,
     endpoint: Endpoint,
     handler: NodeHandler<NetServerSignal>,
-    account: Option<String>,
+    node_manager: Arc<NodeManager>,
+    session: Session,
+    sessions: Arc<SessionRegistry>,
+    gate_config: Arc<GateServerConfig>,
+    //网关的长期密钥,没有监听开启加密时为空
+    server_key: Option<Arc<ServerKey>>,
+    //握手完成后才有值
+    negotiated: Option<Negotiated>,
+    codec: Codec,
+    pushes: PushBuffer,
+    //断开后是否保留会话等待重连,被踢下线的不保留
+    resumable: bool,
+    limiter: RateLimiter,
+    //登录请求处理中,之后收到的请求和通知等绑定会话后再处理
+    logging_in: bool,
+    deferred: VecDeque<Packet>,
+    //Throttle策略下等待令牌的包,不为空时新收到的包排在后面
+    throttled: VecDeque<Throttled>,
+}
+
+//限流延后的包,解码前按包数和字节数限流,解码后按cmd限流
+enum Throttled {
+    Bytes(Bytes),
+    Packet(Packet),
+}
+
+//限流检查的结果
+enum Limit {
+    Pass,
+    Reject,
+    //Throttle策略下需要等待的时间
+    Wait(Duration),
 }
 
 impl ClientActor {
     pub fn new(
         endpoint: Endpoint,
         handler: NodeHandler<NetServerSignal>,
+        node_manager: Arc<NodeManager>,
+        sessions: Arc<SessionRegistry>,
+        gate_config: Arc<GateServerConfig>,
+        server_key: Option<Arc<ServerKey>>,
     ) -> Self {
         Self {
             endpoint,
             handler,
-            last_heartbeat: 0,
-            account: None,
+            node_manager,
+            last_heartbeat: common::time::now_ms(),
+            session: Session::default(),
+            sessions,
+            negotiated: None,
+            codec: Codec::default(),
+            pushes: PushBuffer::new(gate_config.resume_buffer),
+            resumable: true,
+            limiter: RateLimiter::new(&gate_config.rate_limit),
+            logging_in: false,
+            deferred: VecDeque::new(),
+            throttled: VecDeque::new(),
+            gate_config,
+            server_key,
         }
     }
 
-    pub fn push(&mut self, cmd: i32, bytes: Bytes) {
-        self.send(Packet::new_data(Type::Push, cmd, bytes));
+    //分配推送序号并缓存,断线重连时补发
+    pub fn push(&mut self, payload: &SharedPayload) {
+        let seq = self.pushes.push(payload.cmd, payload.data.clone()).seq;
+        let bytes = self.codec.encode_push(seq, payload);
+        self.send_bytes(bytes, Type::Push);
     }
 
-    pub fn response(&mut self, cmd: i32, bytes: Bytes) {
-        self.send(Packet::new_data(Type::Response, cmd, bytes));
+    pub fn response(&mut self, cmd: i32, seq: u32, bytes: Bytes) {
+        self.send(Packet::new_data(Type::Response, cmd, bytes).with_seq(seq));
     }
 
     fn send(&mut self, packet: Packet) {
-        let typ = packet.r#type.to_string();
-        let bytes = Encoder::encode(packet);
+        let typ = packet.r#type;
+        let bytes = self.codec.encode(packet);
+        self.send_bytes(bytes, typ);
+    }
+
+    fn send_bytes(&mut self, bytes: Bytes, typ: Type) {
+        //可靠udp的会话状态在NetServer中,通过信号发送
+        if self.gate_config.kcp.enable && is_udp(&self.endpoint) {
+            self.handler
+                .signals()
+                .send(NetServerSignal::Send(self.endpoint, bytes));
+            return;
+        }
         let status = self.handler.network().send(self.endpoint, bytes.as_ref());
         if status != SendStatus::Sent {
             tracing::error!(
@@ -61,93 +128,620 @@ impl ClientActor {
             );
         }
     }
-    pub(crate) async fn handle_req(&mut self, packet: Packet) {
-        let cmd = packet.cmd;
-        let result = self.inner_handle_req(packet).await;
+    fn handshake(&mut self, packet: Packet) {
+        if self.negotiated.is_some() {
+            tracing::warn!("endpoint:{} repeated handshake ignored", self.endpoint);
+            return;
+        }
+        let result = crate::decode::<HandshakeReq>(packet.data)
+            .map_err(|e| KickNtf {
+                reason: KickReason::KickBadHandshake as i32,
+                message: e.to_string(),
+                reconnect_after: 0,
+            })
+            .and_then(|req| {
+                let server_key = self
+                    .server_key
+                    .as_deref()
+                    .filter(|_| self.encrypt_required());
+                super::handshake::negotiate(&self.gate_config, req, server_key)
+            });
         match result {
-            Ok(p) => {
-                self.send(p);
+            Ok((negotiated, cipher)) => {
+                tracing::info!(
+                    "endpoint:{} handshake session:{} version:{} platform:{:?}",
+                    self.endpoint,
+                    negotiated.session_id,
+                    negotiated.client_version,
+                    negotiated.platform
+                );
+                match crate::encode(negotiated.ack()) {
+                    Ok(bytes) => self.send(Packet::new_data(
+                        Type::HandshakeAck,
+                        HandshakeAck::CMD,
+                        bytes,
+                    )),
+                    Err(e) => tracing::error!(
+                        "endpoint:{} Failed to encode handshake ack:{}",
+                        self.endpoint,
+                        e
+                    ),
+                }
+                //握手应答为明文,之后的包按协商结果编解码
+                self.codec = Codec::new(negotiated.codec_options(&self.gate_config), cipher);
+                self.negotiated = Some(negotiated);
+            }
+            Err(kick) => {
+                tracing::warn!(
+                    "endpoint:{} handshake rejected:{}",
+                    self.endpoint,
+                    kick.message
+                );
+                self.kick(kick);
             }
+        }
+    }
+
+    //重连凭证和应答都按协商结果编解码,协商加密的连接上凭证不会明文出现
+    async fn handle_resume(&mut self, packet: Packet, actor_ref: &ActorRef<ClientActor>) {
+        let seq = packet.seq;
+        let req = match crate::decode::<ResumeReq>(packet.data) {
+            Ok(req) => req,
             Err(e) => {
-                let mut rsp: ErrorRsp = e.into();
-                rsp.cmd = cmd;
-                self.response(
-                    ErrorRsp::CMD,
-                    crate::encode(rsp).expect(
-                        format!("endpoint:{} Failed to encode response: ", self.endpoint).as_str(),
-                    ),
+                self.send(Self::error_rsp(ResumeReq::CMD, seq, e));
+                return;
+            }
+        };
+        //已经登录或恢复过的连接不再恢复其他会话
+        let missed = match self.session.account.is_none() && !req.resume_token.is_empty() {
+            true => {
+                self.resume(&req.resume_token, req.last_push_seq, actor_ref)
+                    .await
+            }
+            false => None,
+        };
+        let rsp = ResumeRsp {
+            resume_token: self
+                .negotiated
+                .as_ref()
+                .map(|x| x.token.clone())
+                .unwrap_or_default(),
+            resumed: missed.is_some(),
+        };
+        match crate::encode(rsp) {
+            Ok(bytes) => self.response(ResumeRsp::CMD, seq, bytes),
+            Err(e) => tracing::error!(
+                "endpoint:{} Failed to encode resume rsp:{}",
+                self.endpoint,
+                e
+            ),
+        }
+        //补发断线期间的推送
+        for packet in missed.unwrap_or_default() {
+            self.send(packet);
+        }
+    }
+
+    //恢复断线前的会话,返回需要补发的推送,推送已丢失时不能恢复
+    async fn resume(
+        &mut self,
+        token: &str,
+        last_push_seq: u32,
+        actor_ref: &ActorRef<ClientActor>,
+    ) -> Option<Vec<Packet>> {
+        let Suspended {
+            session,
+            mut pushes,
+            ..
+        } = self.sessions.resume(token, common::time::now_ms())?;
+        let Some(missed) = pushes.missed(last_push_seq) else {
+            tracing::info!(
+                "endpoint:{} player:{:?} resume failed, pushes lost after:{}",
+                self.endpoint,
+                session.player_id,
+                last_push_seq
+            );
+            return None;
+        };
+        tracing::info!(
+            "endpoint:{} resume account:{:?} player:{:?} missed pushes:{}",
+            self.endpoint,
+            session.account,
+            session.player_id,
+            missed.len()
+        );
+        self.bind_session(session, actor_ref).await;
+        self.pushes = pushes;
+        Some(missed)
+    }
+
+    //同一网关上的重复登录,踢掉旧连接
+    async fn bind_session(&mut self, session: Session, actor_ref: &ActorRef<ClientActor>) {
+        tracing::info!(
+            "endpoint:{} bind account:{:?} player:{:?} game:{:?} world:{:?}",
+            self.endpoint,
+            session.account,
+            session.player_id,
+            session.game,
+            session.world
+        );
+        if let Some(old) = self.sessions.bind(&session, actor_ref) {
+            let kick = KickNtf {
+                reason: KickReason::KickDuplicateLogin as i32,
+                message: "login from another device".to_string(),
+                reconnect_after: 0,
+            };
+            if let Err(e) = old.tell(ClientMessage::Kick(kick)).await {
+                tracing::warn!("endpoint:{} kick old session failed:{}", self.endpoint, e);
+            }
+        }
+        self.session = session;
+    }
+
+    //连接所在的监听是否要求加密
+    fn encrypt_required(&self) -> bool {
+        let adapter_id = self.endpoint.resource_id().adapter_id();
+        let encrypt = &self.gate_config.encrypt;
+        if adapter_id == Transport::FramedTcp.id() {
+            encrypt.tcp
+        } else if adapter_id == Transport::Ws.id() {
+            encrypt.ws
+        } else if adapter_id == Transport::Udp.id() {
+            encrypt.udp
+        } else {
+            false
+        }
+    }
+
+    //发送踢下线通知,随后关闭连接
+    //踢下线包和关闭都走NetServer的信号队列,保证关闭前踢下线包已经发出
+    fn kick(&mut self, kick: KickNtf) {
+        //心跳超时可能只是网络波动,允许重连恢复
+        self.resumable = kick.reason == KickReason::KickHeartbeatTimeout as i32;
+        match crate::encode(kick) {
+            Ok(bytes) => {
+                let bytes = self
+                    .codec
+                    .encode(Packet::new_data(Type::Kick, KickNtf::CMD, bytes));
+                self.handler
+                    .signals()
+                    .send(NetServerSignal::Send(self.endpoint, bytes));
+            }
+            Err(e) => tracing::error!("endpoint:{} Failed to encode kick:{}", self.endpoint, e),
+        }
+        self.handler
+            .signals()
+            .send(NetServerSignal::CloseSession(self.endpoint));
+    }
+
+    //登录中的请求和通知排队,登录完成后按收到的顺序处理
+    async fn dispatch(&mut self, packet: Packet, actor_ref: ActorRef<ClientActor>) {
+        if self.logging_in {
+            if self.deferred.len() >= self.gate_config.rate_limit.max_pending.max(1) {
+                tracing::warn!(
+                    "endpoint:{} too many packets while logging in",
+                    self.endpoint
+                );
+                if packet.r#type == Type::Request {
+                    let e = DataError::RspError(
+                        ErrorRateLimited as i32,
+                        format!("cmd:{} rate limited", packet.cmd),
+                    );
+                    self.send(Self::error_rsp(packet.cmd, packet.seq, e));
+                }
+                return;
+            }
+            self.deferred.push_back(packet);
+            return;
+        }
+        match packet.r#type {
+            Type::Request if packet.cmd == ResumeReq::CMD => {
+                self.handle_resume(packet, &actor_ref).await
+            }
+            Type::Request => {
+                self.logging_in = packet.cmd == LoginReq::CMD;
+                self.handle_req(packet, actor_ref);
+            }
+            Type::Notify => self.handle_ntf(packet).await,
+            _ => {}
+        }
+    }
+
+    //请求并发处理,响应带回客户端的seq,由客户端按seq对应请求,不保证响应顺序
+    pub(crate) fn handle_req(&mut self, packet: Packet, actor_ref: ActorRef<ClientActor>) {
+        let endpoint = self.endpoint;
+        let node_manager = self.node_manager.clone();
+        let session = self.session.clone();
+        tokio::spawn(async move {
+            let cmd = packet.cmd;
+            let seq = packet.seq;
+            let (rsp, bind) =
+                match Self::inner_handle_req(&node_manager, &session, &actor_ref, packet).await {
+                    Ok((p, bind)) => (p.with_seq(seq), bind),
+                    Err(e) => (Self::error_rsp(cmd, seq, e), None),
+                };
+            let msg = match cmd {
+                LoginReq::CMD => ClientMessage::Login { session: bind, rsp },
+                _ => ClientMessage::SendPacket(rsp),
+            };
+            if let Err(e) = actor_ref.tell(msg).await {
+                tracing::warn!(
+                    "endpoint:{} cmd:{} seq:{} response dropped:{}",
+                    endpoint,
+                    cmd,
+                    seq,
+                    e
+                );
+            }
+        });
+    }
+    fn error_rsp(cmd: i32, seq: u32, e: DataError) -> Packet {
+        let mut rsp: ErrorRsp = e.into();
+        rsp.cmd = cmd;
+        Packet::new_data(
+            Type::Response,
+            ErrorRsp::CMD,
+            crate::encode(rsp).expect("ErrorRsp encoding is infallible"),
+        )
+        .with_seq(seq)
+    }
+
+    //超出限流时按策略处理
+    fn throttle(
+        &mut self,
+        check: impl FnOnce(&mut RateLimiter, Instant) -> Option<Duration>,
+    ) -> Limit {
+        let Some(wait) = check(&mut self.limiter, Instant::now()) else {
+            return Limit::Pass;
+        };
+        match self.limiter.policy {
+            RateLimitPolicy::Drop => Limit::Reject,
+            RateLimitPolicy::Throttle => Limit::Wait(wait),
+            RateLimitPolicy::Kick => {
+                tracing::warn!("endpoint:{} rate limited", self.endpoint);
+                self.kick(KickNtf {
+                    reason: KickReason::KickRateLimited as i32,
+                    message: "rate limited".to_string(),
+                    reconnect_after: 0,
+                });
+                Limit::Reject
+            }
+        }
+    }
+
+    //等待后再处理延后的包,不在handler中sleep,心跳和推送等消息照常处理
+    fn wake_after(actor_ref: ActorRef<ClientActor>, wait: Duration) {
+        tokio::spawn(async move {
+            tokio::time::sleep(wait).await;
+            let _ = actor_ref.tell(ClientMessage::Throttled).await;
+        });
+    }
+
+    //处理一个包,被限流延后时放回队列头并返回需要等待的时间
+    async fn process(
+        &mut self,
+        item: Throttled,
+        actor_ref: &ActorRef<ClientActor>,
+    ) -> Option<Duration> {
+        let packet = match item {
+            Throttled::Bytes(bytes) => {
+                let len = bytes.len();
+                match self.throttle(|x, now| x.check_packet(len, now)) {
+                    Limit::Pass => {}
+                    Limit::Reject => {
+                        tracing::debug!("endpoint:{} packet dropped by rate limit", self.endpoint);
+                        return None;
+                    }
+                    Limit::Wait(wait) => {
+                        self.throttled.push_front(Throttled::Bytes(bytes));
+                        return Some(wait);
+                    }
+                }
+                //解码失败或已处理的握手和心跳不需要延后
+                self.decode(bytes)?
+            }
+            Throttled::Packet(packet) => packet,
+        };
+        let cmd = packet.cmd;
+        match self.throttle(|x, now| x.check_cmd(cmd, now)) {
+            Limit::Pass => self.dispatch(packet, actor_ref.clone()).await,
+            Limit::Reject => {
+                if packet.r#type == Type::Request && self.limiter.policy == RateLimitPolicy::Drop {
+                    let e = DataError::RspError(
+                        ErrorRateLimited as i32,
+                        format!("cmd:{} rate limited", cmd),
+                    );
+                    self.send(Self::error_rsp(cmd, packet.seq, e));
+                } else {
+                    tracing::debug!("endpoint:{} cmd:{} rate limited", self.endpoint, cmd);
+                }
+            }
+            Limit::Wait(wait) => {
+                self.throttled.push_front(Throttled::Packet(packet));
+                return Some(wait);
+            }
+        }
+        None
+    }
+
+    //解码并处理握手和心跳,返回需要转发的请求和通知
+    fn decode(&mut self, bytes: Bytes) -> Option<Packet> {
+        let packet = match self.codec.decode(&bytes) {
+            Ok(packet) => packet,
+            Err(e) => {
+                tracing::error!(
+                    "endpoint:{} decoding failed err:{} input_data:{:?}",
+                    self.endpoint,
+                    e,
+                    bytes
+                );
+                self.handler
+                    .signals()
+                    .send_with_priority(NetServerSignal::CloseSession(self.endpoint));
+                return None;
+            }
+        };
+        //握手完成前只接受握手包,不能绕过版本检查和加密直接转发请求
+        if self.negotiated.is_none() && packet.r#type != Type::Handshake {
+            tracing::warn!(
+                "endpoint:{} {} before handshake",
+                self.endpoint,
+                packet.r#type
+            );
+            let reason = match self.encrypt_required() {
+                true => KickReason::KickEncryptionRequired,
+                false => KickReason::KickBadHandshake,
+            };
+            self.kick(KickNtf {
+                reason: reason as i32,
+                message: "handshake required".to_string(),
+                reconnect_after: 0,
+            });
+            return None;
+        }
+        self.last_heartbeat = common::time::now_ms();
+        match packet.r#type {
+            Type::Handshake => self.handshake(packet),
+            Type::Heartbeat => {
+                //心跳带回客户端收到的最后一个推送序号,只接受握手后按协商结果解码的心跳
+                if packet.seq != 0 && self.negotiated.is_some() {
+                    self.pushes.ack(packet.seq);
+                }
+                let bytes = self.codec.encode(Packet::new_control(Type::Heartbeat));
+                self.handler.signals().send_with_timer(
+                    NetServerSignal::Send(self.endpoint, bytes),
+                    HEARTBEAT_INTERVAL,
                 );
             }
+            Type::Request | Type::Notify => return Some(packet),
+            _ => {}
         }
+        None
     }
+
     pub(crate) async fn handle_ntf(&mut self, packet: Packet) {
         let bytes = packet.data;
         let cmd = packet.cmd;
 
-        let result = match cmd {
-            LoginReq::CMD | RegisterReq::CMD => self.ntf(ServerRole::Login, cmd, bytes).await,
-            c if forward_to_world(c) => self.ntf(ServerRole::World, cmd, bytes).await,
-            c if forward_to_game(c) => self.ntf(ServerRole::Game, cmd, bytes).await,
-            _ => self.ntf(ServerRole::Game, cmd, bytes).await,
+        let result = match Self::route(cmd) {
+            Ok(role) => self.ntf(role, cmd, bytes).await,
+            Err(e) => Err(e),
         };
         if let Err(e) = result {
             tracing::error!("cmd:{} internal error: {:?}", cmd, e);
         }
     }
 
-    async fn inner_handle_req(&mut self, packet: Packet) -> Result<Packet, DataError> {
+    //登录成功时同时返回需要绑定的会话
+    async fn inner_handle_req(
+        node_manager: &NodeManager,
+        session: &Session,
+        actor_ref: &ActorRef<ClientActor>,
+        packet: Packet,
+    ) -> Result<(Packet, Option<Session>), DataError> {
         let bytes = packet.data;
         let cmd = packet.cmd;
+        let role = Self::route(cmd)?;
+        let mut bind = None;
+        let rsp = match role {
+            ServerRole::Login => {
+                let account = match cmd {
+                    LoginReq::CMD => Some(crate::decode::<LoginReq>(bytes.clone())?.account),
+                    _ => None,
+                };
+                let role_id = node_manager.select_login().await?;
+                let rsp = node_manager.ask(role_id, cmd, bytes).await?;
+                if let (Some(account), LoginRsp::CMD) = (account, rsp.cmd) {
+                    let login_rsp = crate::decode::<LoginRsp>(rsp.data.clone())?;
+                    bind = Some(Self::bind(node_manager, account, login_rsp.player_id).await?);
+                }
+                rsp
+            }
+            _ => Self::ask(node_manager, session, actor_ref, role, cmd, bytes).await?,
+        };
+        Ok((Packet::new_data(Type::Response, rsp.cmd, rsp.data), bind))
+    }
+
+    //按cmd.txt中声明的命令码范围转发
+    fn route(cmd: i32) -> Result<ServerRole, DataError> {
+        match protocol::route::route(cmd) {
+            Some(RouteTarget::Login) => Ok(ServerRole::Login),
+            Some(RouteTarget::World) => Ok(ServerRole::World),
+            Some(RouteTarget::Game) => Ok(ServerRole::Game),
+            Some(RouteTarget::Gate) | None => Err(DataError::RspError(
+                ErrorUnknownCommand as i32,
+                format!("UnknownCommandError:{}", cmd),
+            )),
+        }
+    }
 
-        match cmd {
-            LoginReq::CMD | RegisterReq::CMD => self.ask(ServerRole::Login, cmd, bytes).await,
-            c if forward_to_world(c) => self.ask(ServerRole::World, cmd, bytes).await,
-            c if forward_to_game(c) => self.ask(ServerRole::Game, cmd, bytes).await,
-            _ => self.ask(ServerRole::Game, cmd, bytes).await,
+    //登录成功后为玩家分配game/world节点
+    async fn bind(
+        node_manager: &NodeManager,
+        account: String,
+        player_id: i64,
+    ) -> Result<Session, DataError> {
+        let mut session = Session {
+            account: Some(account),
+            player_id: Some(player_id),
+            ..Default::default()
+        };
+        session.bind_node(
+            node_manager
+                .select_by_key(ServerRole::Game, player_id)
+                .await?,
+        );
+        //world节点可以延后到第一次请求时再分配
+        match node_manager
+            .select_by_key(ServerRole::World, player_id)
+            .await
+        {
+            Ok(role_id) => session.bind_node(role_id),
+            Err(e) => tracing::warn!("player:{} bind world failed:{:?}", player_id, e),
         }
-        .map(|x| Packet::new_data(Type::Response, x.cmd, x.data))
+        Ok(session)
+    }
+
+    fn player_id(session: &Session) -> Result<i64, DataError> {
+        session.player_id.ok_or_else(|| {
+            DataError::RspError(ErrorNotLogin as i32, "session not login".to_string())
+        })
     }
 
+    //转发到会话绑定的节点,确定没有送达时通过Center重新分配一次
     async fn ask(
-        &self,
+        node_manager: &NodeManager,
+        session: &Session,
+        actor_ref: &ActorRef<ClientActor>,
         role: ServerRole,
         cmd: i32,
         data: Bytes,
     ) -> Result<ServerMessage, DataError> {
-        Err(DataError::RspError(
-            ErrorFunctionNotImpliment as i32,
-            "".to_string(),
-        ))
-    }
-    async fn ntf(&self, role: ServerRole, cmd: i32, data: Bytes) -> Result<(), DataError> {
-        Err(DataError::RspError(
-            ErrorFunctionNotImpliment as i32,
-            "".to_string(),
-        ))
+        let player_id = Self::player_id(session)?;
+        let role_id = match session.node(&role) {
+            Some(role_id) => role_id.clone(),
+            None => Self::rebind(node_manager, actor_ref, role.clone(), player_id).await?,
+        };
+        match node_manager.ask(role_id, cmd, data.clone()).await {
+            Err(DataError::Unreachable(e)) => {
+                tracing::warn!("player:{} {} unreachable:{}", player_id, role, e);
+                let role_id = Self::rebind(node_manager, actor_ref, role, player_id).await?;
+                node_manager.ask(role_id, cmd, data).await
+            }
+            result => result,
+        }
     }
-}
 
-fn forward_to_world(cmd: i32) -> bool {
-    false
-}
-fn forward_to_game(cmd: i32) -> bool {
-    false
-}
+    async fn rebind(
+        node_manager: &NodeManager,
+        actor_ref: &ActorRef<ClientActor>,
+        role: ServerRole,
+        player_id: i64,
+    ) -> Result<ServerRoleId, DataError> {
+        let role_id = node_manager.select_by_key(role, player_id).await?;
+        if let Err(e) = actor_ref
+            .tell(ClientMessage::BindNode(role_id.clone()))
+            .await
+        {
+            tracing::warn!("player:{} bind {} failed:{}", player_id, role_id, e);
+        }
+        Ok(role_id)
+    }
 
-fn forward_to_login(cmd: i32) -> bool {
-    match cmd {
-        LoginReq::CMD | RegisterReq::CMD => true,
-        _ => false,
+    async fn ntf(&mut self, role: ServerRole, cmd: i32, data: Bytes) -> Result<(), DataError> {
+        if role == ServerRole::Login {
+            let role_id = self.node_manager.select_login().await?;
+            return self.node_manager.tell(role_id, cmd, data).await;
+        }
+        let player_id = Self::player_id(&self.session)?;
+        let role_id = match self.session.node(&role) {
+            Some(role_id) => role_id.clone(),
+            None => {
+                let role_id = self
+                    .node_manager
+                    .select_by_key(role.clone(), player_id)
+                    .await?;
+                self.session.bind_node(role_id.clone());
+                role_id
+            }
+        };
+        match self.node_manager.tell(role_id, cmd, data.clone()).await {
+            Err(DataError::Unreachable(e)) => {
+                tracing::warn!("player:{} {} unreachable:{}", player_id, role, e);
+                let role_id = self.node_manager.select_by_key(role, player_id).await?;
+                self.session.bind_node(role_id.clone());
+                self.node_manager.tell(role_id, cmd, data).await
+            }
+            result => result,
+        }
     }
 }
 
 impl Actor for ClientActor {
     type Error = ();
+
+    async fn on_start(&mut self, actor_ref: ActorRef<Self>) -> Result<(), Self::Error> {
+        self.sessions.connect(&actor_ref);
+        Ok(())
+    }
+
+    async fn on_stop(
+        &mut self,
+        actor_ref: WeakActorRef<Self>,
+        _reason: ActorStopReason,
+    ) -> Result<(), Self::Error> {
+        let grace = self.gate_config.resume_grace;
+        let suspend = self.resumable
+            && grace > 0
+            && self.session.player_id.is_some()
+            && self.negotiated.is_some();
+        if suspend && let Some(negotiated) = &self.negotiated {
+            tracing::info!(
+                "endpoint:{} suspend player:{:?} for {}s",
+                self.endpoint,
+                self.session.player_id,
+                grace
+            );
+            self.sessions.suspend(
+                negotiated.token.clone(),
+                Suspended {
+                    session: self.session.clone(),
+                    pushes: std::mem::take(&mut self.pushes),
+                    expire_at: common::time::now_ms() + grace as u128 * 1000,
+                },
+            );
+        }
+        self.sessions.disconnect(actor_ref.id());
+        let left = self.sessions.unbind(&self.session, actor_ref.id());
+        //没有保留会话的玩家退出所有频道
+        if left
+            && !suspend
+            && let Some(player_id) = self.session.player_id
+        {
+            self.sessions.leave_all(player_id);
+        }
+        Ok(())
+    }
 }
 
 pub enum ClientMessage {
-    ReceivePacket(Packet),
+    //收到的原始数据,由连接自己的Codec解码
+    Receive(Bytes),
     SendPacket(Packet),
+    //登录请求完成,成功时先绑定会话再发出响应
+    Login {
+        session: Option<Session>,
+        rsp: Packet,
+    },
+    BindNode(ServerRoleId),
+    //心跳超时检测
+    CheckIdle(Duration),
+    //服务器主动踢下线
+    Kick(KickNtf),
+    //推送给客户端,广播时多个连接共用同一个负载
+    Push(Arc<SharedPayload>),
+    //限流等待结束,继续处理延后的包
+    Throttled,
 }
 impl Message<ClientMessage> for ClientActor {
     type Reply = ();
@@ -158,27 +752,64 @@ impl Message<ClientMessage> for ClientActor {
         ctx: &mut Context<Self, Self::Reply>,
     ) -> Self::Reply {
         match msg {
-            ClientMessage::ReceivePacket(packet) => match packet.r#type {
-                Type::Handshake => self.send(Packet::new_control(Type::HandshakeAck)),
-                Type::Heartbeat => {
+            ClientMessage::Receive(bytes) => {
+                //有限流延后的包时排在后面,保证按收到的顺序处理
+                if !self.throttled.is_empty() {
+                    if self.throttled.len() >= self.gate_config.rate_limit.max_pending.max(1) {
+                        tracing::warn!("endpoint:{} too many throttled packets", self.endpoint);
+                        return;
+                    }
+                    //排队的包也说明连接还活着,不能因为等待令牌被判定心跳超时
                     self.last_heartbeat = common::time::now_ms();
-                    self.handler.signals().send_with_timer(
-                        NetServerSignal::SendPacket(
-                            self.endpoint,
-                            Packet::new_control(Type::Heartbeat),
-                        ),
-                        HEARTBEAT_INTERVAL,
-                    );
+                    self.throttled.push_back(Throttled::Bytes(bytes));
+                    return;
                 }
-                Type::Request => {
-                    self.handle_req(packet).await;
+                if let Some(wait) = self
+                    .process(Throttled::Bytes(bytes), &ctx.actor_ref())
+                    .await
+                {
+                    Self::wake_after(ctx.actor_ref(), wait);
                 }
-                Type::Notify => {
-                    self.handle_ntf(packet).await;
+            }
+            ClientMessage::Throttled => {
+                while let Some(item) = self.throttled.pop_front() {
+                    if let Some(wait) = self.process(item, &ctx.actor_ref()).await {
+                        Self::wake_after(ctx.actor_ref(), wait);
+                        break;
+                    }
                 }
-                _ => {}
-            },
+            }
             ClientMessage::SendPacket(packet) => self.send(packet),
+            ClientMessage::Login { session, rsp } => {
+                if let Some(session) = session {
+                    self.bind_session(session, &ctx.actor_ref()).await;
+                }
+                self.send(rsp);
+                self.logging_in = false;
+                while !self.logging_in
+                    && let Some(packet) = self.deferred.pop_front()
+                {
+                    self.dispatch(packet, ctx.actor_ref()).await;
+                }
+            }
+            ClientMessage::BindNode(role_id) => self.session.bind_node(role_id),
+            ClientMessage::CheckIdle(timeout) => {
+                let idle = common::time::now_ms().saturating_sub(self.last_heartbeat);
+                if idle > timeout.as_millis() {
+                    tracing::info!(
+                        "endpoint:{} heartbeat timeout idle:{}ms",
+                        self.endpoint,
+                        idle
+                    );
+                    self.kick(KickNtf {
+                        reason: KickReason::KickHeartbeatTimeout as i32,
+                        message: "heartbeat timeout".to_string(),
+                        reconnect_after: 0,
+                    });
+                }
+            }
+            ClientMessage::Kick(kick) => self.kick(kick),
+            ClientMessage::Push(payload) => self.push(&payload),
         }
     }
-}
\ No newline at end of file
+}
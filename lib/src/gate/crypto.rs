@@ -0,0 +1,401 @@
+//! 连接加密:握手时 X25519 交换临时公钥,HKDF-SHA256 派生两个方向的密钥,
+//! 之后每个带负载的包用 ChaCha20-Poly1305 加密
+//!
+//! 网关有一个长期密钥,客户端固定它的公钥。派生密钥时除了两个临时密钥的DH,
+//! 还加入客户端临时密钥与网关长期公钥的DH(同Noise NK)。中间人没有网关的长期私钥,
+//! 算不出会话密钥,既读不到客户端发出的内容,也无法冒充网关。
+//!
+//! 加密后的 payload 为 `nonce计数(u64 LE) + 密文 + tag(16)`,帧头(magic..len)作为附加认证数据。
+//! 每个方向的计数从0递增,接收方用64位滑动窗口拒绝重放,窗口内允许乱序(udp)。
+use crate::gate::packet::DecodeError;
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use hkdf::Hkdf;
+use sha2::Sha256;
+use thiserror::Error;
+use x25519_dalek::{PublicKey, ReusableSecret, SharedSecret, StaticSecret};
+
+pub const PUBLIC_KEY_LEN: usize = 32;
+const COUNTER_LEN: usize = 8;
+const TAG_LEN: usize = 16;
+//加密后负载增加的长度
+pub const SEAL_OVERHEAD: usize = COUNTER_LEN + TAG_LEN;
+const REPLAY_WINDOW: u64 = 64;
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum CryptoError {
+    #[error("invalid public key length:{0}")]
+    InvalidPublicKey(usize),
+    #[error("key exchange is not contributory")]
+    NonContributory,
+    #[error("invalid key:{0}")]
+    InvalidKey(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Side {
+    Server,
+    Client,
+}
+
+//网关的长期密钥,私钥只在网关的配置中
+pub struct ServerKey {
+    secret: StaticSecret,
+    public: PublicKey,
+}
+
+impl ServerKey {
+    pub fn new(secret: [u8; PUBLIC_KEY_LEN]) -> Self {
+        let secret = StaticSecret::from(secret);
+        let public = PublicKey::from(&secret);
+        Self { secret, public }
+    }
+
+    pub fn public_key(&self) -> [u8; PUBLIC_KEY_LEN] {
+        self.public.to_bytes()
+    }
+}
+
+//十六进制的32字节密钥
+pub fn decode_key(key: &str) -> Result<[u8; PUBLIC_KEY_LEN], CryptoError> {
+    let bytes = hex::decode(key.trim()).map_err(|e| CryptoError::InvalidKey(e.to_string()))?;
+    bytes
+        .as_slice()
+        .try_into()
+        .map_err(|_| CryptoError::InvalidKey(format!("length:{}", bytes.len())))
+}
+
+//一次性的密钥交换,双方各生成一个,客户端的临时私钥要做两次DH
+pub struct KeyExchange {
+    secret: ReusableSecret,
+    public: PublicKey,
+}
+
+impl KeyExchange {
+    pub fn new() -> Self {
+        let secret = ReusableSecret::random();
+        let public = PublicKey::from(&secret);
+        Self { secret, public }
+    }
+
+    pub fn public_key(&self) -> [u8; PUBLIC_KEY_LEN] {
+        self.public.to_bytes()
+    }
+
+    //网关用临时私钥和长期私钥分别与客户端的临时公钥做DH
+    pub fn finish_server(
+        self,
+        client_public: &[u8],
+        server_key: &ServerKey,
+    ) -> Result<SessionCipher, CryptoError> {
+        let client = peer_key(client_public)?;
+        let ephemeral = self.secret.diffie_hellman(&client);
+        let identity = server_key.secret.diffie_hellman(&client);
+        derive(
+            &ephemeral,
+            &identity,
+            client.to_bytes(),
+            self.public.to_bytes(),
+            Side::Server,
+        )
+    }
+
+    //客户端用临时私钥分别与网关的临时公钥和固定的长期公钥做DH
+    pub fn finish_client(
+        self,
+        server_public: &[u8],
+        server_key: &[u8; PUBLIC_KEY_LEN],
+    ) -> Result<SessionCipher, CryptoError> {
+        let server = peer_key(server_public)?;
+        let ephemeral = self.secret.diffie_hellman(&server);
+        let identity = self.secret.diffie_hellman(&PublicKey::from(*server_key));
+        derive(
+            &ephemeral,
+            &identity,
+            self.public.to_bytes(),
+            server.to_bytes(),
+            Side::Client,
+        )
+    }
+}
+
+impl Default for KeyExchange {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn peer_key(public: &[u8]) -> Result<PublicKey, CryptoError> {
+    let key: [u8; PUBLIC_KEY_LEN] = public
+        .try_into()
+        .map_err(|_| CryptoError::InvalidPublicKey(public.len()))?;
+    Ok(PublicKey::from(key))
+}
+
+//两个临时公钥作为salt,两次DH的结果依次作为输入
+fn derive(
+    ephemeral: &SharedSecret,
+    identity: &SharedSecret,
+    client_public: [u8; PUBLIC_KEY_LEN],
+    server_public: [u8; PUBLIC_KEY_LEN],
+    side: Side,
+) -> Result<SessionCipher, CryptoError> {
+    //对端使用低阶点时共享密钥可被预测
+    if !ephemeral.was_contributory() || !identity.was_contributory() {
+        return Err(CryptoError::NonContributory);
+    }
+    let mut salt = [0u8; PUBLIC_KEY_LEN * 2];
+    salt[..PUBLIC_KEY_LEN].copy_from_slice(&client_public);
+    salt[PUBLIC_KEY_LEN..].copy_from_slice(&server_public);
+    let mut shared = [0u8; PUBLIC_KEY_LEN * 2];
+    shared[..PUBLIC_KEY_LEN].copy_from_slice(ephemeral.as_bytes());
+    shared[PUBLIC_KEY_LEN..].copy_from_slice(identity.as_bytes());
+    Ok(SessionCipher::derive(&shared, &salt, side))
+}
+
+//一个连接两个方向的加解密状态
+pub struct SessionCipher {
+    tx: ChaCha20Poly1305,
+    rx: ChaCha20Poly1305,
+    tx_counter: u64,
+    replay: ReplayWindow,
+}
+
+impl SessionCipher {
+    fn derive(shared: &[u8], salt: &[u8], side: Side) -> Self {
+        let hkdf = Hkdf::<Sha256>::new(Some(salt), shared);
+        let mut c2s = [0u8; 32];
+        let mut s2c = [0u8; 32];
+        hkdf.expand(b"qs001 c2s", &mut c2s)
+            .expect("32 bytes is a valid hkdf output length");
+        hkdf.expand(b"qs001 s2c", &mut s2c)
+            .expect("32 bytes is a valid hkdf output length");
+        let (tx, rx) = match side {
+            Side::Server => (s2c, c2s),
+            Side::Client => (c2s, s2c),
+        };
+        Self {
+            tx: ChaCha20Poly1305::new(Key::from_slice(&tx)),
+            rx: ChaCha20Poly1305::new(Key::from_slice(&rx)),
+            tx_counter: 0,
+            replay: ReplayWindow::default(),
+        }
+    }
+
+    pub fn seal(&mut self, aad: &[u8], plaintext: &[u8]) -> Vec<u8> {
+        let counter = self.tx_counter;
+        self.tx_counter += 1;
+        let ciphertext = self
+            .tx
+            .encrypt(
+                &nonce(counter),
+                Payload {
+                    msg: plaintext,
+                    aad,
+                },
+            )
+            .expect("chacha20poly1305 encryption is infallible for in-memory buffers");
+        let mut output = Vec::with_capacity(COUNTER_LEN + ciphertext.len());
+        output.extend_from_slice(&counter.to_le_bytes());
+        output.extend_from_slice(&ciphertext);
+        output
+    }
+
+    pub fn open(&mut self, aad: &[u8], payload: &[u8]) -> Result<Vec<u8>, DecodeError> {
+        if payload.len() < SEAL_OVERHEAD {
+            return Err(DecodeError::DecryptFailed);
+        }
+        let mut counter = [0u8; COUNTER_LEN];
+        counter.copy_from_slice(&payload[..COUNTER_LEN]);
+        let counter = u64::from_le_bytes(counter);
+        if !self.replay.check(counter) {
+            return Err(DecodeError::Replayed(counter));
+        }
+        let plaintext = self
+            .rx
+            .decrypt(
+                &nonce(counter),
+                Payload {
+                    msg: &payload[COUNTER_LEN..],
+                    aad,
+                },
+            )
+            .map_err(|_| DecodeError::DecryptFailed)?;
+        //认证通过后才移动窗口,防止伪造的计数把窗口推走
+        self.replay.update(counter);
+        Ok(plaintext)
+    }
+}
+
+//不输出密钥
+impl std::fmt::Debug for SessionCipher {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SessionCipher")
+            .field("tx_counter", &self.tx_counter)
+            .field("rx_next", &self.replay.next)
+            .finish_non_exhaustive()
+    }
+}
+
+fn nonce(counter: u64) -> Nonce {
+    let mut nonce = [0u8; 12];
+    nonce[4..].copy_from_slice(&counter.to_le_bytes());
+    *Nonce::from_slice(&nonce)
+}
+
+//next为已收到的最大计数+1,bitmap第i位表示next-1-i已收到
+#[derive(Default)]
+struct ReplayWindow {
+    next: u64,
+    bitmap: u64,
+}
+
+impl ReplayWindow {
+    fn check(&self, counter: u64) -> bool {
+        if counter >= self.next {
+            return true;
+        }
+        let offset = self.next - 1 - counter;
+        offset < REPLAY_WINDOW && self.bitmap & (1 << offset) == 0
+    }
+
+    fn update(&mut self, counter: u64) {
+        if counter >= self.next {
+            let shift = counter - self.next + 1;
+            self.bitmap = if shift >= REPLAY_WINDOW {
+                0
+            } else {
+                self.bitmap << shift
+            };
+            self.bitmap |= 1;
+            self.next = counter + 1;
+        } else {
+            self.bitmap |= 1 << (self.next - 1 - counter);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn pair() -> (SessionCipher, SessionCipher) {
+        let server_key = ServerKey::new([7; PUBLIC_KEY_LEN]);
+        let server = KeyExchange::new();
+        let client = KeyExchange::new();
+        let server_public = server.public_key();
+        let client_public = client.public_key();
+        (
+            server.finish_server(&client_public, &server_key).unwrap(),
+            client
+                .finish_client(&server_public, &server_key.public_key())
+                .unwrap(),
+        )
+    }
+
+    #[test]
+    fn seal_open() {
+        let (mut server, mut client) = pair();
+        let sealed = client.seal(b"header", b"account");
+        assert_eq!(sealed.len(), 7 + SEAL_OVERHEAD);
+        assert_eq!(server.open(b"header", &sealed).unwrap(), b"account");
+        let sealed = server.seal(b"header", b"player");
+        assert_eq!(client.open(b"header", &sealed).unwrap(), b"player");
+        //同一方向的密钥不能解自己发出的包
+        let sealed = server.seal(b"header", b"player");
+        assert_eq!(
+            server.open(b"header", &sealed),
+            Err(DecodeError::DecryptFailed)
+        );
+    }
+
+    #[test]
+    fn reject_tampered() {
+        let (mut server, mut client) = pair();
+        let mut sealed = client.seal(b"header", b"account");
+        assert_eq!(
+            server.open(b"other", &sealed),
+            Err(DecodeError::DecryptFailed)
+        );
+        let last = sealed.len() - 1;
+        sealed[last] ^= 1;
+        assert_eq!(
+            server.open(b"header", &sealed),
+            Err(DecodeError::DecryptFailed)
+        );
+    }
+
+    #[test]
+    fn reject_replay() {
+        let (mut server, mut client) = pair();
+        let packets: Vec<Vec<u8>> = (0..100).map(|_| client.seal(b"", b"x")).collect();
+        //乱序到达
+        assert!(server.open(b"", &packets[5]).is_ok());
+        assert!(server.open(b"", &packets[3]).is_ok());
+        assert_eq!(server.open(b"", &packets[5]), Err(DecodeError::Replayed(5)));
+        assert_eq!(server.open(b"", &packets[3]), Err(DecodeError::Replayed(3)));
+        assert!(server.open(b"", &packets[99]).is_ok());
+        //超出窗口的旧包
+        assert_eq!(
+            server.open(b"", &packets[10]),
+            Err(DecodeError::Replayed(10))
+        );
+        assert!(server.open(b"", &packets[40]).is_ok());
+    }
+
+    #[test]
+    fn reject_bad_public_key() {
+        let server_key = ServerKey::new([7; PUBLIC_KEY_LEN]);
+        let server = KeyExchange::new();
+        assert_eq!(
+            server.finish_server(&[1, 2, 3], &server_key).err(),
+            Some(CryptoError::InvalidPublicKey(3))
+        );
+        let server = KeyExchange::new();
+        assert_eq!(
+            server.finish_server(&[0u8; 32], &server_key).err(),
+            Some(CryptoError::NonContributory)
+        );
+        //固定的长期公钥是低阶点
+        let client = KeyExchange::new();
+        assert_eq!(
+            client
+                .finish_client(&KeyExchange::new().public_key(), &[0u8; 32])
+                .err(),
+            Some(CryptoError::NonContributory)
+        );
+    }
+
+    //中间人分别与两端交换临时密钥,没有网关的长期私钥时两端的会话密钥对不上
+    #[test]
+    fn reject_man_in_the_middle() {
+        let server_key = ServerKey::new([7; PUBLIC_KEY_LEN]);
+        let client = KeyExchange::new();
+        let client_public = client.public_key();
+        let attacker_key = ServerKey::new([9; PUBLIC_KEY_LEN]);
+        let attacker = KeyExchange::new();
+        let attacker_public = attacker.public_key();
+        let mut attacker = attacker
+            .finish_server(&client_public, &attacker_key)
+            .unwrap();
+        let mut client = client
+            .finish_client(&attacker_public, &server_key.public_key())
+            .unwrap();
+        let sealed = client.seal(b"header", b"account");
+        assert_eq!(
+            attacker.open(b"header", &sealed),
+            Err(DecodeError::DecryptFailed)
+        );
+    }
+
+    #[test]
+    fn key_hex() {
+        let key = decode_key(&"07".repeat(PUBLIC_KEY_LEN)).unwrap();
+        assert_eq!(key, [7; PUBLIC_KEY_LEN]);
+        assert!(matches!(
+            decode_key("0707"),
+            Err(CryptoError::InvalidKey(_))
+        ));
+        assert!(matches!(decode_key("zz"), Err(CryptoError::InvalidKey(_))));
+    }
+}
@@ -0,0 +1,268 @@
+use crate::gate::client::HEARTBEAT_INTERVAL;
+use crate::gate::crypto::{KeyExchange, ServerKey, SessionCipher};
+use crate::gate::packet::CodecOptions;
+use bytes::Bytes;
+use common::config::GateServerConfig;
+use protocol::base_cmd::{
+    Compression, Encryption, HandshakeAck, HandshakeReq, KickNtf, KickReason, Platform,
+};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+static SESSION_SEQ: AtomicU64 = AtomicU64::new(0);
+
+//握手协商的结果,之后的收发都按这里的选项处理
+#[derive(Debug, Clone)]
+pub struct Negotiated {
+    pub session_id: String,
+    pub client_version: String,
+    pub platform: Platform,
+    pub compression: Compression,
+    pub encryption: Encryption,
+    //本次连接的重连凭证,握手后通过ResumeRsp下发
+    pub token: String,
+    //服务端临时公钥,未协商加密时为空
+    pub public_key: Bytes,
+}
+
+impl Negotiated {
+    pub fn codec_options(&self, config: &GateServerConfig) -> CodecOptions {
+        CodecOptions {
+            compress_threshold: match self.compression {
+                Compression::CompressionLz4 => config.compress_threshold,
+                _ => None,
+            },
+        }
+    }
+
+    pub fn ack(&self) -> HandshakeAck {
+        HandshakeAck {
+            heartbeat_interval: HEARTBEAT_INTERVAL.as_millis() as u32,
+            server_time: common::time::now_ms() as i64,
+            compression: self.compression as i32,
+            encryption: self.encryption as i32,
+            session_id: self.session_id.clone(),
+            public_key: self.public_key.clone(),
+        }
+    }
+}
+
+//连接所在监听要求加密时传入网关的长期密钥,协商加密时同时返回连接的密钥
+pub fn negotiate(
+    config: &GateServerConfig,
+    req: HandshakeReq,
+    server_key: Option<&ServerKey>,
+) -> Result<(Negotiated, Option<SessionCipher>), KickNtf> {
+    if let Some(min_version) = &config.min_client_version
+        && !version_compatible(&req.client_version, min_version)
+    {
+        return Err(KickNtf {
+            reason: KickReason::KickVersionIncompatible as i32,
+            message: format!(
+                "client version:{} lower than:{}",
+                req.client_version, min_version
+            ),
+            reconnect_after: 0,
+        });
+    }
+    let compression = choose(&req.compressions, &supported_compressions(config))
+        .unwrap_or(Compression::CompressionNone);
+    let encryption = choose(
+        &req.encryptions,
+        &supported_encryptions(server_key.is_some()),
+    )
+    .unwrap_or(Encryption::EncryptionNone);
+    let (public_key, cipher) = match (encryption, server_key) {
+        (Encryption::EncryptionX25519Chacha20Poly1305, Some(server_key)) => {
+            let exchange = KeyExchange::new();
+            let public_key = Bytes::copy_from_slice(&exchange.public_key());
+            let cipher = exchange
+                .finish_server(&req.public_key, server_key)
+                .map_err(|e| KickNtf {
+                    reason: KickReason::KickBadHandshake as i32,
+                    message: e.to_string(),
+                    reconnect_after: 0,
+                })?;
+            (public_key, Some(cipher))
+        }
+        (_, Some(_)) => {
+            return Err(KickNtf {
+                reason: KickReason::KickEncryptionRequired as i32,
+                message: "encryption required".to_string(),
+                reconnect_after: 0,
+            });
+        }
+        (_, None) => (Bytes::new(), None),
+    };
+    let session_id = format!(
+        "{}-{}-{}",
+        config.id,
+        common::time::now_ms(),
+        SESSION_SEQ.fetch_add(1, Ordering::Relaxed)
+    );
+    let negotiated = Negotiated {
+        session_id,
+        platform: req.platform(),
+        client_version: req.client_version,
+        compression,
+        encryption,
+        token: new_token(),
+        public_key,
+    };
+    Ok((negotiated, cipher))
+}
+
+//128位随机数的十六进制
+fn new_token() -> String {
+    let mut bytes = [0u8; 16];
+    getrandom::getrandom(&mut bytes).expect("os random source unavailable");
+    bytes.iter().map(|x| format!("{:02x}", x)).collect()
+}
+
+//服务端支持的选项按优先级排列
+fn supported_compressions(config: &GateServerConfig) -> Vec<Compression> {
+    match config.compress_threshold {
+        Some(_) => vec![Compression::CompressionLz4],
+        None => vec![],
+    }
+}
+
+fn supported_encryptions(encrypt: bool) -> Vec<Encryption> {
+    match encrypt {
+        true => vec![Encryption::EncryptionX25519Chacha20Poly1305],
+        false => vec![],
+    }
+}
+
+//选出服务端优先级最高且客户端也支持的选项
+fn choose<T: Copy + Into<i32>>(offered: &[i32], supported: &[T]) -> Option<T> {
+    supported
+        .iter()
+        .find(|x| offered.contains(&(**x).into()))
+        .copied()
+}
+
+//按数字逐段比较版本号,格式错误的版本视为不兼容
+fn version_compatible(version: &str, min_version: &str) -> bool {
+    match (parse_version(version), parse_version(min_version)) {
+        (Some(version), Some(min_version)) => version >= min_version,
+        _ => false,
+    }
+}
+
+//末尾的0不参与比较,1.2和1.2.0相同
+fn parse_version(version: &str) -> Option<Vec<u32>> {
+    let mut parts = version
+        .trim()
+        .split('.')
+        .map(|x| x.parse::<u32>().ok())
+        .collect::<Option<Vec<u32>>>()?;
+    while parts.last() == Some(&0) {
+        parts.pop();
+    }
+    Some(parts)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn version() {
+        assert!(version_compatible("1.2.0", "1.2"));
+        assert!(version_compatible("1.10", "1.9.9"));
+        assert!(version_compatible("2", "1.9"));
+        assert!(!version_compatible("1.2", "1.2.1"));
+        assert!(!version_compatible("", "1.0"));
+        assert!(!version_compatible("1.x", "1.0"));
+    }
+
+    #[test]
+    fn reject_old_client() {
+        let config = GateServerConfig {
+            min_client_version: Some("1.2.0".to_string()),
+            ..Default::default()
+        };
+        let req = HandshakeReq {
+            client_version: "1.1.9".to_string(),
+            ..Default::default()
+        };
+        let kick = negotiate(&config, req, None).unwrap_err();
+        assert_eq!(kick.reason(), KickReason::KickVersionIncompatible);
+
+        let req = HandshakeReq {
+            client_version: "1.2.0".to_string(),
+            platform: Platform::PlatformAndroid as i32,
+            ..Default::default()
+        };
+        let (negotiated, _) = negotiate(&config, req.clone(), None).unwrap();
+        assert_eq!(negotiated.platform, Platform::PlatformAndroid);
+        assert_eq!(negotiated.compression, Compression::CompressionNone);
+        assert_eq!(negotiated.codec_options(&config).compress_threshold, None);
+        //每次握手生成新的凭证
+        let (other, _) = negotiate(&config, req, None).unwrap();
+        assert_eq!(negotiated.token.len(), 32);
+        assert_ne!(negotiated.token, other.token);
+    }
+
+    #[test]
+    fn negotiate_compression() {
+        let config = GateServerConfig {
+            compress_threshold: Some(512),
+            ..Default::default()
+        };
+        let req = HandshakeReq {
+            compressions: vec![Compression::CompressionLz4 as i32],
+            ..Default::default()
+        };
+        let (negotiated, _) = negotiate(&config, req, None).unwrap();
+        assert_eq!(negotiated.compression, Compression::CompressionLz4);
+        assert_eq!(
+            negotiated.codec_options(&config).compress_threshold,
+            Some(512)
+        );
+
+        //客户端不支持时不压缩
+        let (negotiated, _) = negotiate(&config, HandshakeReq::default(), None).unwrap();
+        assert_eq!(negotiated.compression, Compression::CompressionNone);
+    }
+
+    #[test]
+    fn negotiate_encryption() {
+        let config = GateServerConfig::default();
+        let server_key = ServerKey::new([7; 32]);
+        let client = KeyExchange::new();
+        let req = HandshakeReq {
+            encryptions: vec![Encryption::EncryptionX25519Chacha20Poly1305 as i32],
+            public_key: Bytes::copy_from_slice(&client.public_key()),
+            ..Default::default()
+        };
+        //监听不要求加密时不加密
+        let (negotiated, cipher) = negotiate(&config, req.clone(), None).unwrap();
+        assert_eq!(negotiated.encryption, Encryption::EncryptionNone);
+        assert!(cipher.is_none());
+
+        let (negotiated, cipher) = negotiate(&config, req, Some(&server_key)).unwrap();
+        assert_eq!(
+            negotiated.encryption,
+            Encryption::EncryptionX25519Chacha20Poly1305
+        );
+        let mut server = cipher.unwrap();
+        let mut client = client
+            .finish_client(&negotiated.ack().public_key, &server_key.public_key())
+            .unwrap();
+        let sealed = client.seal(b"", b"account");
+        assert_eq!(server.open(b"", &sealed).unwrap(), b"account");
+
+        //要求加密但客户端不支持
+        let kick = negotiate(&config, HandshakeReq::default(), Some(&server_key)).unwrap_err();
+        assert_eq!(kick.reason(), KickReason::KickEncryptionRequired);
+        //公钥错误
+        let req = HandshakeReq {
+            encryptions: vec![Encryption::EncryptionX25519Chacha20Poly1305 as i32],
+            public_key: Bytes::from_static(&[1, 2, 3]),
+            ..Default::default()
+        };
+        let kick = negotiate(&config, req, Some(&server_key)).unwrap_err();
+        assert_eq!(kick.reason(), KickReason::KickBadHandshake);
+    }
+}
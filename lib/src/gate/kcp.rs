@@ -0,0 +1,748 @@
+//! udp上的可靠传输,参考KCP的ARQ实现
+//!
+//! 每个udp endpoint一个会话,datagram内可以连续放多个segment:
+//!
+//! ```text
+//! +------+-----+-----+-----+-----+-----+-----+-----+------+
+//! | conv | cmd | frg | wnd | ts  | sn  | una | len | data |
+//! | u32  | u8  | u8  | u16 | u32 | u32 | u32 | u32 | len  |
+//! +------+-----+-----+-----+-----+-----+-----+-----+------+
+//! ```
+//!
+//! - 所有整数为小端,`sn`/`ts` 按u32回绕比较
+//! - `conv` 为会话id,由客户端随机生成,服务端按第一个datagram确定,之后conv不一致的datagram丢弃,
+//!   伪造源地址的一方猜不到conv,不能注入数据或FIN
+//! - `una` 为发送方期望收到的下一个sn,之前的都已确认;`wnd` 为发送方剩余的接收窗口
+//! - 一条消息超过mss时拆成多个segment,`frg` 为后面还剩的分片数
+//! - 一个segment承载一个完整的网关帧,由上层解码
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+use common::config::KcpConfig;
+use std::collections::VecDeque;
+use thiserror::Error;
+
+const CMD_PUSH: u8 = 81; //数据
+const CMD_ACK: u8 = 82; //确认
+const CMD_WASK: u8 = 83; //询问对端窗口
+const CMD_WINS: u8 = 84; //告知本端窗口
+const CMD_FIN: u8 = 85; //关闭会话
+pub const HEADER_LEN: usize = 24;
+const ASK_SEND: u8 = 1;
+const ASK_TELL: u8 = 2;
+const RTO_MAX: u32 = 60_000;
+const THRESH_INIT: u32 = 2;
+const THRESH_MIN: u32 = 2;
+const PROBE_INIT: u32 = 7_000;
+const PROBE_LIMIT: u32 = 120_000;
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum KcpError {
+    #[error("empty message")]
+    Empty,
+    #[error("message too large:{0}")]
+    TooLarge(usize),
+    #[error("malformed segment")]
+    Malformed,
+    #[error("unknown segment cmd:{0}")]
+    UnknownCmd(u8),
+    #[error("conversation mismatch:{0}")]
+    Conv(u32),
+}
+
+#[derive(Debug, Default, Clone)]
+struct Segment {
+    cmd: u8,
+    frg: u8,
+    wnd: u16,
+    ts: u32,
+    sn: u32,
+    una: u32,
+    resend_ts: u32,
+    rto: u32,
+    fastack: u32,
+    xmit: u32,
+    data: Bytes,
+}
+
+impl Segment {
+    fn encode(&self, conv: u32, output: &mut BytesMut) {
+        output.put_u32_le(conv);
+        output.put_u8(self.cmd);
+        output.put_u8(self.frg);
+        output.put_u16_le(self.wnd);
+        output.put_u32_le(self.ts);
+        output.put_u32_le(self.sn);
+        output.put_u32_le(self.una);
+        output.put_u32_le(self.data.len() as u32);
+        output.put_slice(&self.data);
+    }
+}
+
+//u32回绕比较,结果大于0表示a在b之后
+#[inline]
+fn diff(a: u32, b: u32) -> i32 {
+    a.wrapping_sub(b) as i32
+}
+
+//客户端发起会话时生成conv
+pub fn new_conv() -> u32 {
+    let mut bytes = [0u8; 4];
+    getrandom::getrandom(&mut bytes).expect("os random source unavailable");
+    u32::from_le_bytes(bytes)
+}
+
+//datagram中第一个segment的conv,服务端用来建立会话
+pub fn conv(data: &[u8]) -> Option<u32> {
+    data.get(..4)
+        .map(|x| u32::from_le_bytes([x[0], x[1], x[2], x[3]]))
+}
+
+pub struct Kcp {
+    conv: u32,
+    mtu: usize,
+    mss: usize,
+    snd_una: u32,
+    snd_nxt: u32,
+    rcv_nxt: u32,
+    ssthresh: u32,
+    rx_rttval: u32,
+    rx_srtt: u32,
+    rx_rto: u32,
+    rx_minrto: u32,
+    snd_wnd: u16,
+    rcv_wnd: u16,
+    rmt_wnd: u16,
+    cwnd: u32,
+    incr: u32,
+    probe: u8,
+    ts_probe: u32,
+    probe_wait: u32,
+    dead_link: u32,
+    interval: u32,
+    ts_flush: u32,
+    updated: bool,
+    fast_resend: u32,
+    no_cwnd: bool,
+    dead: bool,
+    fin: bool,
+    snd_queue: VecDeque<Segment>,
+    snd_buf: VecDeque<Segment>,
+    rcv_queue: VecDeque<Segment>,
+    rcv_buf: VecDeque<Segment>,
+    acklist: Vec<(u32, u32)>,
+    buffer: BytesMut,
+    output: Vec<Bytes>,
+}
+
+impl Kcp {
+    pub fn new(config: &KcpConfig, conv: u32) -> Self {
+        let mtu = config.mtu.max(HEADER_LEN + 1);
+        let rcv_wnd = config.rcv_wnd.max(1);
+        Self {
+            conv,
+            mtu,
+            mss: mtu - HEADER_LEN,
+            snd_una: 0,
+            snd_nxt: 0,
+            rcv_nxt: 0,
+            ssthresh: THRESH_INIT,
+            rx_rttval: 0,
+            rx_srtt: 0,
+            rx_rto: 200,
+            rx_minrto: config.min_rto,
+            snd_wnd: config.snd_wnd.max(1),
+            rcv_wnd,
+            rmt_wnd: rcv_wnd,
+            cwnd: 1,
+            incr: 0,
+            probe: 0,
+            ts_probe: 0,
+            probe_wait: 0,
+            dead_link: config.dead_link,
+            interval: config.interval.clamp(1, 5_000),
+            ts_flush: 0,
+            updated: false,
+            fast_resend: config.fast_resend,
+            no_cwnd: config.no_cwnd,
+            dead: false,
+            fin: false,
+            snd_queue: VecDeque::new(),
+            snd_buf: VecDeque::new(),
+            rcv_queue: VecDeque::new(),
+            rcv_buf: VecDeque::new(),
+            acklist: vec![],
+            buffer: BytesMut::with_capacity(mtu),
+            output: vec![],
+        }
+    }
+
+    //放入发送队列,由flush按窗口发出
+    pub fn send(&mut self, data: &[u8]) -> Result<(), KcpError> {
+        if data.is_empty() {
+            return Err(KcpError::Empty);
+        }
+        let count = data.len().div_ceil(self.mss);
+        if count > u8::MAX as usize || count >= self.rcv_wnd as usize {
+            return Err(KcpError::TooLarge(data.len()));
+        }
+        for (i, chunk) in data.chunks(self.mss).enumerate() {
+            self.snd_queue.push_back(Segment {
+                cmd: CMD_PUSH,
+                frg: (count - i - 1) as u8,
+                data: Bytes::copy_from_slice(chunk),
+                ..Default::default()
+            });
+        }
+        Ok(())
+    }
+
+    //按顺序取出一条完整消息
+    pub fn recv(&mut self) -> Option<Bytes> {
+        let first = self.rcv_queue.front()?;
+        let count = first.frg as usize + 1;
+        if self.rcv_queue.len() < count {
+            return None;
+        }
+        let recover = self.rcv_queue.len() >= self.rcv_wnd as usize;
+        let message = if count == 1 {
+            self.rcv_queue.pop_front().map(|x| x.data)
+        } else {
+            let mut message = BytesMut::new();
+            for seg in self.rcv_queue.drain(..count) {
+                message.put_slice(&seg.data);
+            }
+            Some(message.freeze())
+        };
+        self.move_rcv_buf();
+        //接收窗口从满变为可用,主动告知对端
+        if recover && self.rcv_queue.len() < self.rcv_wnd as usize {
+            self.probe |= ASK_TELL;
+        }
+        message
+    }
+
+    //处理收到的datagram
+    pub fn input(&mut self, mut data: &[u8], now: u32) -> Result<(), KcpError> {
+        if data.len() < HEADER_LEN {
+            return Err(KcpError::Malformed);
+        }
+        let prev_una = self.snd_una;
+        let mut max_ack: Option<(u32, u32)> = None;
+        while data.len() >= HEADER_LEN {
+            let conv = data.get_u32_le();
+            if conv != self.conv {
+                return Err(KcpError::Conv(conv));
+            }
+            let cmd = data.get_u8();
+            let frg = data.get_u8();
+            let wnd = data.get_u16_le();
+            let ts = data.get_u32_le();
+            let sn = data.get_u32_le();
+            let una = data.get_u32_le();
+            let len = data.get_u32_le() as usize;
+            if data.len() < len {
+                return Err(KcpError::Malformed);
+            }
+            if !matches!(cmd, CMD_PUSH | CMD_ACK | CMD_WASK | CMD_WINS | CMD_FIN) {
+                return Err(KcpError::UnknownCmd(cmd));
+            }
+            self.rmt_wnd = wnd;
+            self.parse_una(una);
+            self.shrink_buf();
+            match cmd {
+                CMD_ACK => {
+                    if diff(now, ts) >= 0 {
+                        self.update_ack(diff(now, ts) as u32);
+                    }
+                    self.parse_ack(sn);
+                    self.shrink_buf();
+                    max_ack = match max_ack {
+                        Some((max_sn, _)) if diff(sn, max_sn) <= 0 => max_ack,
+                        _ => Some((sn, ts)),
+                    };
+                }
+                CMD_PUSH => {
+                    if diff(sn, self.rcv_nxt.wrapping_add(self.rcv_wnd as u32)) < 0 {
+                        self.acklist.push((sn, ts));
+                        if diff(sn, self.rcv_nxt) >= 0 {
+                            self.parse_data(Segment {
+                                cmd,
+                                frg,
+                                wnd,
+                                ts,
+                                sn,
+                                una,
+                                data: Bytes::copy_from_slice(&data[..len]),
+                                ..Default::default()
+                            });
+                        }
+                    }
+                }
+                CMD_WASK => self.probe |= ASK_TELL,
+                CMD_WINS => {}
+                _ => self.fin = true,
+            }
+            data.advance(len);
+        }
+        if let Some((sn, ts)) = max_ack {
+            self.parse_fastack(sn, ts);
+        }
+        //有新的确认,增大拥塞窗口
+        if diff(self.snd_una, prev_una) > 0 && self.cwnd < self.rmt_wnd as u32 {
+            let mss = self.mss as u32;
+            if self.cwnd < self.ssthresh {
+                self.cwnd += 1;
+                self.incr += mss;
+            } else {
+                self.incr = self.incr.max(mss);
+                self.incr += mss * mss / self.incr + mss / 16;
+                if (self.cwnd + 1) * mss <= self.incr {
+                    self.cwnd = self.incr.div_ceil(mss);
+                }
+            }
+            if self.cwnd > self.rmt_wnd as u32 {
+                self.cwnd = self.rmt_wnd as u32;
+                self.incr = self.rmt_wnd as u32 * mss;
+            }
+        }
+        Ok(())
+    }
+
+    //按interval定时调用,到时间才flush
+    pub fn update(&mut self, now: u32) {
+        if !self.updated {
+            self.updated = true;
+            self.ts_flush = now;
+        }
+        let mut slap = diff(now, self.ts_flush);
+        if !(-10_000..10_000).contains(&slap) {
+            self.ts_flush = now;
+            slap = 0;
+        }
+        if slap >= 0 {
+            self.ts_flush = self.ts_flush.wrapping_add(self.interval);
+            if diff(now, self.ts_flush) >= 0 {
+                self.ts_flush = now.wrapping_add(self.interval);
+            }
+            self.flush(now);
+        }
+    }
+
+    //发出ack、窗口探测、新数据和需要重传的数据
+    pub fn flush(&mut self, now: u32) {
+        if !self.updated {
+            return;
+        }
+        let wnd = self.wnd_unused();
+        let mut seg = Segment {
+            cmd: CMD_ACK,
+            wnd,
+            una: self.rcv_nxt,
+            ..Default::default()
+        };
+        for (sn, ts) in std::mem::take(&mut self.acklist) {
+            seg.sn = sn;
+            seg.ts = ts;
+            self.write(&seg);
+        }
+
+        //对端窗口为0时定时探测
+        if self.rmt_wnd == 0 {
+            if self.probe_wait == 0 {
+                self.probe_wait = PROBE_INIT;
+                self.ts_probe = now.wrapping_add(self.probe_wait);
+            } else if diff(now, self.ts_probe) >= 0 {
+                self.probe_wait = (self.probe_wait + self.probe_wait / 2).min(PROBE_LIMIT);
+                self.ts_probe = now.wrapping_add(self.probe_wait);
+                self.probe |= ASK_SEND;
+            }
+        } else {
+            self.ts_probe = 0;
+            self.probe_wait = 0;
+        }
+        seg.sn = 0;
+        seg.ts = 0;
+        if self.probe & ASK_SEND != 0 {
+            seg.cmd = CMD_WASK;
+            self.write(&seg);
+        }
+        if self.probe & ASK_TELL != 0 {
+            seg.cmd = CMD_WINS;
+            self.write(&seg);
+        }
+        self.probe = 0;
+
+        let mut cwnd = self.snd_wnd.min(self.rmt_wnd) as u32;
+        if !self.no_cwnd {
+            cwnd = cwnd.min(self.cwnd);
+        }
+        while diff(self.snd_nxt, self.snd_una.wrapping_add(cwnd)) < 0 {
+            let Some(mut seg) = self.snd_queue.pop_front() else {
+                break;
+            };
+            seg.sn = self.snd_nxt;
+            self.snd_nxt = self.snd_nxt.wrapping_add(1);
+            seg.ts = now;
+            seg.resend_ts = now;
+            seg.rto = self.rx_rto;
+            self.snd_buf.push_back(seg);
+        }
+
+        let resent = match self.fast_resend {
+            0 => u32::MAX,
+            x => x,
+        };
+        let mut change = false;
+        let mut lost = false;
+        let mut snd_buf = std::mem::take(&mut self.snd_buf);
+        for seg in snd_buf.iter_mut() {
+            let need_send = if seg.xmit == 0 {
+                seg.rto = self.rx_rto;
+                seg.resend_ts = now.wrapping_add(seg.rto);
+                true
+            } else if diff(now, seg.resend_ts) >= 0 {
+                //超时重传,rto按1.5倍增长
+                seg.rto = (seg.rto + seg.rto / 2).min(RTO_MAX);
+                seg.resend_ts = now.wrapping_add(seg.rto);
+                lost = true;
+                true
+            } else if seg.fastack >= resent {
+                seg.fastack = 0;
+                seg.resend_ts = now.wrapping_add(seg.rto);
+                change = true;
+                true
+            } else {
+                false
+            };
+            if need_send {
+                seg.xmit += 1;
+                seg.ts = now;
+                seg.wnd = wnd;
+                seg.una = self.rcv_nxt;
+                self.write(seg);
+                if seg.xmit >= self.dead_link {
+                    self.dead = true;
+                }
+            }
+        }
+        self.snd_buf = snd_buf;
+        self.flush_buffer();
+
+        if change {
+            let inflight = self.snd_nxt.wrapping_sub(self.snd_una);
+            self.ssthresh = (inflight / 2).max(THRESH_MIN);
+            self.cwnd = self.ssthresh + resent.min(self.ssthresh);
+            self.incr = self.cwnd * self.mss as u32;
+        }
+        if lost {
+            self.ssthresh = (self.cwnd / 2).max(THRESH_MIN);
+            self.cwnd = 1;
+            self.incr = self.mss as u32;
+        }
+        if self.cwnd < 1 {
+            self.cwnd = 1;
+            self.incr = self.mss as u32;
+        }
+    }
+
+    //取出待发送的datagram
+    pub fn take_output(&mut self) -> Vec<Bytes> {
+        std::mem::take(&mut self.output)
+    }
+
+    //关闭会话的datagram,不保证送达
+    pub fn fin(&self) -> Bytes {
+        let mut output = BytesMut::with_capacity(HEADER_LEN);
+        Segment {
+            cmd: CMD_FIN,
+            wnd: self.wnd_unused(),
+            una: self.rcv_nxt,
+            ..Default::default()
+        }
+        .encode(self.conv, &mut output);
+        output.freeze()
+    }
+
+    //重传次数达到dead_link,认为连接已断开
+    pub fn is_dead(&self) -> bool {
+        self.dead
+    }
+
+    //对端主动关闭
+    pub fn is_fin(&self) -> bool {
+        self.fin
+    }
+
+    //发送的数据都已被确认
+    pub fn is_idle(&self) -> bool {
+        self.snd_queue.is_empty() && self.snd_buf.is_empty()
+    }
+
+    //还没被确认的segment数量,包括还没发出的
+    pub fn wait_snd(&self) -> usize {
+        self.snd_queue.len() + self.snd_buf.len()
+    }
+
+    fn wnd_unused(&self) -> u16 {
+        (self.rcv_wnd as usize).saturating_sub(self.rcv_queue.len()) as u16
+    }
+
+    fn write(&mut self, seg: &Segment) {
+        if self.buffer.len() + HEADER_LEN + seg.data.len() > self.mtu {
+            self.flush_buffer();
+        }
+        seg.encode(self.conv, &mut self.buffer);
+    }
+
+    fn flush_buffer(&mut self) {
+        if !self.buffer.is_empty() {
+            self.output.push(self.buffer.split().freeze());
+        }
+    }
+
+    fn update_ack(&mut self, rtt: u32) {
+        if self.rx_srtt == 0 {
+            self.rx_srtt = rtt;
+            self.rx_rttval = rtt / 2;
+        } else {
+            let delta = rtt.abs_diff(self.rx_srtt);
+            self.rx_rttval = (3 * self.rx_rttval + delta) / 4;
+            self.rx_srtt = ((7 * self.rx_srtt + rtt) / 8).max(1);
+        }
+        let rto = self.rx_srtt + self.interval.max(4 * self.rx_rttval);
+        self.rx_rto = rto.clamp(self.rx_minrto, RTO_MAX);
+    }
+
+    fn shrink_buf(&mut self) {
+        self.snd_una = self.snd_buf.front().map_or(self.snd_nxt, |x| x.sn);
+    }
+
+    fn parse_ack(&mut self, sn: u32) {
+        if diff(sn, self.snd_una) < 0 || diff(sn, self.snd_nxt) >= 0 {
+            return;
+        }
+        if let Some(index) = self.snd_buf.iter().position(|x| x.sn == sn) {
+            self.snd_buf.remove(index);
+        }
+    }
+
+    fn parse_una(&mut self, una: u32) {
+        while let Some(seg) = self.snd_buf.front() {
+            if diff(seg.sn, una) >= 0 {
+                break;
+            }
+            self.snd_buf.pop_front();
+        }
+    }
+
+    //跳过的segment累计fastack,达到fast_resend后不等超时直接重传
+    fn parse_fastack(&mut self, sn: u32, ts: u32) {
+        if diff(sn, self.snd_una) < 0 || diff(sn, self.snd_nxt) >= 0 {
+            return;
+        }
+        for seg in self.snd_buf.iter_mut() {
+            if diff(sn, seg.sn) <= 0 {
+                break;
+            }
+            if diff(ts, seg.ts) >= 0 {
+                seg.fastack += 1;
+            }
+        }
+    }
+
+    fn parse_data(&mut self, seg: Segment) {
+        let sn = seg.sn;
+        if diff(sn, self.rcv_nxt.wrapping_add(self.rcv_wnd as u32)) >= 0
+            || diff(sn, self.rcv_nxt) < 0
+        {
+            return;
+        }
+        //rcv_buf按sn有序,重复的丢弃
+        let index = self
+            .rcv_buf
+            .iter()
+            .rposition(|x| diff(sn, x.sn) >= 0)
+            .map_or(0, |i| i + 1);
+        if index > 0 && self.rcv_buf[index - 1].sn == sn {
+            return;
+        }
+        self.rcv_buf.insert(index, seg);
+        self.move_rcv_buf();
+    }
+
+    fn move_rcv_buf(&mut self) {
+        while let Some(seg) = self.rcv_buf.front() {
+            if seg.sn != self.rcv_nxt || self.rcv_queue.len() >= self.rcv_wnd as usize {
+                break;
+            }
+            self.rcv_nxt = self.rcv_nxt.wrapping_add(1);
+            let seg = self.rcv_buf.pop_front().unwrap();
+            self.rcv_queue.push_back(seg);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn config() -> KcpConfig {
+        KcpConfig {
+            mtu: 200,
+            ..Default::default()
+        }
+    }
+
+    //确定性的丢包和乱序
+    struct Link {
+        seed: u64,
+        loss: u64,
+    }
+
+    impl Link {
+        fn next(&mut self) -> u64 {
+            self.seed = self
+                .seed
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            self.seed >> 33
+        }
+
+        fn transfer(&mut self, from: &mut Kcp, to: &mut Kcp, now: u32) {
+            let mut datagrams = from.take_output();
+            if datagrams.len() > 1 && self.next().is_multiple_of(2) {
+                datagrams.reverse();
+            }
+            for datagram in datagrams {
+                if self.next() % 100 >= self.loss {
+                    to.input(&datagram, now).unwrap();
+                }
+            }
+        }
+    }
+
+    fn run(loss: u64, messages: &[Vec<u8>]) -> Vec<Bytes> {
+        let mut server = Kcp::new(&config(), 1);
+        let mut client = Kcp::new(&config(), 1);
+        let mut link = Link { seed: 7, loss };
+        for message in messages {
+            client.send(message).unwrap();
+        }
+        let mut received = vec![];
+        let mut now = 0u32;
+        while received.len() < messages.len() && now < 600_000 {
+            client.update(now);
+            link.transfer(&mut client, &mut server, now);
+            server.update(now);
+            link.transfer(&mut server, &mut client, now);
+            while let Some(message) = server.recv() {
+                received.push(message);
+            }
+            now += 10;
+        }
+        assert!(!client.is_dead());
+        received
+    }
+
+    fn messages() -> Vec<Vec<u8>> {
+        (0..200u32)
+            .map(|i| {
+                let len = 1 + (i as usize * 37) % 900;
+                (0..len).map(|x| (x as u32 + i) as u8).collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn in_order_without_loss() {
+        let messages = messages();
+        let received = run(0, &messages);
+        assert_eq!(received, messages);
+    }
+
+    #[test]
+    fn in_order_with_loss() {
+        let messages = messages();
+        let received = run(20, &messages);
+        assert_eq!(received, messages);
+    }
+
+    #[test]
+    fn dead_link() {
+        let mut client = Kcp::new(&config(), 1);
+        client.send(b"hello").unwrap();
+        let mut now = 0;
+        while !client.is_dead() && now < 600_000 {
+            client.update(now);
+            client.take_output();
+            now += 10;
+        }
+        assert!(client.is_dead());
+        assert!(!client.is_idle());
+    }
+
+    #[test]
+    fn fin_and_errors() {
+        let mut server = Kcp::new(&config(), 1);
+        let client = Kcp::new(&config(), 1);
+        server.input(&client.fin(), 0).unwrap();
+        assert!(server.is_fin());
+        assert_eq!(server.input(&[CMD_PUSH; 3], 0), Err(KcpError::Malformed));
+        let mut unknown = [0; HEADER_LEN];
+        unknown[0] = 1;
+        assert_eq!(server.input(&unknown, 0), Err(KcpError::UnknownCmd(0)));
+        assert_eq!(server.send(&[]), Err(KcpError::Empty));
+        assert!(matches!(
+            server.send(&vec![0; 200 * 256]),
+            Err(KcpError::TooLarge(_))
+        ));
+    }
+
+    //conv不一致的datagram不影响会话,伪造的FIN被丢弃
+    #[test]
+    fn conv_mismatch() {
+        let mut server = Kcp::new(&config(), 7);
+        let mut client = Kcp::new(&config(), 7);
+        let spoofed = Kcp::new(&config(), 8);
+        assert_eq!(conv(&client.fin()), Some(7));
+        assert_eq!(server.input(&spoofed.fin(), 0), Err(KcpError::Conv(8)));
+        assert!(!server.is_fin());
+
+        client.send(b"hello").unwrap();
+        client.update(0);
+        for datagram in client.take_output() {
+            server.input(&datagram, 0).unwrap();
+        }
+        assert_eq!(server.recv().unwrap().as_ref(), b"hello");
+        assert_eq!(conv(&[1, 2]), None);
+    }
+
+    #[test]
+    fn wrapping_sequence() {
+        let mut server = Kcp::new(&config(), 1);
+        let mut client = Kcp::new(&config(), 1);
+        for kcp in [&mut server, &mut client] {
+            kcp.snd_una = u32::MAX - 5;
+            kcp.snd_nxt = u32::MAX - 5;
+            kcp.rcv_nxt = u32::MAX - 5;
+        }
+        let mut link = Link { seed: 3, loss: 10 };
+        let messages: Vec<Vec<u8>> = (0..50u8).map(|i| vec![i; 10]).collect();
+        for message in &messages {
+            client.send(message).unwrap();
+        }
+        let mut received = vec![];
+        let mut now = u32::MAX - 1000;
+        for _ in 0..10_000 {
+            client.update(now);
+            link.transfer(&mut client, &mut server, now);
+            server.update(now);
+            link.transfer(&mut server, &mut client, now);
+            while let Some(message) = server.recv() {
+                received.push(message.to_vec());
+            }
+            now = now.wrapping_add(10);
+        }
+        assert_eq!(received, messages);
+        assert!(client.is_idle());
+    }
+}
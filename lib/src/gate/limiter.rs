@@ -0,0 +1,224 @@
+//! 客户端连接的限流
+//!
+//! - 每个连接按包数和字节数各一个令牌桶,解码前检查
+//! - 配置了限制的cmd各一个令牌桶,解码后检查
+//! - 单个ip的连接数在建立连接时检查
+use common::config::{RateLimitConfig, RateLimitPolicy};
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::time::{Duration, Instant};
+
+//令牌桶,按速率补充,最多积累burst个
+#[derive(Debug, Clone)]
+pub struct TokenBucket {
+    rate: f64,
+    burst: f64,
+    tokens: f64,
+    last: Instant,
+}
+
+impl TokenBucket {
+    //速率为0时不限制
+    pub fn new(rate: u32, burst: u32, now: Instant) -> Option<Self> {
+        if rate == 0 {
+            return None;
+        }
+        let burst = if burst == 0 { rate } else { burst } as f64;
+        Some(Self {
+            rate: rate as f64,
+            burst,
+            tokens: burst,
+            last: now,
+        })
+    }
+
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.last).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.rate).min(self.burst);
+        self.last = now;
+    }
+
+    //返回还需要等待的时间
+    fn wait(&mut self, n: f64, now: Instant) -> Duration {
+        self.refill(now);
+        //超过burst的消耗按burst算,否则永远无法通过
+        let n = n.min(self.burst);
+        if self.tokens >= n {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64((n - self.tokens) / self.rate)
+        }
+    }
+
+    fn take(&mut self, n: f64) {
+        self.tokens -= n.min(self.burst);
+    }
+
+    //令牌足够时消耗并返回None,否则返回需要等待的时间
+    pub fn check(&mut self, n: u32, now: Instant) -> Option<Duration> {
+        let wait = self.wait(n as f64, now);
+        if wait.is_zero() {
+            self.take(n as f64);
+            None
+        } else {
+            Some(wait)
+        }
+    }
+}
+
+//一个连接的限流状态
+#[derive(Debug, Clone)]
+pub struct RateLimiter {
+    pub policy: RateLimitPolicy,
+    packets: Option<TokenBucket>,
+    bytes: Option<TokenBucket>,
+    cmds: HashMap<i32, TokenBucket>,
+}
+
+impl RateLimiter {
+    pub fn new(config: &RateLimitConfig) -> Self {
+        let now = Instant::now();
+        Self {
+            policy: config.policy,
+            packets: TokenBucket::new(config.packets_per_sec, config.packet_burst, now),
+            bytes: TokenBucket::new(config.bytes_per_sec, config.byte_burst, now),
+            cmds: config
+                .cmds
+                .iter()
+                .filter_map(|x| Some((x.cmd, TokenBucket::new(x.per_sec, x.burst, now)?)))
+                .collect(),
+        }
+    }
+
+    //收到一个包,包数和字节数都满足才消耗
+    pub fn check_packet(&mut self, len: usize, now: Instant) -> Option<Duration> {
+        let packets = self
+            .packets
+            .as_mut()
+            .map_or(Duration::ZERO, |x| x.wait(1.0, now));
+        let bytes = self
+            .bytes
+            .as_mut()
+            .map_or(Duration::ZERO, |x| x.wait(len as f64, now));
+        let wait = packets.max(bytes);
+        if !wait.is_zero() {
+            return Some(wait);
+        }
+        if let Some(x) = self.packets.as_mut() {
+            x.take(1.0);
+        }
+        if let Some(x) = self.bytes.as_mut() {
+            x.take(len as f64);
+        }
+        None
+    }
+
+    pub fn check_cmd(&mut self, cmd: i32, now: Instant) -> Option<Duration> {
+        self.cmds.get_mut(&cmd)?.check(1, now)
+    }
+}
+
+//每个ip的连接数
+#[derive(Debug, Default)]
+pub struct ConnectionLimiter {
+    max: u32,
+    counts: HashMap<IpAddr, u32>,
+}
+
+impl ConnectionLimiter {
+    pub fn new(max: u32) -> Self {
+        Self {
+            max,
+            counts: HashMap::new(),
+        }
+    }
+
+    //超过上限时返回false,不计数
+    pub fn acquire(&mut self, ip: IpAddr) -> bool {
+        let count = self.counts.entry(ip).or_default();
+        if self.max > 0 && *count >= self.max {
+            return false;
+        }
+        *count += 1;
+        true
+    }
+
+    pub fn release(&mut self, ip: IpAddr) {
+        if let Some(count) = self.counts.get_mut(&ip) {
+            *count = count.saturating_sub(1);
+            if *count == 0 {
+                self.counts.remove(&ip);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use common::config::CmdRateLimit;
+
+    #[test]
+    fn token_bucket() {
+        let now = Instant::now();
+        assert!(TokenBucket::new(0, 10, now).is_none());
+        let mut bucket = TokenBucket::new(10, 20, now).unwrap();
+        for _ in 0..20 {
+            assert_eq!(bucket.check(1, now), None);
+        }
+        assert_eq!(bucket.check(1, now), Some(Duration::from_millis(100)));
+        //补充速率为每秒10个
+        let now = now + Duration::from_millis(500);
+        for _ in 0..5 {
+            assert_eq!(bucket.check(1, now), None);
+        }
+        assert!(bucket.check(1, now).is_some());
+        //不超过burst
+        let now = now + Duration::from_secs(60);
+        assert_eq!(bucket.check(100, now), None);
+        assert!(bucket.check(1, now).is_some());
+    }
+
+    #[test]
+    fn packets_and_bytes() {
+        let config = RateLimitConfig {
+            packets_per_sec: 10,
+            bytes_per_sec: 100,
+            cmds: vec![CmdRateLimit {
+                cmd: 1001,
+                per_sec: 1,
+                burst: 2,
+            }],
+            ..Default::default()
+        };
+        let mut limiter = RateLimiter::new(&config);
+        let now = Instant::now();
+        assert_eq!(limiter.check_packet(60, now), None);
+        //字节数不够时包数也不消耗
+        assert!(limiter.check_packet(60, now).is_some());
+        for _ in 0..9 {
+            assert_eq!(limiter.check_packet(1, now), None);
+        }
+        assert!(limiter.check_packet(1, now).is_some());
+
+        assert_eq!(limiter.check_cmd(1001, now), None);
+        assert_eq!(limiter.check_cmd(1001, now), None);
+        assert_eq!(limiter.check_cmd(1001, now), Some(Duration::from_secs(1)));
+        assert_eq!(limiter.check_cmd(1002, now), None);
+    }
+
+    #[test]
+    fn connections_per_ip() {
+        let ip: IpAddr = "127.0.0.1".parse().unwrap();
+        let other: IpAddr = "127.0.0.2".parse().unwrap();
+        let mut limiter = ConnectionLimiter::new(2);
+        assert!(limiter.acquire(ip));
+        assert!(limiter.acquire(ip));
+        assert!(!limiter.acquire(ip));
+        assert!(limiter.acquire(other));
+        limiter.release(ip);
+        assert!(limiter.acquire(ip));
+        let mut unlimited = ConnectionLimiter::new(0);
+        assert!((0..100).all(|_| unlimited.acquire(ip)));
+    }
+}
@@ -1,22 +1,37 @@
-use crate::gate::net_server::{NetServer, NetServerSignal};
+use crate::discovery::NodeManager;
+use crate::gate::client::ClientMessage;
+use crate::gate::net_server::{GateStats, NetServer, NetServerSignal};
+use crate::gate::packet::SharedPayload;
+use crate::gate::session::SessionRegistry;
+use bytes::Bytes;
 use common::config::{GateServerConfig, GlobalConfig, ServerRoleId};
 use kameo::actor::{ActorRef, WeakActorRef};
 use kameo::error::ActorStopReason;
-use kameo::{Actor, RemoteActor};
+use kameo::message::{Context, Message};
+use kameo::{Actor, RemoteActor, remote_message};
 use message_io::node::{NodeHandler, NodeTask};
+use protocol::base_cmd::{KickNtf, KickReason};
+use serde::{Deserialize, Serialize};
 use std::fmt::Display;
 use std::sync::Arc;
 
 pub mod client;
+pub mod crypto;
+pub mod handshake;
+pub mod kcp;
+pub mod limiter;
 pub mod net_server;
 pub mod node;
 pub mod packet;
+pub mod session;
 #[derive(RemoteActor)]
 pub struct GateActor {
     global_config: Arc<GlobalConfig>,
     role_id: ServerRoleId,
     gate_config: GateServerConfig,
     node_task: Option<(NodeTask, NodeHandler<NetServerSignal>)>,
+    sessions: Arc<SessionRegistry>,
+    stats: Arc<GateStats>,
 }
 
 impl GateActor {
@@ -30,6 +45,8 @@ impl GateActor {
             global_config,
             gate_config,
             node_task: None,
+            sessions: Arc::new(SessionRegistry::default()),
+            stats: Arc::new(GateStats::default()),
         }
     }
 }
@@ -46,9 +63,10 @@ impl Actor for GateActor {
             })?;
         //启动监听
         let net_server = NetServer::new(
-            self.gate_config.out_tcp_port,
-            self.gate_config.out_ws_port,
-            self.gate_config.out_udp_port,
+            Arc::new(self.gate_config.clone()),
+            Arc::new(NodeManager::new()),
+            self.sessions.clone(),
+            self.stats.clone(),
         )
         .map_err(|e| {
             tracing::error!("GateActor ListenNetFail fail:{}", e);
@@ -75,6 +93,136 @@ impl Actor for GateActor {
     }
 }
 
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub enum KickTarget {
+    Account(String),
+    Player(i64),
+}
+
+//推送的对象,只推送给在本网关的玩家
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub enum PushTarget {
+    Player(i64),
+    Players(Vec<i64>),
+    //频道(公会、房间等),成员通过JoinChannel加入
+    Channel(String),
+    //本网关的所有连接,包括还没有登录的
+    All,
+}
+
+#[derive(Deserialize, Serialize)]
+pub enum GateMessage {
+    //踢玩家下线,reason为KickReason
+    Kick {
+        target: KickTarget,
+        reason: i32,
+        message: String,
+        reconnect_after: u32,
+    },
+    //推送,data为已经编码好的protobuf消息
+    Push {
+        target: PushTarget,
+        cmd: i32,
+        data: Bytes,
+    },
+    //加入频道,发给所有网关,每个网关只记录当时在本网关(包括断线保留中)的玩家,其他的忽略
+    //频道成员不跟随玩家迁移,之后登录到其他网关的玩家需要重新加入
+    JoinChannel {
+        channel: String,
+        players: Vec<i64>,
+    },
+    LeaveChannel {
+        channel: String,
+        players: Vec<i64>,
+    },
+    //停服维护:停止接收新连接,所有连接以KickMaintenance踢下线,断线保留的会话丢弃
+    Drain {
+        message: String,
+        reconnect_after: u32,
+    },
+}
+#[remote_message("GateMessage")]
+impl Message<GateMessage> for GateActor {
+    type Reply = bool;
+
+    async fn handle(
+        &mut self,
+        msg: GateMessage,
+        _ctx: &mut Context<Self, Self::Reply>,
+    ) -> Self::Reply {
+        match msg {
+            GateMessage::Kick {
+                target,
+                reason,
+                message,
+                reconnect_after,
+            } => {
+                let Some(actor_ref) = self.sessions.find(&target) else {
+                    //断线保留中的会话直接丢弃
+                    return self.sessions.discard(&target);
+                };
+                tracing::info!("kick {:?} reason:{} message:{}", target, reason, message);
+                let kick = KickNtf {
+                    reason,
+                    message,
+                    reconnect_after,
+                };
+                actor_ref.tell(ClientMessage::Kick(kick)).await.is_ok()
+            }
+            //返回是否推送给了至少一个玩家
+            GateMessage::Push { target, cmd, data } => {
+                //所有连接共用一份负载
+                let payload = Arc::new(SharedPayload::new(cmd, data));
+                let players = match target {
+                    PushTarget::Player(player_id) => vec![player_id],
+                    PushTarget::Players(players) => players,
+                    PushTarget::Channel(channel) => self.sessions.members(&channel),
+                    PushTarget::All => return self.sessions.push_all(&payload) > 0,
+                };
+                let mut count = 0;
+                for player_id in players {
+                    if self.sessions.push(player_id, &payload) {
+                        count += 1;
+                    }
+                }
+                count > 0
+            }
+            //返回是否有玩家加入
+            GateMessage::JoinChannel { channel, players } => {
+                let ignored = self.sessions.join(&channel, &players);
+                if !ignored.is_empty() {
+                    tracing::debug!(
+                        "channel:{} players not on this gate ignored:{:?}",
+                        channel,
+                        ignored
+                    );
+                }
+                ignored.len() < players.len()
+            }
+            GateMessage::LeaveChannel { channel, players } => {
+                self.sessions.leave(&channel, &players);
+                true
+            }
+            GateMessage::Drain {
+                message,
+                reconnect_after,
+            } => {
+                let Some((_, handler)) = &self.node_task else {
+                    return false;
+                };
+                let kick = KickNtf {
+                    reason: KickReason::KickMaintenance as i32,
+                    message,
+                    reconnect_after,
+                };
+                handler.signals().send(NetServerSignal::Drain(kick));
+                self.sessions.expire(u128::MAX);
+                true
+            }
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub enum GateActorError {
     ConnectFail(String),
@@ -1,42 +1,180 @@
-use crate::gate::client::{ClientActor, ClientMessage};
-use crate::gate::packet::{Decoder, Encoder, Packet};
+use crate::discovery::NodeManager;
+use crate::gate::client::{ClientActor, ClientMessage, HEARTBEAT_INTERVAL};
+use crate::gate::crypto::{self, ServerKey};
+use crate::gate::kcp::{self, Kcp};
+use crate::gate::limiter::ConnectionLimiter;
+use crate::gate::session::SessionRegistry;
 use bytes::{Buf, BufMut, Bytes, BytesMut};
+use protocol::base_cmd::KickNtf;
+use common::config::{GateServerConfig, MailboxOverflow};
 use kameo::Actor;
 use kameo::actor::ActorRef;
+use kameo::error::SendError;
+use kameo::mailbox::{self, MailboxSender};
 use kameo::message::{Context, Message};
-use message_io::network::{Endpoint, NetEvent, SendStatus, Transport};
+use message_io::network::{Endpoint, NetEvent, ResourceId, SendStatus, Transport};
 use message_io::node;
 use message_io::node::{NodeEvent, NodeHandler, NodeListener, NodeTask};
 use scc::HashIndex;
+use std::collections::HashMap;
 use std::io;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::time::Duration;
 
 pub struct NetServer {
     handler: NodeHandler<NetServerSignal>,
     node_listener: Option<NodeListener<NetServerSignal>>,
+    //tcp和ws的监听,停服时移除
+    listeners: Vec<ResourceId>,
     client_actors: HashIndex<Endpoint, ActorRef<ClientActor>>,
+    node_manager: Arc<NodeManager>,
+    sessions: Arc<SessionRegistry>,
+    gate_config: Arc<GateServerConfig>,
+    server_key: Option<Arc<ServerKey>>,
+    stats: Arc<GateStats>,
 }
 pub enum NetServerSignal {
     CloseSession(Endpoint),
-    SendPacket(Endpoint, Packet),
+    //已经由连接的Codec编码好的数据
+    Send(Endpoint, Bytes),
+    //定时检测心跳超时的连接,清理过期的断线会话
+    Sweep,
+    //定时驱动udp可靠会话的重传和确认
+    KcpTick,
+    //停服:停止接收新连接,踢下线所有连接
+    Drain(KickNtf),
+}
+
+//udp可靠会话,关闭后等待发完剩余数据再发FIN
+struct KcpSession {
+    kcp: Kcp,
+    closing: bool,
+}
+
+//连接和消息队列的统计,队列长度在Sweep时刷新
+#[derive(Debug, Default)]
+pub struct GateStats {
+    pub connections: AtomicUsize,
+    //所有连接队列中等待处理的消息数
+    pub queued: AtomicUsize,
+    //单个连接最长的队列
+    pub max_queued: AtomicUsize,
+    //队列满被丢弃的包
+    pub dropped: AtomicU64,
+}
+
+//客户端队列中等待处理的消息数
+pub fn queue_depth(actor_ref: &ActorRef<ClientActor>) -> usize {
+    match actor_ref.mailbox_sender() {
+        MailboxSender::Bounded(tx) => tx.max_capacity() - tx.capacity(),
+        MailboxSender::Unbounded(_) => 0,
+    }
+}
+
+//创建ClientActor和投递收到的数据
+struct Clients {
+    handler: NodeHandler<NetServerSignal>,
+    node_manager: Arc<NodeManager>,
+    sessions: Arc<SessionRegistry>,
+    gate_config: Arc<GateServerConfig>,
+    server_key: Option<Arc<ServerKey>>,
+    stats: Arc<GateStats>,
+}
+
+impl Clients {
+    fn spawn(&self, endpoint: Endpoint) -> ActorRef<ClientActor> {
+        let actor = ClientActor::new(
+            endpoint,
+            self.handler.clone(),
+            self.node_manager.clone(),
+            self.sessions.clone(),
+            self.gate_config.clone(),
+            self.server_key.clone(),
+        );
+        let size = self.gate_config.mailbox.size.max(1);
+        kameo::actor::spawn_with_mailbox(actor, mailbox::bounded(size))
+    }
+
+    //在message-io的线程中直接放入队列,同一连接的包按收到的顺序处理
+    fn deliver(&self, endpoint: Endpoint, actor_ref: &ActorRef<ClientActor>, bytes: Bytes) {
+        match actor_ref.tell(ClientMessage::Receive(bytes)).try_send() {
+            Ok(()) => {}
+            Err(SendError::MailboxFull(_)) => {
+                self.stats.dropped.fetch_add(1, Ordering::Relaxed);
+                match self.gate_config.mailbox.overflow {
+                    MailboxOverflow::Drop => {
+                        tracing::warn!("endpoint:{} mailbox full, packet dropped", endpoint);
+                    }
+                    MailboxOverflow::Close => {
+                        tracing::warn!("endpoint:{} mailbox full, close session", endpoint);
+                        self.handler.signals().send(NetServerSignal::CloseSession(endpoint));
+                    }
+                }
+            }
+            Err(e) => {
+                tracing::warn!("endpoint:{} Failed to send message:{}", endpoint, e);
+                self.handler.signals().send(NetServerSignal::CloseSession(endpoint));
+            }
+        }
+    }
+}
+
+//有监听开启加密时必须配置长期密钥,客户端需要固定日志中的公钥
+fn load_server_key(gate_config: &GateServerConfig) -> io::Result<Option<Arc<ServerKey>>> {
+    let encrypt = &gate_config.encrypt;
+    let Some(key) = &encrypt.key else {
+        if encrypt.tcp || encrypt.ws || encrypt.udp {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "encrypt.key required"));
+        }
+        return Ok(None);
+    };
+    let key = crypto::decode_key(key)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, format!("encrypt.key {}", e)))?;
+    let server_key = ServerKey::new(key);
+    tracing::info!("gate public key:{}", hex::encode(server_key.public_key()));
+    Ok(Some(Arc::new(server_key)))
+}
+
+pub fn is_udp(endpoint: &Endpoint) -> bool {
+    endpoint.resource_id().adapter_id() == Transport::Udp.id()
+}
+
+fn kcp_now() -> u32 {
+    common::time::now_ms() as u32
+}
+
+fn send_datagrams(handler: &NodeHandler<NetServerSignal>, endpoint: Endpoint, kcp: &mut Kcp) {
+    for datagram in kcp.take_output() {
+        let send_status = handler.network().send(endpoint, datagram.as_ref());
+        if send_status != SendStatus::Sent {
+            tracing::error!("endpoint:{:?} Failed to send datagram: {:?}", endpoint, send_status);
+        }
+    }
 }
 impl NetServer {
     pub fn new(
-        tcp_port: Option<u16>,
-        ws_port: Option<u16>,
-        udp_port: Option<u16>,
+        gate_config: Arc<GateServerConfig>,
+        node_manager: Arc<NodeManager>,
+        sessions: Arc<SessionRegistry>,
+        stats: Arc<GateStats>,
     ) -> io::Result<NetServer> {
+        let server_key = load_server_key(&gate_config)?;
         let (handler, listener) = node::split::<NetServerSignal>();
-        if let Some(port) = tcp_port {
+        let mut listeners = vec![];
+        if let Some(port) = gate_config.out_tcp_port {
             let addr = ("0.0.0.0", port);
             let result = handler.network().listen(Transport::FramedTcp, &addr)?;
             tracing::info!("tcp server listening on {:?}", result);
+            listeners.push(result.0);
         }
-        if let Some(port) = ws_port {
+        if let Some(port) = gate_config.out_ws_port {
             let addr = ("0.0.0.0", port);
             let result = handler.network().listen(Transport::Ws, &addr)?;
             tracing::info!("ws server listening on {:?}", result);
+            listeners.push(result.0);
         }
-        if let Some(port) = udp_port {
+        if let Some(port) = gate_config.out_udp_port {
             let addr = ("0.0.0.0", port);
             let result = handler.network().listen(Transport::Udp, &addr)?;
             tracing::info!("udp server listening on {:?}", result);
@@ -44,64 +182,281 @@ impl NetServer {
         Ok(NetServer {
             handler,
             node_listener: Some(listener),
+            listeners,
             client_actors: HashIndex::new(),
+            node_manager,
+            sessions,
+            gate_config,
+            server_key,
+            stats,
         })
     }
 
     pub fn run(mut self) -> (NodeTask, NodeHandler<NetServerSignal>) {
         let handler = self.handler.clone();
         let client_actors = self.client_actors.clone();
+        let sessions = self.sessions.clone();
         let node_listener = self.node_listener.take().unwrap();
+        let gate_config = self.gate_config.clone();
+        let stats = self.stats.clone();
+        let listeners = std::mem::take(&mut self.listeners);
+        //停服中不再接收新连接
+        let mut draining = false;
+        let clients = Clients {
+            handler: handler.clone(),
+            node_manager: self.node_manager.clone(),
+            sessions: sessions.clone(),
+            gate_config: gate_config.clone(),
+            server_key: self.server_key.clone(),
+            stats: stats.clone(),
+        };
+        let heartbeat_timeout = Duration::from_secs(gate_config.heartbeat_timeout);
+        handler
+            .signals()
+            .send_with_timer(NetServerSignal::Sweep, HEARTBEAT_INTERVAL);
+        let kcp_config = gate_config.kcp.clone();
+        let kcp_enable = kcp_config.enable && gate_config.out_udp_port.is_some();
+        let kcp_interval = Duration::from_millis(kcp_config.interval.max(1) as u64);
+        if kcp_enable {
+            handler.signals().send_with_timer(NetServerSignal::KcpTick, kcp_interval);
+        }
+        //只在message-io的线程中访问
+        let mut kcp_sessions: HashMap<Endpoint, KcpSession> = HashMap::new();
+        let mut connections = ConnectionLimiter::new(gate_config.rate_limit.max_connections_per_ip);
+        //回调在message-io的线程中执行,需要进入tokio运行时才能spawn
+        let runtime = tokio::runtime::Handle::current();
         let task = node_listener.for_each_async(move |event| {
+            let _guard = runtime.enter();
             match event {
                 NodeEvent::Network(net_event) => match net_event {
                     NetEvent::Connected(_, _) => (), // Only generated at connect() calls.
                     NetEvent::Accepted(endpoint, _listener_id) => {
-                        let actor_ref = kameo::spawn(ClientActor::new(endpoint, handler.clone()));
-                        client_actors.insert(endpoint, actor_ref.clone()).unwrap();
+                        if draining {
+                            handler.network().remove(endpoint.resource_id());
+                            return;
+                        }
+                        if !connections.acquire(endpoint.addr().ip()) {
+                            tracing::warn!("endpoint:{} too many connections from ip", endpoint);
+                            handler.network().remove(endpoint.resource_id());
+                            return;
+                        }
+                        let actor_ref = clients.spawn(endpoint);
+                        client_actors.insert(endpoint, actor_ref).unwrap();
+                    }
+                    NetEvent::Message(endpoint, input_data) if is_udp(&endpoint) && kcp_enable => {
+                        if draining && !kcp_sessions.contains_key(&endpoint) {
+                            return;
+                        }
+                        let now = kcp_now();
+                        //会话的conv由客户端的第一个datagram确定
+                        let Some(conv) = kcp::conv(input_data) else {
+                            return;
+                        };
+                        let session = kcp_sessions.entry(endpoint).or_insert_with(|| KcpSession {
+                            kcp: Kcp::new(&kcp_config, conv),
+                            closing: false,
+                        });
+                        if session.kcp.is_fin() {
+                            return;
+                        }
+                        if let Err(e) = session.kcp.input(input_data, now) {
+                            tracing::debug!("endpoint:{} invalid kcp datagram:{}", endpoint, e);
+                            //不合法的datagram不建立会话
+                            if !client_actors.contains(&endpoint) && !session.closing {
+                                kcp_sessions.remove(&endpoint);
+                            }
+                            return;
+                        }
+                        if !client_actors.contains(&endpoint) && !session.closing {
+                            if !connections.acquire(endpoint.addr().ip()) {
+                                tracing::warn!("endpoint:{} too many connections from ip", endpoint);
+                                kcp_sessions.remove(&endpoint);
+                                return;
+                            }
+                            session.kcp.update(now);
+                            let _ = client_actors.insert(endpoint, clients.spawn(endpoint));
+                        }
+                        //客户端主动断开
+                        if session.kcp.is_fin() {
+                            handler.signals().send(NetServerSignal::CloseSession(endpoint));
+                            return;
+                        }
+                        let mut messages = vec![];
+                        while let Some(message) = session.kcp.recv() {
+                            messages.push(message);
+                        }
+                        //尽快回复ack
+                        session.kcp.flush(now);
+                        send_datagrams(&handler, endpoint, &mut session.kcp);
+                        if session.closing || messages.is_empty() {
+                            return;
+                        }
+                        if let Some(actor_ref) = client_actors.peek_with(&endpoint, |_, v| v.clone()) {
+                            for bytes in messages {
+                                clients.deliver(endpoint, &actor_ref, bytes);
+                            }
+                        }
                     }
                     NetEvent::Message(endpoint, input_data) => {
+                        //udp没有Accepted事件,收到第一个datagram时建立会话
+                        if is_udp(&endpoint) && !client_actors.contains(&endpoint) {
+                            if draining {
+                                return;
+                            }
+                            if !connections.acquire(endpoint.addr().ip()) {
+                                tracing::warn!("endpoint:{} too many connections from ip", endpoint);
+                                return;
+                            }
+                            let _ = client_actors.insert(endpoint, clients.spawn(endpoint));
+                        }
                         let x = client_actors.peek_with(&endpoint, |_, v| v.clone());
                         if let Some(actor_ref) = x {
-                            let x1 = if actor_ref.is_alive() {
-                                let packet = Decoder::decode(input_data);
-                                let packet = if let Some(packet) = packet { packet } else {
-                                    tracing::error!("actor:{:?} decoding failed endpoint:{} input_data:{:?}",actor_ref, endpoint,input_data);
-                                    handler.signals().send_with_priority(NetServerSignal::CloseSession(endpoint));
-                                    return;
-                                };
-
-                                tokio::spawn(async move {
-                                    let result = actor_ref.tell(ClientMessage::ReceivePacket(packet)).await;
-                                    //发送失败
-                                    if let Err(e) = result {
-                                        tracing::warn!("actor:{:?} Failed to send message:{}", actor_ref,e);
-                                    }
-                                });
-                            } else {
-                                tracing::warn!("actor:{:?} is not alive:{} ", actor_ref ,endpoint);
-                                handler.signals().send(NetServerSignal::CloseSession(endpoint));
-                            };
-                            x1
+                            //解码依赖连接协商的压缩和加密,交给ClientActor处理
+                            clients.deliver(endpoint, &actor_ref, Bytes::copy_from_slice(input_data));
                         } else {
                             tracing::warn!("Received unexpected message from client,server already clean,but client still send message");
                         }
                     }
                     NetEvent::Disconnected(endpoint) => {
-                        client_actors.remove(&endpoint);
+                        if let Some(actor_ref) = client_actors.peek_with(&endpoint, |_, v| v.clone()) {
+                            actor_ref.kill();
+                        }
+                        if client_actors.remove(&endpoint) {
+                            connections.release(endpoint.addr().ip());
+                        }
                     }
                 }
                 NodeEvent::Signal(s) => match s {
                     NetServerSignal::CloseSession(s) => {
-                        client_actors.remove(&s);
+                        if let Some(actor_ref) = client_actors.peek_with(&s, |_, v| v.clone()) {
+                            actor_ref.kill();
+                        }
+                        if client_actors.remove(&s) {
+                            connections.release(s.addr().ip());
+                        }
+                        //udp的连接共用监听资源,不能移除
+                        if !is_udp(&s) {
+                            handler.network().remove(s.resource_id());
+                        } else if let Some(session) = kcp_sessions.get_mut(&s) {
+                            //等剩余数据(如踢下线通知)发完后在KcpTick中发FIN
+                            session.closing = true;
+                            if session.kcp.is_fin() || session.kcp.is_dead() {
+                                kcp_sessions.remove(&s);
+                            }
+                        }
                     }
-                    NetServerSignal::SendPacket(s, p) => {
-                        let typ = p.r#type.to_string();
-                        let send_status = handler.network().send(s, Encoder::encode(p).as_ref());
+                    NetServerSignal::Send(s, bytes) => {
+                        if let Some(session) = kcp_sessions.get_mut(&s) {
+                            //客户端不确认时发送队列不能无限增长
+                            if session.kcp.wait_snd() >= kcp_config.snd_queue.max(1) {
+                                stats.dropped.fetch_add(1, Ordering::Relaxed);
+                                match gate_config.mailbox.overflow {
+                                    MailboxOverflow::Drop => {
+                                        tracing::warn!("endpoint:{} kcp send queue full, packet dropped", s);
+                                    }
+                                    MailboxOverflow::Close => {
+                                        tracing::warn!("endpoint:{} kcp send queue full, close session", s);
+                                        handler.signals().send(NetServerSignal::CloseSession(s));
+                                    }
+                                }
+                                return;
+                            }
+                            if let Err(e) = session.kcp.send(bytes.as_ref()) {
+                                tracing::error!("endpoint:{:?} Failed to send bytes: {}", s, e);
+                                return;
+                            }
+                            session.kcp.flush(kcp_now());
+                            send_datagrams(&handler, s, &mut session.kcp);
+                            return;
+                        }
+                        //可靠会话已关闭
+                        if kcp_enable && is_udp(&s) {
+                            return;
+                        }
+                        let send_status = handler.network().send(s, bytes.as_ref());
                         if send_status != SendStatus::Sent {
-                            tracing::error!("endpoint:{:?} Failed to send packet: {:?}",s,typ);
+                            tracing::error!("endpoint:{:?} Failed to send bytes: {:?}",s,send_status);
                         };
                     }
+                    NetServerSignal::Sweep => {
+                        sessions.expire(common::time::now_ms());
+                        let (mut count, mut queued, mut max_queued) = (0, 0, 0);
+                        client_actors.retain(|endpoint, actor_ref| {
+                            if !actor_ref.is_alive() {
+                                connections.release(endpoint.addr().ip());
+                                return false;
+                            }
+                            let depth = queue_depth(actor_ref);
+                            count += 1;
+                            queued += depth;
+                            max_queued = max_queued.max(depth);
+                            //超时时间为0表示不检测,队列满时等下次检测
+                            if !heartbeat_timeout.is_zero()
+                                && let Err(e) = actor_ref.tell(ClientMessage::CheckIdle(heartbeat_timeout)).try_send()
+                            {
+                                tracing::warn!("endpoint:{} check idle failed:{}", endpoint, e);
+                            }
+                            true
+                        });
+                        stats.connections.store(count, Ordering::Relaxed);
+                        stats.queued.store(queued, Ordering::Relaxed);
+                        stats.max_queued.store(max_queued, Ordering::Relaxed);
+                        if queued > 0 {
+                            tracing::debug!(
+                                "connections:{} queued:{} max queued:{} dropped:{}",
+                                count,
+                                queued,
+                                max_queued,
+                                stats.dropped.load(Ordering::Relaxed)
+                            );
+                        }
+                        handler.signals().send_with_timer(NetServerSignal::Sweep, HEARTBEAT_INTERVAL);
+                    }
+                    NetServerSignal::KcpTick => {
+                        let now = kcp_now();
+                        kcp_sessions.retain(|endpoint, session| {
+                            session.kcp.update(now);
+                            send_datagrams(&handler, *endpoint, &mut session.kcp);
+                            if session.kcp.is_dead() {
+                                tracing::info!("endpoint:{} kcp dead link", endpoint);
+                                if !session.closing {
+                                    handler.signals().send(NetServerSignal::CloseSession(*endpoint));
+                                }
+                                return false;
+                            }
+                            if session.closing && session.kcp.is_idle() {
+                                let send_status = handler.network().send(*endpoint, session.kcp.fin().as_ref());
+                                if send_status != SendStatus::Sent {
+                                    tracing::warn!("endpoint:{:?} Failed to send fin: {:?}", endpoint, send_status);
+                                }
+                                return false;
+                            }
+                            true
+                        });
+                        handler.signals().send_with_timer(NetServerSignal::KcpTick, kcp_interval);
+                    }
+                    NetServerSignal::Drain(kick) => {
+                        if draining {
+                            return;
+                        }
+                        draining = true;
+                        //udp的监听和连接共用资源,不移除,只拒绝新的会话
+                        for listener in &listeners {
+                            handler.network().remove(*listener);
+                        }
+                        let mut count = 0;
+                        client_actors.retain(|_, actor_ref| {
+                            //队列可能已满,等待放入,踢下线包和关闭由ClientActor发出
+                            let actor_ref = actor_ref.clone();
+                            let kick = kick.clone();
+                            tokio::spawn(async move {
+                                let _ = actor_ref.tell(ClientMessage::Kick(kick)).await;
+                            });
+                            count += 1;
+                            true
+                        });
+                        tracing::info!("gate draining, kick {} connections", count);
+                    }
                 }
             }
         });
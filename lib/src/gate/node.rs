@@ -1,8 +1,11 @@
-use crate::gate::GateActor;
-use crate::node::Node;
+use crate::gate::{GateActor, GateMessage, PushTarget};
+use crate::node::{Node, stop_actor};
 use common::config::{GlobalConfig, ServerRoleId};
+use common::resource::TableVersion;
 use kameo::actor::ActorRef;
+use protocol::base_cmd::ConfigVersionNtf;
 use std::sync::Arc;
+use std::time::Duration;
 
 pub struct GateNode {
     global_config: Arc<GlobalConfig>,
@@ -51,15 +54,53 @@ impl Node for GateNode {
         Ok(())
     }
 
+    //先从Center注销,再踢下线所有玩家
+    async fn drain(&mut self) -> anyhow::Result<()> {
+        //注销失败也要踢下线
+        let unregister = self.disconnect_center().await;
+        if let Some(actor_ref) = &self.gate_ref {
+            actor_ref
+                .ask(GateMessage::Drain {
+                    message: "server maintenance".to_string(),
+                    reconnect_after: 0,
+                })
+                .await?;
+        }
+        unregister
+    }
+
+    //通知所有在线玩家新的配置表版本
+    async fn config_reloaded(&mut self, version: &TableVersion) -> anyhow::Result<()> {
+        let Some(actor_ref) = &self.gate_ref else {
+            return Ok(());
+        };
+        let data = crate::encode(ConfigVersionNtf {
+            version: version.id,
+            hash: version.hash.clone(),
+        })?;
+        actor_ref
+            .ask(GateMessage::Push {
+                target: PushTarget::All,
+                cmd: ConfigVersionNtf::CMD,
+                data,
+            })
+            .await?;
+        Ok(())
+    }
+
     async fn stop(&mut self) -> anyhow::Result<()> {
         let actor_ref = self.gate_ref.take().unwrap();
-        //停止actor
-        actor_ref.kill();
-        actor_ref.wait_for_stop().await;
+        //处理完队列中的消息后停止actor
+        let timeout = Duration::from_secs(self.global_config.shutdown.stop_timeout);
+        stop_actor(actor_ref, timeout).await;
         Ok(())
     }
 
     fn server_role_id(&self) -> ServerRoleId {
         self.role_id.clone()
     }
+
+    fn global_config(&self) -> &GlobalConfig {
+        &self.global_config
+    }
 }
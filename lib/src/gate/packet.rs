@@ -1,9 +1,53 @@
+//! 客户端与网关之间的帧格式(TCP/WS/UDP 共用)
+//!
+//! ```text
+//! +-------+---------+-------+------+-----------+-----------+-----------+---------+-----------+
+//! | magic | version | flags | type | cmd       | seq       | len       | payload | crc32     |
+//! | u8    | u8      | u8    | u8   | varint    | varint    | varint    | len字节 | u32 LE    |
+//! |       |         |       |      | i32zigzag | u32       | u32       |         | 可选      |
+//! +-------+---------+-------+------+-----------+-----------+-----------+---------+-----------+
+//! ```
+//!
+//! - `magic` 固定为 [`MAGIC`],`version` 当前为 [`VERSION`]
+//! - `flags` 见 [`Flags`],置位 [`Flags::CRC32`] 时帧尾追加 crc32,覆盖 crc 之前的全部字节
+//! - 置位 [`Flags::COMPRESSED`] 时 payload 为 `原始长度(u32 LE) + lz4 block`,`len` 为压缩后的长度
+//! - 置位 [`Flags::ENCRYPTED`] 时 payload 先压缩再加密,格式见 [`crate::gate::crypto`]
+//! - varint 与 protobuf 一致:每字节低7位有效,最高位为继续位;cmd 使用 zigzag 编码
+//! - `seq` 为客户端请求序号;Push 为服务端推送序号,Heartbeat 为客户端确认收到的最后一个推送序号,其余控制包填0
+//! - 一个传输层消息只承载一帧,帧尾不允许有多余字节
+use crate::gate::crypto::{SEAL_OVERHEAD, SessionCipher};
 use bytes::{BufMut, Bytes, BytesMut};
 use integer_encoding::VarInt;
 use std::fmt::{Display, Formatter};
+use std::sync::OnceLock;
+use thiserror::Error;
+
+pub const MAGIC: u8 = 0x51;
+pub const VERSION: u8 = 1;
+//最大负载长度
+pub const MAX_PAYLOAD_LEN: usize = 4 * 1024 * 1024;
+//固定头: magic + version + flags + type
+const FIXED_HEADER_LEN: usize = 4;
+const CRC_LEN: usize = 4;
+
+bitflags::bitflags! {
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct Flags: u8 {
+        const CRC32 = 0b0000_0001;
+        const COMPRESSED = 0b0000_0010;
+        const ENCRYPTED = 0b0000_0100;
+    }
+}
+
+//连接握手协商后的编码选项
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CodecOptions {
+    //Response/Push负载达到该长度才压缩,None表示未协商压缩
+    pub compress_threshold: Option<usize>,
+}
 
 #[repr(u8)]
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Type {
     Handshake = 1,    // 握手请求
     HandshakeAck = 2, // 握手响应
@@ -44,10 +88,10 @@ impl Type {
     }
     pub fn need_data(&self) -> bool {
         match self {
-            Type::Handshake => false,
+            Type::Handshake => true,
             Type::HandshakeAck => true,
             Type::Heartbeat => false,
-            Type::Kick => false,
+            Type::Kick => true,
             Type::Request => true,
             Type::Response => true,
             Type::Notify => true,
@@ -55,51 +99,327 @@ impl Type {
         }
     }
 }
-#[derive(Debug)]
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum DecodeError {
+    #[error("frame truncated: need {need} bytes, got {got}")]
+    Truncated { need: usize, got: usize },
+    #[error("bad magic:{0:#04x}")]
+    BadMagic(u8),
+    #[error("unsupported version:{0}")]
+    UnsupportedVersion(u8),
+    #[error("unknown flags:{0:#010b}")]
+    UnknownFlags(u8),
+    #[error("unknown packet type:{0}")]
+    UnknownType(u8),
+    #[error("invalid varint field:{0}")]
+    InvalidVarint(&'static str),
+    #[error("payload too large:{0}")]
+    PayloadTooLarge(usize),
+    #[error("{0} packet must not carry payload")]
+    UnexpectedPayload(Type),
+    #[error("trailing bytes after frame:{0}")]
+    TrailingBytes(usize),
+    #[error("crc mismatch expected:{expected:#010x} actual:{actual:#010x}")]
+    ChecksumMismatch { expected: u32, actual: u32 },
+    #[error("decompress failed:{0}")]
+    DecompressFailed(String),
+    #[error("encrypted frame without session key")]
+    MissingKey,
+    #[error("{0} packet must be encrypted")]
+    Unencrypted(Type),
+    #[error("decrypt failed")]
+    DecryptFailed,
+    #[error("replayed nonce:{0}")]
+    Replayed(u64),
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub struct Packet {
     pub r#type: Type,
     pub cmd: i32,
+    pub seq: u32,
     pub data: Bytes,
 }
 
 pub struct Encoder {}
 impl Encoder {
     pub fn encode(packet: Packet) -> Bytes {
-        let mut output = BytesMut::with_capacity(1 + packet.data.len());
-        output.put_u8(packet.r#type as u8);
-        if packet.data.len() > 0 {
-            output.put(packet.data);
+        Self::encode_with(packet, Flags::empty())
+    }
+
+    //加密需要连接的密钥,使用Codec::encode
+    pub fn encode_with(mut packet: Packet, mut flags: Flags) -> Bytes {
+        if flags.contains(Flags::COMPRESSED) {
+            //压缩后没有变小的直接发原始数据
+            match compress(&packet.data) {
+                Some(data) => packet.data = data,
+                None => flags.remove(Flags::COMPRESSED),
+            }
+        }
+        Self::encode_inner(packet, flags, None)
+    }
+
+    //置位COMPRESSED时packet.data已经是压缩后的数据
+    fn encode_inner(packet: Packet, mut flags: Flags, cipher: Option<&mut SessionCipher>) -> Bytes {
+        flags.set(Flags::ENCRYPTED, cipher.is_some());
+        let payload_len = match cipher {
+            Some(_) => packet.data.len() + SEAL_OVERHEAD,
+            None => packet.data.len(),
         };
+        let mut varint = [0u8; 10];
+        let mut output = BytesMut::with_capacity(FIXED_HEADER_LEN + 15 + payload_len + CRC_LEN);
+        output.put_u8(MAGIC);
+        output.put_u8(VERSION);
+        output.put_u8(flags.bits());
+        output.put_u8(packet.r#type as u8);
+        let n = packet.cmd.encode_var(&mut varint);
+        output.put_slice(&varint[..n]);
+        let n = packet.seq.encode_var(&mut varint);
+        output.put_slice(&varint[..n]);
+        let n = (payload_len as u32).encode_var(&mut varint);
+        output.put_slice(&varint[..n]);
+        match cipher {
+            //帧头作为附加认证数据
+            Some(cipher) => {
+                let sealed = cipher.seal(&output, &packet.data);
+                output.put_slice(&sealed);
+            }
+            None => output.put(packet.data),
+        }
+        if flags.contains(Flags::CRC32) {
+            let crc = crc32fast::hash(&output);
+            output.put_u32_le(crc);
+        }
         output.freeze()
     }
 }
 pub struct Decoder;
 impl Decoder {
-    pub fn decode(input_data: &[u8]) -> Option<Packet> {
-        let package_type = Type::from_u8(input_data[0]);
-        let package_type = match package_type {
-            None => {
-                return None;
+    //加密的帧需要连接的密钥,使用Codec::decode
+    pub fn decode(input_data: &[u8]) -> Result<Packet, DecodeError> {
+        Self::decode_inner(input_data, None).map(|(packet, _)| packet)
+    }
+
+    fn decode_inner(
+        input_data: &[u8],
+        cipher: Option<&mut SessionCipher>,
+    ) -> Result<(Packet, Flags), DecodeError> {
+        if input_data.len() < FIXED_HEADER_LEN {
+            return Err(DecodeError::Truncated {
+                need: FIXED_HEADER_LEN,
+                got: input_data.len(),
+            });
+        }
+        if input_data[0] != MAGIC {
+            return Err(DecodeError::BadMagic(input_data[0]));
+        }
+        if input_data[1] != VERSION {
+            return Err(DecodeError::UnsupportedVersion(input_data[1]));
+        }
+        let flags =
+            Flags::from_bits(input_data[2]).ok_or(DecodeError::UnknownFlags(input_data[2]))?;
+        let package_type =
+            Type::from_u8(input_data[3]).ok_or(DecodeError::UnknownType(input_data[3]))?;
+
+        let mut offset = FIXED_HEADER_LEN;
+        let (cmd, n) =
+            i64::decode_var(&input_data[offset..]).ok_or(DecodeError::InvalidVarint("cmd"))?;
+        let cmd = i32::try_from(cmd).map_err(|_| DecodeError::InvalidVarint("cmd"))?;
+        offset += n;
+        let (seq, n) =
+            u64::decode_var(&input_data[offset..]).ok_or(DecodeError::InvalidVarint("seq"))?;
+        let seq = u32::try_from(seq).map_err(|_| DecodeError::InvalidVarint("seq"))?;
+        offset += n;
+        let (len, n) =
+            u64::decode_var(&input_data[offset..]).ok_or(DecodeError::InvalidVarint("len"))?;
+        offset += n;
+        let len = usize::try_from(len).map_err(|_| DecodeError::InvalidVarint("len"))?;
+        if len > MAX_PAYLOAD_LEN {
+            return Err(DecodeError::PayloadTooLarge(len));
+        }
+        if len > 0 && !package_type.need_data() {
+            return Err(DecodeError::UnexpectedPayload(package_type));
+        }
+
+        let crc_len = if flags.contains(Flags::CRC32) {
+            CRC_LEN
+        } else {
+            0
+        };
+        let need = offset + len + crc_len;
+        if input_data.len() < need {
+            return Err(DecodeError::Truncated {
+                need,
+                got: input_data.len(),
+            });
+        }
+        if input_data.len() > need {
+            return Err(DecodeError::TrailingBytes(input_data.len() - need));
+        }
+        if flags.contains(Flags::CRC32) {
+            let body_end = offset + len;
+            let mut crc = [0u8; CRC_LEN];
+            crc.copy_from_slice(&input_data[body_end..]);
+            let expected = u32::from_le_bytes(crc);
+            let actual = crc32fast::hash(&input_data[..body_end]);
+            if expected != actual {
+                return Err(DecodeError::ChecksumMismatch { expected, actual });
             }
-            Some(t) => t,
+        }
+        let payload = &input_data[offset..offset + len];
+        let data = if flags.contains(Flags::ENCRYPTED) {
+            let cipher = cipher.ok_or(DecodeError::MissingKey)?;
+            Bytes::from(cipher.open(&input_data[..offset], payload)?)
+        } else {
+            Bytes::copy_from_slice(payload)
         };
-        if package_type.need_data() {
-            let cmd = i32::decode_var(input_data).unwrap().0;
-            Some(Packet::new_data(
-                package_type,
-                cmd,
-                Bytes::copy_from_slice(&input_data[1..]),
-            ))
+        let data = if flags.contains(Flags::COMPRESSED) {
+            decompress(&data)?
         } else {
-            Some(Packet::new_control(package_type))
+            data
+        };
+        let packet = Packet {
+            r#type: package_type,
+            cmd,
+            seq,
+            data,
+        };
+        Ok((packet, flags))
+    }
+}
+//推送给多个连接的负载,压缩只做一次;推送序号和加密每个连接不同,在各自的Codec中处理
+#[derive(Debug)]
+pub struct SharedPayload {
+    pub cmd: i32,
+    pub data: Bytes,
+    compressed: OnceLock<Option<Bytes>>,
+}
+
+impl SharedPayload {
+    pub fn new(cmd: i32, data: Bytes) -> Self {
+        Self {
+            cmd,
+            data,
+            compressed: OnceLock::new(),
         }
     }
+
+    //压缩后没有变小时为None
+    fn compressed(&self) -> Option<&Bytes> {
+        self.compressed
+            .get_or_init(|| compress(&self.data))
+            .as_ref()
+    }
+}
+
+//一个连接的编解码状态,握手协商后设置压缩和加密
+#[derive(Default)]
+pub struct Codec {
+    options: CodecOptions,
+    cipher: Option<SessionCipher>,
 }
+
+impl Codec {
+    pub fn new(options: CodecOptions, cipher: Option<SessionCipher>) -> Self {
+        Self { options, cipher }
+    }
+
+    pub fn encode(&mut self, mut packet: Packet) -> Bytes {
+        let mut flags = Flags::empty();
+        if self.compressible(&packet)
+            && let Some(data) = compress(&packet.data)
+        {
+            packet.data = data;
+            flags |= Flags::COMPRESSED;
+        }
+        self.encode_inner(packet, flags)
+    }
+
+    //与encode结果相同,压缩使用负载缓存的结果
+    pub fn encode_push(&mut self, seq: u32, payload: &SharedPayload) -> Bytes {
+        let mut packet =
+            Packet::new_data(Type::Push, payload.cmd, payload.data.clone()).with_seq(seq);
+        let mut flags = Flags::empty();
+        if self.compressible(&packet)
+            && let Some(data) = payload.compressed()
+        {
+            packet.data = data.clone();
+            flags |= Flags::COMPRESSED;
+        }
+        self.encode_inner(packet, flags)
+    }
+
+    fn compressible(&self, packet: &Packet) -> bool {
+        match self.options.compress_threshold {
+            Some(threshold) => {
+                matches!(packet.r#type, Type::Response | Type::Push)
+                    && packet.data.len() >= threshold
+            }
+            None => false,
+        }
+    }
+
+    fn encode_inner(&mut self, packet: Packet, flags: Flags) -> Bytes {
+        //控制包没有负载,不加密
+        let cipher = self.cipher.as_mut().filter(|_| packet.r#type.need_data());
+        Encoder::encode_inner(packet, flags, cipher)
+    }
+
+    pub fn decode(&mut self, input_data: &[u8]) -> Result<Packet, DecodeError> {
+        let encrypted = self.cipher.is_some();
+        let (packet, flags) = Decoder::decode_inner(input_data, self.cipher.as_mut())?;
+        //协商加密后带负载的包必须加密,防止降级成明文
+        if encrypted && packet.r#type.need_data() && !flags.contains(Flags::ENCRYPTED) {
+            return Err(DecodeError::Unencrypted(packet.r#type));
+        }
+        Ok(packet)
+    }
+}
+
+fn compress(data: &[u8]) -> Option<Bytes> {
+    if data.is_empty() {
+        return None;
+    }
+    let compressed = lz4_flex::block::compress(data);
+    if compressed.len() + 4 >= data.len() {
+        return None;
+    }
+    let mut output = BytesMut::with_capacity(compressed.len() + 4);
+    output.put_u32_le(data.len() as u32);
+    output.put_slice(&compressed);
+    Some(output.freeze())
+}
+
+fn decompress(payload: &[u8]) -> Result<Bytes, DecodeError> {
+    if payload.len() < 4 {
+        return Err(DecodeError::DecompressFailed(
+            "missing raw length".to_string(),
+        ));
+    }
+    let raw_len = u32::from_le_bytes([payload[0], payload[1], payload[2], payload[3]]) as usize;
+    //先校验原始长度,防止解压炸弹
+    if raw_len > MAX_PAYLOAD_LEN {
+        return Err(DecodeError::PayloadTooLarge(raw_len));
+    }
+    let data = lz4_flex::block::decompress(&payload[4..], raw_len)
+        .map_err(|e| DecodeError::DecompressFailed(e.to_string()))?;
+    if data.len() != raw_len {
+        return Err(DecodeError::DecompressFailed(format!(
+            "raw length:{} actual:{}",
+            raw_len,
+            data.len()
+        )));
+    }
+    Ok(Bytes::from(data))
+}
+
 impl Packet {
     pub fn new_control(r#type: Type) -> Self {
         Packet {
             r#type,
             cmd: 0,
+            seq: 0,
             data: Bytes::default(),
         }
     }
@@ -107,7 +427,268 @@ impl Packet {
         Packet {
             r#type,
             cmd,
+            seq: 0,
             data: bytes,
         }
     }
+    pub fn with_seq(mut self, seq: u32) -> Self {
+        self.seq = seq;
+        self
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::gate::crypto::{KeyExchange, ServerKey};
+    use proptest::prelude::*;
+
+    fn packet_type() -> impl Strategy<Value = Type> {
+        (1u8..=8).prop_map(|t| Type::from_u8(t).unwrap())
+    }
+
+    fn packet() -> impl Strategy<Value = Packet> {
+        (
+            packet_type(),
+            any::<i32>(),
+            any::<u32>(),
+            proptest::collection::vec(any::<u8>(), 0..512),
+        )
+            .prop_map(|(r#type, cmd, seq, data)| {
+                let data = if r#type.need_data() { data } else { vec![] };
+                Packet {
+                    r#type,
+                    cmd,
+                    seq,
+                    data: Bytes::from(data),
+                }
+            })
+    }
+
+    #[test]
+    fn encode_layout() {
+        let bytes = Encoder::encode(Packet {
+            r#type: Type::Request,
+            cmd: 1001,
+            seq: 3,
+            data: Bytes::from_static(&[7, 8]),
+        });
+        // cmd 1001 zigzag => 2002 => [0xd2, 0x0f]
+        assert_eq!(
+            bytes.as_ref(),
+            &[MAGIC, VERSION, 0, 5, 0xd2, 0x0f, 3, 2, 7, 8]
+        );
+    }
+
+    #[test]
+    fn decode_errors() {
+        assert_eq!(
+            Decoder::decode(&[]),
+            Err(DecodeError::Truncated { need: 4, got: 0 })
+        );
+        assert_eq!(
+            Decoder::decode(&[0, VERSION, 0, 5, 0, 0, 0]),
+            Err(DecodeError::BadMagic(0))
+        );
+        assert_eq!(
+            Decoder::decode(&[MAGIC, 9, 0, 5, 0, 0, 0]),
+            Err(DecodeError::UnsupportedVersion(9))
+        );
+        assert_eq!(
+            Decoder::decode(&[MAGIC, VERSION, 0x80, 5, 0, 0, 0]),
+            Err(DecodeError::UnknownFlags(0x80))
+        );
+        assert_eq!(
+            Decoder::decode(&[MAGIC, VERSION, 0, 99, 0, 0, 0]),
+            Err(DecodeError::UnknownType(99))
+        );
+        assert_eq!(
+            Decoder::decode(&[MAGIC, VERSION, 0, 5, 0x80]),
+            Err(DecodeError::InvalidVarint("cmd"))
+        );
+        assert_eq!(
+            Decoder::decode(&[MAGIC, VERSION, 0, 3, 0, 0, 1, 1]),
+            Err(DecodeError::UnexpectedPayload(Type::Heartbeat))
+        );
+        assert_eq!(
+            Decoder::decode(&[MAGIC, VERSION, 0, 5, 0, 0, 2, 1]),
+            Err(DecodeError::Truncated { need: 9, got: 8 })
+        );
+        assert_eq!(
+            Decoder::decode(&[MAGIC, VERSION, 0, 5, 0, 0, 0, 1]),
+            Err(DecodeError::TrailingBytes(1))
+        );
+    }
+
+    #[test]
+    fn crc_detects_corruption() {
+        let mut bytes = Encoder::encode_with(
+            Packet::new_data(Type::Push, 1102, Bytes::from_static(b"store")),
+            Flags::CRC32,
+        )
+        .to_vec();
+        let last = bytes.len() - 5;
+        bytes[last] ^= 0xff;
+        assert!(matches!(
+            Decoder::decode(&bytes),
+            Err(DecodeError::ChecksumMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn compress_above_threshold() {
+        let mut codec = Codec::new(
+            CodecOptions {
+                compress_threshold: Some(64),
+            },
+            None,
+        );
+        let data = Bytes::from(vec![7u8; 4096]);
+        let packet = Packet::new_data(Type::Push, 1102, data.clone());
+        let bytes = codec.encode(packet.clone());
+        assert_eq!(bytes[2], Flags::COMPRESSED.bits());
+        assert!(bytes.len() < data.len());
+        assert_eq!(Decoder::decode(&bytes), Ok(packet));
+
+        //低于阈值或者不是Response/Push不压缩
+        let small = Packet::new_data(Type::Push, 1102, Bytes::from(vec![7u8; 32]));
+        assert_eq!(codec.encode(small)[2], 0);
+        let request = Packet::new_data(Type::Request, 1102, data);
+        assert_eq!(codec.encode(request)[2], 0);
+    }
+
+    #[test]
+    fn encode_shared_push() {
+        let options = CodecOptions {
+            compress_threshold: Some(64),
+        };
+        let payload = SharedPayload::new(1102, Bytes::from(vec![7u8; 4096]));
+        for data_len in [4096, 32] {
+            let payload = SharedPayload::new(1102, payload.data.slice(..data_len));
+            let packet = Packet::new_data(Type::Push, 1102, payload.data.clone()).with_seq(9);
+            let mut codec = Codec::new(options, None);
+            assert_eq!(codec.encode_push(9, &payload), codec.encode(packet.clone()));
+            //每个连接的密钥不同,解密后一致
+            let (mut server, mut client) = encrypted_pair(options);
+            let bytes = server.encode_push(9, &payload);
+            assert_eq!(client.decode(&bytes), Ok(packet));
+        }
+        assert!(payload.compressed.get().is_none());
+        let mut codec = Codec::new(options, None);
+        codec.encode_push(1, &payload);
+        assert!(payload.compressed.get().unwrap().is_some());
+    }
+
+    fn encrypted_pair(options: CodecOptions) -> (Codec, Codec) {
+        let server_key = ServerKey::new([7; 32]);
+        let server = KeyExchange::new();
+        let client = KeyExchange::new();
+        let server_public = server.public_key();
+        let client_public = client.public_key();
+        (
+            Codec::new(
+                options,
+                Some(server.finish_server(&client_public, &server_key).unwrap()),
+            ),
+            Codec::new(
+                options,
+                Some(
+                    client
+                        .finish_client(&server_public, &server_key.public_key())
+                        .unwrap(),
+                ),
+            ),
+        )
+    }
+
+    #[test]
+    fn encrypted_codec() {
+        let (mut server, mut client) = encrypted_pair(CodecOptions {
+            compress_threshold: Some(64),
+        });
+        let request =
+            Packet::new_data(Type::Request, 1001, Bytes::from_static(b"account")).with_seq(1);
+        let bytes = client.encode(request.clone());
+        assert_eq!(bytes[2], Flags::ENCRYPTED.bits());
+        assert!(!bytes.windows(7).any(|x| x == b"account"));
+        assert_eq!(server.decode(&bytes), Ok(request));
+        //重放同一帧
+        assert_eq!(server.decode(&bytes), Err(DecodeError::Replayed(0)));
+
+        let push = Packet::new_data(Type::Push, 1102, Bytes::from(vec![7u8; 4096]));
+        let bytes = server.encode(push.clone());
+        assert_eq!(bytes[2], (Flags::ENCRYPTED | Flags::COMPRESSED).bits());
+        assert_eq!(client.decode(&bytes), Ok(push));
+
+        //控制包不加密
+        let heartbeat = Packet::new_control(Type::Heartbeat);
+        let bytes = client.encode(heartbeat.clone());
+        assert_eq!(bytes[2], 0);
+        assert_eq!(server.decode(&bytes), Ok(heartbeat));
+
+        //协商加密后拒绝明文
+        let plain = Encoder::encode(Packet::new_data(
+            Type::Request,
+            1001,
+            Bytes::from_static(b"x"),
+        ));
+        assert_eq!(
+            server.decode(&plain),
+            Err(DecodeError::Unencrypted(Type::Request))
+        );
+        //没有密钥无法解密
+        let bytes = client.encode(Packet::new_data(
+            Type::Notify,
+            1001,
+            Bytes::from_static(b"x"),
+        ));
+        assert_eq!(Decoder::decode(&bytes), Err(DecodeError::MissingKey));
+    }
+
+    #[test]
+    fn reject_decompress_bomb() {
+        let mut payload = vec![];
+        payload.extend_from_slice(&(MAX_PAYLOAD_LEN as u32 + 1).to_le_bytes());
+        payload.extend_from_slice(&lz4_flex::block::compress(&[0u8; 16]));
+        let packet = Packet::new_data(Type::Push, 1, Bytes::from(payload));
+        let mut bytes = Encoder::encode(packet).to_vec();
+        bytes[2] = Flags::COMPRESSED.bits();
+        assert_eq!(
+            Decoder::decode(&bytes),
+            Err(DecodeError::PayloadTooLarge(MAX_PAYLOAD_LEN + 1))
+        );
+    }
+
+    proptest! {
+        #[test]
+        fn round_trip(packet in packet(), crc in any::<bool>(), compressed in any::<bool>()) {
+            let mut flags = if crc { Flags::CRC32 } else { Flags::empty() };
+            if compressed {
+                flags |= Flags::COMPRESSED;
+            }
+            let bytes = Encoder::encode_with(packet.clone(), flags);
+            prop_assert_eq!(Decoder::decode(&bytes), Ok(packet));
+        }
+
+        #[test]
+        fn fuzz_random_input(input in proptest::collection::vec(any::<u8>(), 0..64)) {
+            let _ = Decoder::decode(&input);
+        }
+
+        #[test]
+        fn fuzz_mutated_frame(
+            packet in packet(),
+            crc in any::<bool>(),
+            index in any::<prop::sample::Index>(),
+            xor in 1u8..=255,
+            cut in any::<prop::sample::Index>(),
+        ) {
+            let flags = if crc { Flags::CRC32 } else { Flags::empty() };
+            let mut bytes = Encoder::encode_with(packet, flags).to_vec();
+            let i = index.index(bytes.len());
+            bytes[i] ^= xor;
+            let _ = Decoder::decode(&bytes);
+            let _ = Decoder::decode(&bytes[..cut.index(bytes.len())]);
+        }
+    }
 }
@@ -0,0 +1,428 @@
+use crate::gate::KickTarget;
+use crate::gate::client::{ClientActor, ClientMessage};
+use crate::gate::packet::{Packet, SharedPayload, Type};
+use bytes::Bytes;
+use common::config::{ServerRole, ServerRoleId};
+use dashmap::DashMap;
+use kameo::actor::{ActorID, ActorRef};
+use kameo::error::SendError;
+use std::collections::{HashSet, VecDeque};
+use std::sync::Arc;
+
+//客户端会话,登录成功后绑定玩家以及所在的game/world节点
+#[derive(Debug, Clone, Default)]
+pub struct Session {
+    pub account: Option<String>,
+    pub player_id: Option<i64>,
+    pub game: Option<ServerRoleId>,
+    pub world: Option<ServerRoleId>,
+}
+
+impl Session {
+    pub fn node(&self, role: &ServerRole) -> Option<&ServerRoleId> {
+        match role {
+            ServerRole::Game => self.game.as_ref(),
+            ServerRole::World => self.world.as_ref(),
+            _ => None,
+        }
+    }
+
+    pub fn bind_node(&mut self, role_id: ServerRoleId) {
+        match role_id.0 {
+            ServerRole::Game => self.game = Some(role_id),
+            ServerRole::World => self.world = Some(role_id),
+            _ => {}
+        }
+    }
+}
+
+//推送序号和未被客户端确认的推送,断线重连时补发
+#[derive(Debug, Clone, Default)]
+pub struct PushBuffer {
+    pub seq: u32,
+    pushes: VecDeque<Packet>,
+    capacity: usize,
+}
+
+impl PushBuffer {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            ..Default::default()
+        }
+    }
+
+    //分配序号并缓存,超出容量丢弃最早的
+    pub fn push(&mut self, cmd: i32, data: Bytes) -> Packet {
+        self.seq = self.seq.wrapping_add(1);
+        let packet = Packet::new_data(Type::Push, cmd, data).with_seq(self.seq);
+        if self.capacity > 0 {
+            if self.pushes.len() >= self.capacity {
+                self.pushes.pop_front();
+            }
+            self.pushes.push_back(packet.clone());
+        }
+        packet
+    }
+
+    //客户端确认收到seq及之前的推送
+    pub fn ack(&mut self, seq: u32) {
+        while let Some(packet) = self.pushes.front() {
+            if (packet.seq.wrapping_sub(seq) as i32) > 0 {
+                break;
+            }
+            self.pushes.pop_front();
+        }
+    }
+
+    //客户端收到last_seq之后需要补发的推送,有被丢弃的推送时返回None
+    pub fn missed(&mut self, last_seq: u32) -> Option<Vec<Packet>> {
+        self.ack(last_seq);
+        let first = self
+            .pushes
+            .front()
+            .map_or(self.seq.wrapping_add(1), |x| x.seq);
+        if first != last_seq.wrapping_add(1) {
+            return None;
+        }
+        Some(self.pushes.iter().cloned().collect())
+    }
+}
+
+//断线后保留的会话,等待客户端凭resume token重连
+#[derive(Debug)]
+pub struct Suspended {
+    pub session: Session,
+    pub pushes: PushBuffer,
+    pub expire_at: u128,
+}
+
+//本网关已登录的连接,按账号和玩家id索引
+#[derive(Default)]
+pub struct SessionRegistry {
+    //所有存活的连接,包括还没有登录的
+    connections: DashMap<ActorID, ActorRef<ClientActor>>,
+    accounts: DashMap<String, ActorRef<ClientActor>>,
+    players: DashMap<i64, ActorRef<ClientActor>>,
+    //resume token -> 断线的会话
+    suspended: DashMap<String, Suspended>,
+    //玩家id -> resume token
+    suspended_players: DashMap<i64, String>,
+    //频道(公会、房间等)中本网关的玩家
+    channels: DashMap<String, HashSet<i64>>,
+    player_channels: DashMap<i64, HashSet<String>>,
+}
+
+impl SessionRegistry {
+    pub fn connect(&self, actor_ref: &ActorRef<ClientActor>) {
+        self.connections.insert(actor_ref.id(), actor_ref.clone());
+    }
+
+    pub fn disconnect(&self, actor_id: ActorID) {
+        self.connections.remove(&actor_id);
+    }
+
+    //登记会话,返回同一玩家之前的连接
+    pub fn bind(
+        &self,
+        session: &Session,
+        actor_ref: &ActorRef<ClientActor>,
+    ) -> Option<ActorRef<ClientActor>> {
+        let mut old = None;
+        //重新登录后断线保留的会话失效
+        if let Some(player_id) = session.player_id {
+            self.discard(&KickTarget::Player(player_id));
+        }
+        if let Some(account) = &session.account {
+            old = self.accounts.insert(account.clone(), actor_ref.clone());
+        }
+        if let Some(player_id) = session.player_id {
+            old = self.players.insert(player_id, actor_ref.clone()).or(old);
+        }
+        old.filter(|x| x.id() != actor_ref.id())
+    }
+
+    //连接关闭时移除,已被新连接替换的不移除,返回玩家是否已经不在本网关
+    pub fn unbind(&self, session: &Session, actor_id: ActorID) -> bool {
+        if let Some(account) = &session.account {
+            self.accounts.remove_if(account, |_, v| v.id() == actor_id);
+        }
+        match session.player_id {
+            Some(player_id) => self
+                .players
+                .remove_if(&player_id, |_, v| v.id() == actor_id)
+                .is_some(),
+            None => false,
+        }
+    }
+
+    pub fn find(&self, target: &KickTarget) -> Option<ActorRef<ClientActor>> {
+        match target {
+            KickTarget::Account(account) => self.accounts.get(account).map(|x| x.clone()),
+            KickTarget::Player(player_id) => self.players.get(player_id).map(|x| x.clone()),
+        }
+    }
+
+    //连接断开后保留会话,同一玩家之前保留的会话被替换
+    pub fn suspend(&self, token: String, suspended: Suspended) {
+        if let Some(player_id) = suspended.session.player_id
+            && let Some(old) = self.suspended_players.insert(player_id, token.clone())
+        {
+            self.suspended.remove(&old);
+        }
+        self.suspended.insert(token, suspended);
+    }
+
+    //凭token取回保留的会话,过期的不能恢复
+    pub fn resume(&self, token: &str, now: u128) -> Option<Suspended> {
+        let (token, suspended) = self.suspended.remove(token)?;
+        if let Some(player_id) = suspended.session.player_id {
+            self.suspended_players
+                .remove_if(&player_id, |_, v| *v == token);
+        }
+        Some(suspended).filter(|x| x.expire_at > now)
+    }
+
+    //丢弃保留的会话,返回是否存在
+    pub fn discard(&self, target: &KickTarget) -> bool {
+        let token = match target {
+            KickTarget::Player(player_id) => {
+                self.suspended_players.get(player_id).map(|x| x.clone())
+            }
+            KickTarget::Account(account) => self
+                .suspended
+                .iter()
+                .find(|x| x.session.account.as_ref() == Some(account))
+                .map(|x| x.key().clone()),
+        };
+        let Some(token) = token else {
+            return false;
+        };
+        if let Some(player_id) = self.resume(&token, 0).and_then(|x| x.session.player_id) {
+            self.leave_all(player_id);
+        }
+        true
+    }
+
+    //清理过期的会话
+    pub fn expire(&self, now: u128) {
+        self.suspended.retain(|_, v| {
+            if v.expire_at > now {
+                return true;
+            }
+            tracing::info!(
+                "session expired account:{:?} player:{:?}",
+                v.session.account,
+                v.session.player_id
+            );
+            if let Some(player_id) = v.session.player_id {
+                self.leave_all(player_id);
+            }
+            false
+        });
+        self.suspended_players
+            .retain(|_, token| self.suspended.contains_key(token));
+    }
+
+    //推送给玩家,断线保留期间先缓存,重连后补发
+    //不等待队列,队列已满的连接丢弃本次推送
+    pub fn push(&self, player_id: i64, payload: &Arc<SharedPayload>) -> bool {
+        let actor_ref = self.players.get(&player_id).map(|x| x.clone());
+        if let Some(actor_ref) = actor_ref {
+            match actor_ref
+                .tell(ClientMessage::Push(payload.clone()))
+                .try_send()
+            {
+                Ok(()) => return true,
+                Err(SendError::MailboxFull(_)) => {
+                    tracing::warn!(
+                        "player:{} mailbox full, push:{} dropped",
+                        player_id,
+                        payload.cmd
+                    );
+                    return false;
+                }
+                //连接正在关闭,可能已进入断线保留
+                Err(_) => {}
+            }
+        }
+        let Some(token) = self.suspended_players.get(&player_id).map(|x| x.clone()) else {
+            return false;
+        };
+        match self.suspended.get_mut(&token) {
+            Some(mut suspended) => {
+                suspended.pushes.push(payload.cmd, payload.data.clone());
+                true
+            }
+            None => false,
+        }
+    }
+
+    //本网关的玩家,包括断线保留中的
+    fn contains(&self, player_id: i64) -> bool {
+        self.players.contains_key(&player_id) || self.suspended_players.contains_key(&player_id)
+    }
+
+    //推送给本网关的所有连接,包括还没有登录的,断线保留中的会话先缓存,返回推送的数量
+    pub fn push_all(&self, payload: &Arc<SharedPayload>) -> usize {
+        let mut count = 0;
+        for actor_ref in self.connections.iter() {
+            match actor_ref
+                .tell(ClientMessage::Push(payload.clone()))
+                .try_send()
+            {
+                Ok(()) => count += 1,
+                Err(SendError::MailboxFull(_)) => {
+                    tracing::warn!(
+                        "actor:{} mailbox full, push:{} dropped",
+                        actor_ref.id(),
+                        payload.cmd
+                    );
+                }
+                Err(_) => {}
+            }
+        }
+        for mut suspended in self.suspended.iter_mut() {
+            suspended.pushes.push(payload.cmd, payload.data.clone());
+            count += 1;
+        }
+        count
+    }
+
+    //加入频道,只记录在本网关的玩家,返回不在本网关被忽略的玩家
+    pub fn join(&self, channel: &str, players: &[i64]) -> Vec<i64> {
+        let (players, ignored): (Vec<i64>, Vec<i64>) =
+            players.iter().partition(|x| self.contains(**x));
+        if players.is_empty() {
+            return ignored;
+        }
+        for player_id in &players {
+            self.player_channels
+                .entry(*player_id)
+                .or_default()
+                .insert(channel.to_string());
+        }
+        self.channels
+            .entry(channel.to_string())
+            .or_default()
+            .extend(players.iter().copied());
+        ignored
+    }
+
+    pub fn leave(&self, channel: &str, players: &[i64]) {
+        for player_id in players {
+            self.player_channels
+                .remove_if_mut(player_id, |_, v| v.remove(channel) && v.is_empty());
+        }
+        self.channels.remove_if_mut(channel, |_, v| {
+            players.iter().for_each(|x| {
+                v.remove(x);
+            });
+            v.is_empty()
+        });
+    }
+
+    //玩家离开本网关时退出所有频道
+    pub fn leave_all(&self, player_id: i64) {
+        let Some((_, channels)) = self.player_channels.remove(&player_id) else {
+            return;
+        };
+        for channel in channels {
+            self.channels
+                .remove_if_mut(&channel, |_, v| v.remove(&player_id) && v.is_empty());
+        }
+    }
+
+    pub fn members(&self, channel: &str) -> Vec<i64> {
+        self.channels
+            .get(channel)
+            .map(|x| x.iter().copied().collect())
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn suspended(player_id: i64, capacity: usize, expire_at: u128) -> Suspended {
+        Suspended {
+            session: Session {
+                account: Some(format!("account{}", player_id)),
+                player_id: Some(player_id),
+                ..Default::default()
+            },
+            pushes: PushBuffer::new(capacity),
+            expire_at,
+        }
+    }
+
+    #[test]
+    fn push_buffer() {
+        let mut buffer = PushBuffer::new(3);
+        for i in 0..5 {
+            assert_eq!(buffer.push(1, Bytes::from(vec![i])).seq, i as u32 + 1);
+        }
+        //1、2已被丢弃
+        assert!(buffer.missed(0).is_none());
+        let missed = buffer.missed(3).unwrap();
+        assert_eq!(missed.iter().map(|x| x.seq).collect::<Vec<_>>(), vec![4, 5]);
+        buffer.ack(5);
+        assert!(buffer.missed(5).unwrap().is_empty());
+        //客户端的序号比服务器新
+        assert!(buffer.missed(6).is_none());
+    }
+
+    #[test]
+    fn suspend_and_resume() {
+        let registry = SessionRegistry::default();
+        let payload = Arc::new(SharedPayload::new(10, Bytes::from_static(b"x")));
+        registry.suspend("a".to_string(), suspended(1, 8, 100));
+        assert!(registry.push(1, &payload));
+        assert!(!registry.push(2, &payload));
+        //断线保留中的会话也缓存全服推送
+        assert_eq!(registry.push_all(&payload), 1);
+        let mut resumed = registry.resume("a", 50).unwrap();
+        assert_eq!(resumed.session.player_id, Some(1));
+        assert_eq!(resumed.pushes.missed(0).unwrap().len(), 2);
+        //token只能使用一次
+        assert!(registry.resume("a", 50).is_none());
+        assert!(!registry.push(1, &payload));
+
+        registry.suspend("b".to_string(), suspended(1, 8, 100));
+        assert!(registry.resume("b", 100).is_none());
+
+        //同一玩家再次断线,旧token失效
+        registry.suspend("c".to_string(), suspended(1, 8, 100));
+        registry.suspend("d".to_string(), suspended(1, 8, 100));
+        assert!(registry.resume("c", 0).is_none());
+        assert!(registry.discard(&KickTarget::Account("account1".to_string())));
+        assert!(registry.resume("d", 0).is_none());
+
+        registry.suspend("e".to_string(), suspended(2, 8, 100));
+        registry.expire(100);
+        assert!(!registry.discard(&KickTarget::Player(2)));
+    }
+
+    #[test]
+    fn channels() {
+        let registry = SessionRegistry::default();
+        registry.suspend("a".to_string(), suspended(1, 8, 100));
+        registry.suspend("b".to_string(), suspended(2, 8, 100));
+        //不在本网关的玩家不记录
+        assert_eq!(registry.join("guild", &[1, 2, 3]), vec![3]);
+        assert!(registry.join("room", &[1]).is_empty());
+        let mut members = registry.members("guild");
+        members.sort();
+        assert_eq!(members, vec![1, 2]);
+        registry.leave("guild", &[2]);
+        assert_eq!(registry.members("guild"), vec![1]);
+        //断线保留过期后退出所有频道
+        registry.expire(100);
+        assert!(registry.members("guild").is_empty());
+        assert!(registry.members("room").is_empty());
+        assert!(registry.channels.is_empty());
+        assert!(registry.player_channels.is_empty());
+        assert!(registry.suspended_players.is_empty());
+    }
+}
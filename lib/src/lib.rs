@@ -1,19 +1,21 @@
-use crate::DataError::{Other, RspError};
+use crate::DataError::{Other, RspError, Timeout, Unreachable};
 use bytes::{Bytes, BytesMut};
 use kameo::Reply;
 use prost::Message;
-use protocol::base_cmd::BaseError::ErrorServerInternal;
+use protocol::base_cmd::BaseError::{ErrorServerInternal, ErrorServerNotFound};
 use protocol::base_cmd::ErrorRsp;
 use serde::{Deserialize, Serialize};
 use std::ops::Deref;
 use thiserror::Error;
 
+pub mod admin;
 mod discovery;
 mod game;
-mod gate;
+pub mod gate;
 mod login;
 pub mod node;
 mod registry;
+pub mod store;
 mod world;
 
 mod center;
@@ -38,6 +40,12 @@ pub enum DataError {
     RspError(i32, String),
     #[error("other error:{0}")]
     Other(String),
+    //远程节点不可达,请求确定没有送达,可以重新选择节点重试
+    #[error("unreachable:{0}")]
+    Unreachable(String),
+    //请求已经发出但没有收到结果,可能已经执行,不能重试
+    #[error("timeout:{0}")]
+    Timeout(String),
 }
 
 impl From<DataError> for ErrorRsp {
@@ -53,6 +61,16 @@ impl From<DataError> for ErrorRsp {
                 code: ErrorServerInternal as i32,
                 message: msg,
             },
+            Unreachable(msg) => ErrorRsp {
+                cmd: 0,
+                code: ErrorServerNotFound as i32,
+                message: msg,
+            },
+            Timeout(msg) => ErrorRsp {
+                cmd: 0,
+                code: ErrorServerInternal as i32,
+                message: msg,
+            },
         }
     }
 }
@@ -1,12 +1,52 @@
 use crate::DataError;
+use crate::gate::KickTarget;
 use crate::login::node::LoginActor;
+use protocol::base_cmd::KickReason;
+use protocol::login_cmd::LoginError::ErrorLoginAccountNotExits;
 use protocol::login_cmd::{LoginReq, LoginRsp, RegisterReq, RegisterRsp};
 
+//账号到玩家id的映射,按服务器id分表
+const ACCOUNT_PLAYER_KEY: &str = "account_player";
+const PLAYER_ID_SEQ_KEY: &str = "player_id_seq";
+
 pub async fn login_req(actor: &mut LoginActor, msg: LoginReq) -> Result<LoginRsp, DataError> {
     let account = msg.account;
     let server_id = msg.server_id;
-    let mut redis_conn = &mut actor.redis_conn;
-    Ok(LoginRsp {})
+    if account.is_empty() {
+        return Err(DataError::RspError(
+            ErrorLoginAccountNotExits as i32,
+            "empty account".to_string(),
+        ));
+    }
+    let store = &mut actor.store;
+    let key = format!("{}:{}", ACCOUNT_PLAYER_KEY, server_id);
+    let player_id = store.hget(&key, &account).await?;
+    let player_id = match player_id {
+        Some(player_id) => player_id,
+        None => {
+            //首次登录分配玩家id,并发登录时以先写入的为准
+            let player_id = store.incr(PLAYER_ID_SEQ_KEY, 1).await?;
+            store.hset_nx(&key, &account, player_id).await?;
+            store
+                .hget(&key, &account)
+                .await?
+                .ok_or_else(|| DataError::Other(format!("account:{} lost", account)))?
+        }
+    };
+    //踢掉其他设备上的登录,本次登录的连接在收到响应后才绑定玩家,不会被踢
+    if let Err(e) = actor
+        .node_manager
+        .kick(
+            KickTarget::Player(player_id),
+            KickReason::KickDuplicateLogin,
+            "login from another device".to_string(),
+            0,
+        )
+        .await
+    {
+        tracing::warn!("player:{} kick duplicate login failed:{:?}", player_id, e);
+    }
+    Ok(LoginRsp { player_id })
 }
 pub async fn register_req(
     actor: &mut LoginActor,
@@ -1,15 +1,19 @@
-use crate::node::Node;
+use crate::discovery::NodeManager;
+use crate::node::{Node, stop_actor};
+use crate::store::Store;
 use common::config::{GlobalConfig, LoginServerConfig, ServerRoleId};
 use kameo::actor::ActorRef;
 use kameo::{Actor, RemoteActor};
-use redis::aio::ConnectionManager;
 use std::fmt::{Display, Formatter};
 use std::sync::Arc;
+use std::time::Duration;
 
 pub struct LoginNode {
     global_config: Arc<GlobalConfig>,
     role_id: ServerRoleId,
     login_ref: Option<ActorRef<LoginActor>>,
+    //不指定时按配置连接redis
+    store: Option<Store>,
 }
 impl LoginNode {
     pub fn new(global_config: Arc<GlobalConfig>, role_id: ServerRoleId) -> Self {
@@ -17,8 +21,14 @@ impl LoginNode {
             global_config,
             role_id,
             login_ref: None,
+            store: None,
         }
     }
+
+    pub fn with_store(mut self, store: Store) -> Self {
+        self.store = Some(store);
+        self
+    }
 }
 #[async_trait::async_trait]
 impl Node for LoginNode {
@@ -38,14 +48,17 @@ impl Node for LoginNode {
             .await?;
 
         //连接redis
-        let redis_conn = common::redis::create(&login_config.keydb).await?;
+        let store = match self.store.clone() {
+            Some(store) => store,
+            None => Store::Redis(common::redis::create(&login_config.keydb).await?),
+        };
 
         //集群启动好后,启动LoginActor
         let login_ref = kameo::spawn(LoginActor::new(
             global_config,
             role_id,
             login_config,
-            redis_conn,
+            store,
         ));
         let result = login_ref.wait_startup_result().await;
         if let Err(e) = result {
@@ -63,15 +76,19 @@ impl Node for LoginNode {
 
     async fn stop(&mut self) -> anyhow::Result<()> {
         let actor_ref = self.login_ref.take().unwrap();
-        //停止actor
-        actor_ref.kill();
-        actor_ref.wait_for_stop().await;
+        //处理完队列中的消息后停止actor
+        let timeout = Duration::from_secs(self.global_config.shutdown.stop_timeout);
+        stop_actor(actor_ref, timeout).await;
         Ok(())
     }
 
     fn server_role_id(&self) -> ServerRoleId {
         self.role_id.clone()
     }
+
+    fn global_config(&self) -> &GlobalConfig {
+        &self.global_config
+    }
 }
 
 #[derive(RemoteActor)]
@@ -79,7 +96,8 @@ pub struct LoginActor {
     config: Arc<GlobalConfig>,
     server_role_id: ServerRoleId,
     login_config: LoginServerConfig,
-    pub redis_conn: ConnectionManager,
+    pub store: Store,
+    pub node_manager: NodeManager,
 }
 
 impl LoginActor {
@@ -87,13 +105,14 @@ impl LoginActor {
         config: Arc<GlobalConfig>,
         server_role_id: ServerRoleId,
         login_config: LoginServerConfig,
-        redis_conn: ConnectionManager,
+        store: Store,
     ) -> Self {
         Self {
             config,
             server_role_id,
             login_config,
-            redis_conn,
+            store,
+            node_manager: NodeManager::new(),
         }
     }
 }
@@ -1,37 +1,131 @@
+use crate::admin::AdminActor;
 use crate::center::{CenterActor, CenterMessage};
-use common::config::{ServerRole, ServerRoleId};
-use kameo::actor::RemoteActorRef;
+use common::config::{GlobalConfig, ServerRole, ServerRoleId};
+use common::resource::{ReloadStatus, TableVersion};
+use kameo::Actor;
+use kameo::actor::{ActorRef, RemoteActorRef};
+use kameo::error::BootstrapError;
 use kameo::prelude::ActorSwarm;
 use kameo::remote::dial_opts::DialOpts;
-use std::ops::Deref;
+use std::sync::{Mutex, OnceLock};
 use std::time::Duration;
+use tokio::sync::watch;
 use tokio::sync::watch::Receiver;
 
+//本进程监听的集群地址,同一进程的多个角色共用一个ActorSwarm,互相之间不需要连接
+static LISTEN_ADDRESSES: Mutex<Vec<String>> = Mutex::new(vec![]);
+//本进程所有节点共用的信号
+static SIGNAL: OnceLock<watch::Sender<Signal>> = OnceLock::new();
+
+//停服流程的阶段,按顺序执行
+//watch只保留最新的值,节点收到后面的阶段时先补执行前面还没执行的阶段
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub enum Signal {
     None,
+    //重新加载配置表,不属于停服流程
+    ReloadConfig,
+    //配置表已经回滚,只广播和上报当前版本,不重新加载
+    ConfigChanged,
+    //从Center注销,网关停止接收连接并踢下线所有玩家
+    Drain,
+    //保存玩家数据
+    Flush,
+    //停止actor
     Stop,
 }
+
+impl Signal {
+    pub fn next(self) -> Signal {
+        match self {
+            Signal::None | Signal::ReloadConfig | Signal::ConfigChanged => Signal::Drain,
+            Signal::Drain => Signal::Flush,
+            Signal::Flush | Signal::Stop => Signal::Stop,
+        }
+    }
+}
+
+pub fn signal_sender() -> &'static watch::Sender<Signal> {
+    SIGNAL.get_or_init(|| watch::channel(Signal::None).0)
+}
+
+//通知本进程的节点重新加载配置表,停服中返回false
+pub fn reload_config() -> bool {
+    let modified = notify_config(Signal::ReloadConfig);
+    if modified {
+        common::resource::request_reload();
+    }
+    modified
+}
+
+//回滚到内存中保留的版本,再通知本进程的节点广播和上报新版本
+pub fn rollback_config(hash: &str) -> anyhow::Result<TableVersion> {
+    let version = common::resource::rollback(hash).map_err(|e| anyhow::anyhow!("{}", e))?;
+    notify_config(Signal::ConfigChanged);
+    Ok(version)
+}
+
+fn notify_config(signal: Signal) -> bool {
+    signal_sender().send_if_modified(|current| {
+        if *current >= Signal::Drain {
+            return false;
+        }
+        *current = signal;
+        true
+    })
+}
+
+//等待actor处理完队列中的消息后停止,超时则强制停止
+pub async fn stop_actor<A: Actor>(actor_ref: ActorRef<A>, timeout: Duration) {
+    //发送失败说明actor已经停止
+    if actor_ref.stop_gracefully().await.is_err() {
+        return;
+    }
+    if tokio::time::timeout(timeout, actor_ref.wait_for_stop())
+        .await
+        .is_err()
+    {
+        tracing::warn!("actor:{} stop timeout, killed", actor_ref.id());
+        actor_ref.kill();
+        actor_ref.wait_for_stop().await;
+    }
+}
+
 #[async_trait::async_trait]
 pub trait Node: Send + Sync {
     async fn init(&mut self, mut signal_rx: Receiver<Signal>) -> anyhow::Result<()> {
         tracing::info!("starting node :{}", self.server_role_id());
 
         self.start().await?;
-        loop {
-            let changed = signal_rx.changed().await;
-            match changed {
-                Ok(_) => {
-                    let signal = signal_rx.borrow();
-                    match signal.deref() {
-                        Signal::Stop => {
-                            break;
-                        }
-                        _ => {}
-                    }
-                }
+        let mut phase = Signal::None;
+        while phase < Signal::Stop {
+            let signal = match signal_rx.changed().await {
+                Ok(_) => *signal_rx.borrow_and_update(),
                 Err(e) => {
+                    //发送端已经关闭,执行完剩下的阶段
                     tracing::error!("{} signal recv err :{}", self.server_role_id(), e);
+                    Signal::Stop
+                }
+            };
+            match signal {
+                Signal::ReloadConfig => {
+                    self.reload_config().await;
+                    continue;
+                }
+                Signal::ConfigChanged => {
+                    let version = common::resource::current();
+                    tracing::info!(
+                        "node :{} config changed to:{:?}",
+                        self.server_role_id(),
+                        version
+                    );
+                    self.publish_config(version.as_ref()).await;
+                    continue;
                 }
+                _ => {}
+            }
+            while phase < signal {
+                phase = phase.next();
+                self.shutdown_phase(phase).await;
             }
         }
         tracing::info!("stopping node :{}", self.server_role_id());
@@ -40,12 +134,103 @@ pub trait Node: Send + Sync {
         Ok(())
     }
 
+    //执行停服的一个阶段,出错只记录日志,继续后面的阶段
+    async fn shutdown_phase(&mut self, phase: Signal) {
+        tracing::info!("node :{} shutdown phase:{:?}", self.server_role_id(), phase);
+        let result = match phase {
+            Signal::Drain => self.drain().await,
+            Signal::Flush => self.flush().await,
+            Signal::None | Signal::ReloadConfig | Signal::ConfigChanged | Signal::Stop => Ok(()),
+        };
+        if let Err(e) = result {
+            tracing::error!(
+                "node :{} shutdown phase:{:?} failed:{}",
+                self.server_role_id(),
+                phase,
+                e
+            );
+        }
+    }
+
     async fn start(&mut self) -> anyhow::Result<()>;
 
+    //停止接收新的请求,默认只从Center注销
+    async fn drain(&mut self) -> anyhow::Result<()> {
+        self.disconnect_center().await
+    }
+
+    //保存数据,默认没有需要保存的数据
+    async fn flush(&mut self) -> anyhow::Result<()> {
+        Ok(())
+    }
+
     async fn stop(&mut self) -> anyhow::Result<()>;
 
+    //校验并替换配置表,失败时继续使用旧的配置表
+    async fn reload_config(&mut self) {
+        let config = self.global_config().config.clone();
+        let result = tokio::task::spawn_blocking(move || common::resource::reload(&config)).await;
+        let version = match result {
+            Ok(Ok(ReloadStatus::Reloaded(version))) => {
+                tracing::info!("node :{} config version:{}", self.server_role_id(), version);
+                Some(version)
+            }
+            Ok(Ok(ReloadStatus::Unchanged(version))) => {
+                tracing::info!(
+                    "node :{} config unchanged, version:{}",
+                    self.server_role_id(),
+                    version
+                );
+                None
+            }
+            Ok(Err(e)) => {
+                tracing::error!(
+                    "node :{} reload config failed, keep version:{:?} :{}",
+                    self.server_role_id(),
+                    common::resource::current(),
+                    e
+                );
+                None
+            }
+            Err(e) => {
+                tracing::error!("node :{} reload config err :{}", self.server_role_id(), e);
+                None
+            }
+        };
+        self.publish_config(version.as_ref()).await;
+    }
+
+    //广播新的版本并上报当前版本,version为None时只上报
+    //只有配置表真正替换后才调用config_reloaded
+    async fn publish_config(&mut self, version: Option<&TableVersion>) {
+        if let Some(version) = version
+            && let Err(e) = self.config_reloaded(version).await
+        {
+            tracing::error!(
+                "node :{} broadcast config version:{} failed:{}",
+                self.server_role_id(),
+                version,
+                e
+            );
+        }
+        if let Err(e) = self.report_config_version().await {
+            tracing::error!(
+                "node :{} report config version failed:{}",
+                self.server_role_id(),
+                e
+            );
+        }
+    }
+
+    //配置表已替换为version
+    async fn config_reloaded(&mut self, _version: &TableVersion) -> anyhow::Result<()> {
+        Ok(())
+    }
+
     fn server_role_id(&self) -> ServerRoleId;
 
+    fn global_config(&self) -> &GlobalConfig;
+
     async fn start_actor_swarm(
         &self,
         self_address: String,
@@ -53,18 +238,36 @@ pub trait Node: Send + Sync {
     ) -> anyhow::Result<()> {
         //启动集群
         let role_id = self.server_role_id();
-        let actor_swarm = ActorSwarm::bootstrap()
-            .expect(format!("actor_swarm bootstrap failed :{}", role_id).as_str());
+        let (actor_swarm, bootstrapped) = match ActorSwarm::bootstrap() {
+            Ok(actor_swarm) => (actor_swarm, true),
+            //ActorSwarm是进程内全局的,已经由同一进程的其他角色启动
+            Err(BootstrapError::AlreadyBootstrapped(actor_swarm, _)) => (actor_swarm, false),
+            Err(e) => {
+                return Err(anyhow::anyhow!(
+                    "actor_swarm bootstrap failed :{} {:?}",
+                    role_id,
+                    e
+                ));
+            }
+        };
         let listener_id = actor_swarm.listen_on(self_address.parse()?).await?;
+        LISTEN_ADDRESSES.lock().unwrap().push(self_address.clone());
         tracing::info!(
             "ActorSwarm[{}] listening addr:{} listener_id:{}",
             role_id,
             self_address,
             listener_id
         );
+        //每个进程一个AdminActor
+        if bootstrapped {
+            let admin_ref = kameo::spawn(AdminActor::new(*actor_swarm.local_peer_id()));
+            if let Err(e) = admin_ref.wait_startup_result().await {
+                return Err(anyhow::anyhow!("AdminActor start failed:{}", e));
+            }
+        }
         //连接其他地址
         for other_addr in other_addresses {
-            if other_addr == self_address {
+            if LISTEN_ADDRESSES.lock().unwrap().contains(&other_addr) {
                 continue;
             }
             actor_swarm
@@ -92,9 +295,59 @@ pub trait Node: Send + Sync {
         actor_ref
             .tell(&CenterMessage::Register {
                 server_role_id: self.server_role_id(),
-                peer_id: actor_swarm.local_peer_id().clone(),
+                peer_id: *actor_swarm.local_peer_id(),
+            })
+            .await?;
+        actor_ref
+            .tell(&CenterMessage::ConfigVersion {
+                server_role_id,
+                version: common::resource::current(),
             })
             .await?;
         Ok(actor_ref)
     }
+
+    //上报当前的配置表版本,Center据此检查集群中的版本是否一致
+    async fn report_config_version(&self) -> anyhow::Result<()> {
+        let server_role_id = self.server_role_id();
+        if server_role_id.0 == ServerRole::Center {
+            return Ok(());
+        }
+        let actor_ref =
+            RemoteActorRef::<CenterActor>::lookup(&ServerRole::Center.to_string()).await?;
+        let Some(actor_ref) = actor_ref else {
+            return Err(anyhow::anyhow!("center not found"));
+        };
+        actor_ref
+            .tell(&CenterMessage::ConfigVersion {
+                server_role_id,
+                version: common::resource::current(),
+            })
+            .await?;
+        Ok(())
+    }
+
+    //从Center注销,其他节点不再把请求路由到本节点
+    async fn disconnect_center(&self) -> anyhow::Result<()> {
+        let server_role_id = self.server_role_id();
+        if server_role_id.0 == ServerRole::Center {
+            return Ok(());
+        }
+        let Some(actor_swarm) = ActorSwarm::get() else {
+            return Ok(());
+        };
+        let actor_ref =
+            RemoteActorRef::<CenterActor>::lookup(&ServerRole::Center.to_string()).await?;
+        //Center已经停止
+        let Some(actor_ref) = actor_ref else {
+            return Ok(());
+        };
+        actor_ref
+            .tell(&CenterMessage::Unregister {
+                server_role_id,
+                peer_id: *actor_swarm.local_peer_id(),
+            })
+            .await?;
+        Ok(())
+    }
 }
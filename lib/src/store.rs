@@ -0,0 +1,81 @@
+use crate::DataError;
+use dashmap::DashMap;
+use redis::AsyncCommands;
+use redis::aio::ConnectionManager;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+//节点使用的kv存储,测试时用进程内的内存存储代替redis
+#[derive(Clone)]
+pub enum Store {
+    Redis(ConnectionManager),
+    Memory(Arc<MemoryStore>),
+}
+
+//只实现用到的命令,语义与redis一致
+#[derive(Debug, Default)]
+pub struct MemoryStore {
+    strings: DashMap<String, i64>,
+    hashes: DashMap<String, HashMap<String, i64>>,
+}
+
+impl Store {
+    pub fn memory() -> Self {
+        Store::Memory(Arc::new(MemoryStore::default()))
+    }
+
+    pub async fn hget(&mut self, key: &str, field: &str) -> Result<Option<i64>, DataError> {
+        match self {
+            Store::Redis(conn) => conn.hget(key, field).await.map_err(redis_err),
+            Store::Memory(store) => Ok(store.hashes.get(key).and_then(|x| x.get(field).copied())),
+        }
+    }
+
+    pub async fn hset_nx(&mut self, key: &str, field: &str, value: i64) -> Result<bool, DataError> {
+        match self {
+            Store::Redis(conn) => conn.hset_nx(key, field, value).await.map_err(redis_err),
+            Store::Memory(store) => {
+                let mut hash = store.hashes.entry(key.to_string()).or_default();
+                if hash.contains_key(field) {
+                    return Ok(false);
+                }
+                hash.insert(field.to_string(), value);
+                Ok(true)
+            }
+        }
+    }
+
+    pub async fn incr(&mut self, key: &str, delta: i64) -> Result<i64, DataError> {
+        match self {
+            Store::Redis(conn) => conn.incr(key, delta).await.map_err(redis_err),
+            Store::Memory(store) => {
+                let mut value = store.strings.entry(key.to_string()).or_default();
+                *value += delta;
+                Ok(*value)
+            }
+        }
+    }
+}
+
+fn redis_err(e: redis::RedisError) -> DataError {
+    DataError::Other(e.to_string())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[tokio::test]
+    async fn memory_store() {
+        let mut store = Store::memory();
+        assert_eq!(store.hget("account", "a").await.unwrap(), None);
+        assert!(store.hset_nx("account", "a", 1).await.unwrap());
+        assert!(!store.hset_nx("account", "a", 2).await.unwrap());
+        assert_eq!(store.hget("account", "a").await.unwrap(), Some(1));
+        assert_eq!(store.incr("seq", 1).await.unwrap(), 1);
+        assert_eq!(store.incr("seq", 2).await.unwrap(), 3);
+        //clone共用同一份数据
+        let mut other = store.clone();
+        assert_eq!(other.incr("seq", 1).await.unwrap(), 4);
+    }
+}
@@ -1,3 +1,4 @@
+use crate::discovery::NodeManager;
 use crate::node::Node;
 use crate::{DataError, ServerMessage};
 use common::config::{GlobalConfig, ServerRoleId, WorldServerConfig};
@@ -13,6 +14,8 @@ pub struct WorldActor {
     config: Arc<GlobalConfig>,
     server_role_id: ServerRoleId,
     world_config: WorldServerConfig,
+    //推送给客户端
+    pub node_manager: NodeManager,
 }
 
 impl WorldActor {
@@ -25,6 +28,7 @@ impl WorldActor {
             config,
             server_role_id,
             world_config,
+            node_manager: NodeManager::new(),
         }
     }
 }
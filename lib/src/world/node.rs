@@ -1,8 +1,9 @@
-use crate::node::Node;
+use crate::node::{Node, stop_actor};
 use crate::world::WorldActor;
 use common::config::{GlobalConfig, ServerRoleId};
 use kameo::actor::ActorRef;
 use std::sync::Arc;
+use std::time::Duration;
 
 pub struct WorldNode {
     global_config: Arc<GlobalConfig>,
@@ -52,13 +53,17 @@ impl Node for WorldNode {
 
     async fn stop(&mut self) -> anyhow::Result<()> {
         let actor_ref = self.world_ref.take().unwrap();
-        //停止actor
-        actor_ref.kill();
-        actor_ref.wait_for_stop().await;
+        //处理完队列中的消息后停止actor
+        let timeout = Duration::from_secs(self.global_config.shutdown.stop_timeout);
+        stop_actor(actor_ref, timeout).await;
         Ok(())
     }
 
     fn server_role_id(&self) -> ServerRoleId {
         self.role_id.clone()
     }
+
+    fn global_config(&self) -> &GlobalConfig {
+        &self.global_config
+    }
 }
@@ -36,7 +36,9 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     }
     let base_dir = "./src";
     let proto_dir = Path::new(base_dir);
-    let proto_files = find_proto_files(proto_dir)?;
+    let mut proto_files = find_proto_files(proto_dir)?;
+    //保证生成文件顺序稳定
+    proto_files.sort();
 
     check_code(proto_files.clone(), "cmd.txt", true)?;
     check_code(proto_files.clone(), "error.txt", false)?;
@@ -72,6 +74,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         writeln!(&mut mod_content, "}}")?;
     }
     mod_content.push_str("pub mod extension;\n");
+    mod_content.push_str("pub mod route;\n");
     // 写入 lib.rs
     fs::write(mod_path, mod_content)?;
     Ok(())
@@ -85,9 +88,11 @@ fn check_code(
     let file = File::open(code_file)?;
     let reader = BufReader::new(file);
     let mut range_map: HashMap<String, (u16, u16)> = HashMap::new();
+    //模块转发的目标服务,未声明的默认转发到game
+    let mut target_map: HashMap<String, String> = HashMap::new();
     let mut unique_codes = HashSet::new();
     let mut code_map: BTreeMap<u16, String> = BTreeMap::new();
-    let re = Regex::new(r"(\w+)\s*=\s*\[\s*(\d+)\s*,\s*(\d+)\s*]").unwrap();
+    let re = Regex::new(r"(\w+)\s*=\s*\[\s*(\d+)\s*,\s*(\d+)\s*](?:\s*->\s*(\w+))?").unwrap();
     for line in reader.lines() {
         let line = line?;
         if line.is_empty() {
@@ -98,8 +103,12 @@ fn check_code(
                 captures[1].to_string(),
                 (captures[2].parse()?, captures[3].parse()?),
             );
+            if let Some(target) = captures.get(4) {
+                target_map.insert(captures[1].to_string(), target.as_str().to_string());
+            }
         }
     }
+    let mut routes: Vec<(u16, u16, String, String)> = vec![];
     let re_code = Regex::new(r"(\w+)\s*=\s*\s*(\d+)\s*").unwrap();
     let mut extension_content = String::new();
     for file in proto_files {
@@ -122,6 +131,19 @@ fn check_code(
                 enum_name, code_file
             ),
         };
+        if cmd {
+            let target = target_map
+                .get(&enum_name)
+                .map(|x| x.to_lowercase())
+                .unwrap_or("game".to_string());
+            if !ROUTE_TARGETS.contains(&target.as_str()) {
+                panic!(
+                    "{} route target:{} not in {:?} file:{}",
+                    enum_name, target, ROUTE_TARGETS, code_file
+                );
+            }
+            routes.push((*range.0, *range.1, enum_name.clone(), target));
+        }
 
         // 1. 读取文件内容
         let content = fs::read_to_string(file)?;
@@ -198,11 +220,67 @@ impl crate::{}::{} {{
     if cmd {
         fs::write(Path::new("./src/client/cmd.proto"), proto_content)?;
         fs::write(Path::new("./src/extension.rs"), extension_content)?;
+        fs::write(Path::new("./src/route.rs"), route_content(routes))?;
     } else {
         fs::write(Path::new("./src/client/error.proto"), proto_content)?;
     }
     Ok(())
 }
+const ROUTE_TARGETS: [&str; 4] = ["gate", "login", "world", "game"];
+
+//生成命令码到目标服务的路由表
+fn route_content(mut routes: Vec<(u16, u16, String, String)>) -> String {
+    routes.sort();
+    let mut content = String::new();
+    content.push_str(
+        "// 由build.rs根据cmd.txt生成,不要手动修改
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RouteTarget {
+    Gate,
+    Login,
+    World,
+    Game,
+}
+
+#[derive(Debug)]
+pub struct CmdRoute {
+    pub module: &'static str,
+    pub start: i32,
+    pub end: i32,
+    pub target: RouteTarget,
+}
+
+pub const ROUTES: &[CmdRoute] = &[
+",
+    );
+    for (start, end, module, target) in routes {
+        content.push_str(&format!(
+            "    CmdRoute {{
+        module: \"{}\",
+        start: {},
+        end: {},
+        target: RouteTarget::{},
+    }},
+",
+            module,
+            start,
+            end,
+            snake_to_camel(&target)
+        ));
+    }
+    content.push_str(
+        "];
+
+pub fn route(cmd: i32) -> Option<RouteTarget> {
+    ROUTES
+        .iter()
+        .find(|r| cmd >= r.start && cmd <= r.end)
+        .map(|r| r.target)
+}
+",
+    );
+    content
+}
 fn _camel_to_snake(camel: &str) -> String {
     let mut snake = String::new();
     for (i, c) in camel.chars().enumerate() {
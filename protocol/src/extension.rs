@@ -1,17 +1,57 @@
 
-impl crate::store_cmd::StoreInfoReq {
-    pub const CMD: i32 = 1101;
+impl crate::base_cmd::ErrorRsp {
+    pub const CMD: i32 = 601;
 
     pub const fn cmd(&self) -> i32 {
-        1101
+        601
     }
 }
                     
-impl crate::store_cmd::StoreInfoRsp {
-    pub const CMD: i32 = 1102;
+impl crate::base_cmd::KickNtf {
+    pub const CMD: i32 = 602;
 
     pub const fn cmd(&self) -> i32 {
-        1102
+        602
+    }
+}
+                    
+impl crate::base_cmd::HandshakeReq {
+    pub const CMD: i32 = 603;
+
+    pub const fn cmd(&self) -> i32 {
+        603
+    }
+}
+                    
+impl crate::base_cmd::HandshakeAck {
+    pub const CMD: i32 = 604;
+
+    pub const fn cmd(&self) -> i32 {
+        604
+    }
+}
+                    
+impl crate::base_cmd::ConfigVersionNtf {
+    pub const CMD: i32 = 605;
+
+    pub const fn cmd(&self) -> i32 {
+        605
+    }
+}
+                    
+impl crate::base_cmd::ResumeReq {
+    pub const CMD: i32 = 606;
+
+    pub const fn cmd(&self) -> i32 {
+        606
+    }
+}
+                    
+impl crate::base_cmd::ResumeRsp {
+    pub const CMD: i32 = 607;
+
+    pub const fn cmd(&self) -> i32 {
+        607
     }
 }
                     
@@ -63,11 +103,19 @@ impl crate::login_cmd::LogoutRsp {
     }
 }
                     
-impl crate::base_cmd::ErrorRsp {
-    pub const CMD: i32 = 601;
+impl crate::store_cmd::StoreInfoReq {
+    pub const CMD: i32 = 1101;
 
     pub const fn cmd(&self) -> i32 {
-        601
+        1101
+    }
+}
+                    
+impl crate::store_cmd::StoreInfoRsp {
+    pub const CMD: i32 = 1102;
+
+    pub const fn cmd(&self) -> i32 {
+        1102
     }
 }
                     
\ No newline at end of file
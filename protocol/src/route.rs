@@ -0,0 +1,44 @@
+// 由build.rs根据cmd.txt生成,不要手动修改
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RouteTarget {
+    Gate,
+    Login,
+    World,
+    Game,
+}
+
+#[derive(Debug)]
+pub struct CmdRoute {
+    pub module: &'static str,
+    pub start: i32,
+    pub end: i32,
+    pub target: RouteTarget,
+}
+
+pub const ROUTES: &[CmdRoute] = &[
+    CmdRoute {
+        module: "BaseCmd",
+        start: 601,
+        end: 1000,
+        target: RouteTarget::Gate,
+    },
+    CmdRoute {
+        module: "LoginCmd",
+        start: 1001,
+        end: 1100,
+        target: RouteTarget::Login,
+    },
+    CmdRoute {
+        module: "StoreCmd",
+        start: 1101,
+        end: 1200,
+        target: RouteTarget::Game,
+    },
+];
+
+pub fn route(cmd: i32) -> Option<RouteTarget> {
+    ROUTES
+        .iter()
+        .find(|r| cmd >= r.start && cmd <= r.end)
+        .map(|r| r.target)
+}
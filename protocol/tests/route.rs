@@ -0,0 +1,30 @@
+//! build.rs 根据 cmd.txt 生成的路由表
+use protocol::route::{ROUTES, RouteTarget, route};
+
+#[test]
+fn route_by_cmd_range() {
+    //LoginCmd=[1001,1100]->login
+    assert_eq!(route(1001), Some(RouteTarget::Login));
+    assert_eq!(route(1100), Some(RouteTarget::Login));
+    //StoreCmd=[1101,1200]->game
+    assert_eq!(route(1101), Some(RouteTarget::Game));
+    assert_eq!(route(1200), Some(RouteTarget::Game));
+    //BaseCmd=[601,1000]->gate,由网关自己处理
+    assert_eq!(route(601), Some(RouteTarget::Gate));
+    assert_eq!(route(1000), Some(RouteTarget::Gate));
+}
+
+#[test]
+fn route_unknown_cmd() {
+    for cmd in [0, 600, 1201, i32::MAX, -1] {
+        assert_eq!(route(cmd), None, "cmd:{}", cmd);
+    }
+}
+
+#[test]
+fn routes_sorted_without_overlap() {
+    for pair in ROUTES.windows(2) {
+        assert!(pair[0].end < pair[1].start, "{:?} {:?}", pair[0], pair[1]);
+    }
+    assert!(ROUTES.iter().all(|x| x.start <= x.end));
+}
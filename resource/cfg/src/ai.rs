@@ -17,12 +17,14 @@ pub enum EExecutor {
     SERVER = 1,
 }
 
-impl From<i32> for EExecutor {
-    fn from(value: i32) -> Self {
+impl TryFrom<i32> for EExecutor {
+    type Error = LubanError;
+
+    fn try_from(value: i32) -> Result<Self, LubanError> {
         match value { 
-            0 => EExecutor::CLIENT,
-            1 => EExecutor::SERVER,
-            _ => panic!("Invalid value for EExecutor:{}", value),
+            0 => Ok(EExecutor::CLIENT),
+            1 => Ok(EExecutor::SERVER),
+            _ => Err(LubanError::Bean(format!("Invalid value for EExecutor:{}", value))),
         }
     }
 }
@@ -33,12 +35,14 @@ pub enum EFinishMode {
     DELAYED = 1,
 }
 
-impl From<i32> for EFinishMode {
-    fn from(value: i32) -> Self {
+impl TryFrom<i32> for EFinishMode {
+    type Error = LubanError;
+
+    fn try_from(value: i32) -> Result<Self, LubanError> {
         match value { 
-            0 => EFinishMode::IMMEDIATE,
-            1 => EFinishMode::DELAYED,
-            _ => panic!("Invalid value for EFinishMode:{}", value),
+            0 => Ok(EFinishMode::IMMEDIATE),
+            1 => Ok(EFinishMode::DELAYED),
+            _ => Err(LubanError::Bean(format!("Invalid value for EFinishMode:{}", value))),
         }
     }
 }
@@ -51,14 +55,16 @@ pub enum EFlowAbortMode {
     BOTH = 3,
 }
 
-impl From<i32> for EFlowAbortMode {
-    fn from(value: i32) -> Self {
+impl TryFrom<i32> for EFlowAbortMode {
+    type Error = LubanError;
+
+    fn try_from(value: i32) -> Result<Self, LubanError> {
         match value { 
-            0 => EFlowAbortMode::NONE,
-            1 => EFlowAbortMode::LOWER_PRIORITY,
-            2 => EFlowAbortMode::SELF,
-            3 => EFlowAbortMode::BOTH,
-            _ => panic!("Invalid value for EFlowAbortMode:{}", value),
+            0 => Ok(EFlowAbortMode::NONE),
+            1 => Ok(EFlowAbortMode::LOWER_PRIORITY),
+            2 => Ok(EFlowAbortMode::SELF),
+            3 => Ok(EFlowAbortMode::BOTH),
+            _ => Err(LubanError::Bean(format!("Invalid value for EFlowAbortMode:{}", value))),
         }
     }
 }
@@ -77,20 +83,22 @@ pub enum EKeyType {
     OBJECT = 10,
 }
 
-impl From<i32> for EKeyType {
-    fn from(value: i32) -> Self {
+impl TryFrom<i32> for EKeyType {
+    type Error = LubanError;
+
+    fn try_from(value: i32) -> Result<Self, LubanError> {
         match value { 
-            1 => EKeyType::BOOL,
-            2 => EKeyType::INT,
-            3 => EKeyType::FLOAT,
-            4 => EKeyType::STRING,
-            5 => EKeyType::VECTOR,
-            6 => EKeyType::ROTATOR,
-            7 => EKeyType::NAME,
-            8 => EKeyType::CLASS1,
-            9 => EKeyType::ENUM1,
-            10 => EKeyType::OBJECT,
-            _ => panic!("Invalid value for EKeyType:{}", value),
+            1 => Ok(EKeyType::BOOL),
+            2 => Ok(EKeyType::INT),
+            3 => Ok(EKeyType::FLOAT),
+            4 => Ok(EKeyType::STRING),
+            5 => Ok(EKeyType::VECTOR),
+            6 => Ok(EKeyType::ROTATOR),
+            7 => Ok(EKeyType::NAME),
+            8 => Ok(EKeyType::CLASS1),
+            9 => Ok(EKeyType::ENUM1),
+            10 => Ok(EKeyType::OBJECT),
+            _ => Err(LubanError::Bean(format!("Invalid value for EKeyType:{}", value))),
         }
     }
 }
@@ -101,12 +109,14 @@ pub enum ENotifyObserverMode {
     ON_RESULT_CHANGE = 1,
 }
 
-impl From<i32> for ENotifyObserverMode {
-    fn from(value: i32) -> Self {
+impl TryFrom<i32> for ENotifyObserverMode {
+    type Error = LubanError;
+
+    fn try_from(value: i32) -> Result<Self, LubanError> {
         match value { 
-            0 => ENotifyObserverMode::ON_VALUE_CHANGE,
-            1 => ENotifyObserverMode::ON_RESULT_CHANGE,
-            _ => panic!("Invalid value for ENotifyObserverMode:{}", value),
+            0 => Ok(ENotifyObserverMode::ON_VALUE_CHANGE),
+            1 => Ok(ENotifyObserverMode::ON_RESULT_CHANGE),
+            _ => Err(LubanError::Bean(format!("Invalid value for ENotifyObserverMode:{}", value))),
         }
     }
 }
@@ -123,18 +133,20 @@ pub enum EOperator {
     NOT_CONTAINS = 7,
 }
 
-impl From<i32> for EOperator {
-    fn from(value: i32) -> Self {
+impl TryFrom<i32> for EOperator {
+    type Error = LubanError;
+
+    fn try_from(value: i32) -> Result<Self, LubanError> {
         match value { 
-            0 => EOperator::IS_EQUAL_TO,
-            1 => EOperator::IS_NOT_EQUAL_TO,
-            2 => EOperator::IS_LESS_THAN,
-            3 => EOperator::IS_LESS_THAN_OR_EQUAL_TO,
-            4 => EOperator::IS_GREAT_THAN,
-            5 => EOperator::IS_GREAT_THAN_OR_EQUAL_TO,
-            6 => EOperator::CONTAINS,
-            7 => EOperator::NOT_CONTAINS,
-            _ => panic!("Invalid value for EOperator:{}", value),
+            0 => Ok(EOperator::IS_EQUAL_TO),
+            1 => Ok(EOperator::IS_NOT_EQUAL_TO),
+            2 => Ok(EOperator::IS_LESS_THAN),
+            3 => Ok(EOperator::IS_LESS_THAN_OR_EQUAL_TO),
+            4 => Ok(EOperator::IS_GREAT_THAN),
+            5 => Ok(EOperator::IS_GREAT_THAN_OR_EQUAL_TO),
+            6 => Ok(EOperator::CONTAINS),
+            7 => Ok(EOperator::NOT_CONTAINS),
+            _ => Err(LubanError::Bean(format!("Invalid value for EOperator:{}", value))),
         }
     }
 }
@@ -150,10 +162,10 @@ pub struct BehaviorTree {
 
 impl BehaviorTree{
     pub fn new(mut buf: &mut ByteBuf) -> Result<BehaviorTree, LubanError> {
-        let id = buf.read_int();
-        let name = buf.read_string();
-        let desc = buf.read_string();
-        let blackboard_id = buf.read_string();
+        let id = buf.try_read_int()?;
+        let name = buf.try_read_string()?;
+        let desc = buf.try_read_string()?;
+        let blackboard_id = buf.try_read_string()?;
         let root = crate::ai::ComposeNode::new(&mut buf)?;
         
         Ok(BehaviorTree { id, name, desc, blackboard_id, root, })
@@ -172,10 +184,10 @@ pub struct Blackboard {
 
 impl Blackboard{
     pub fn new(mut buf: &mut ByteBuf) -> Result<Blackboard, LubanError> {
-        let name = buf.read_string();
-        let desc = buf.read_string();
-        let parent_name = buf.read_string();
-        let keys = {let n0 = std::cmp::min(buf.read_size(), buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(crate::ai::BlackboardKey::new(&mut buf)?); } _e0 };
+        let name = buf.try_read_string()?;
+        let desc = buf.try_read_string()?;
+        let parent_name = buf.try_read_string()?;
+        let keys = {let n0 = std::cmp::min(buf.try_read_size()?, buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(crate::ai::BlackboardKey::new(&mut buf)?); } _e0 };
         
         Ok(Blackboard { name, desc, parent_name, keys, })
     }
@@ -194,11 +206,11 @@ pub struct BlackboardKey {
 
 impl BlackboardKey{
     pub fn new(mut buf: &mut ByteBuf) -> Result<BlackboardKey, LubanError> {
-        let name = buf.read_string();
-        let desc = buf.read_string();
-        let is_static = buf.read_bool();
-        let key_type = buf.read_int().into();
-        let type_class_name = buf.read_string();
+        let name = buf.try_read_string()?;
+        let desc = buf.try_read_string()?;
+        let is_static = buf.try_read_bool()?;
+        let key_type = buf.try_read_enum()?;
+        let type_class_name = buf.try_read_string()?;
         
         Ok(BlackboardKey { name, desc, is_static, key_type, type_class_name, })
     }
@@ -212,7 +224,7 @@ pub struct KeyData {
 
 impl KeyData {
     pub fn new(mut buf: &mut ByteBuf) -> Result<std::sync::Arc<AbstractBase>, LubanError> {
-        let type_id = buf.read_int();
+        let type_id = buf.try_read_int()?;
         match type_id {
             crate::ai::FloatKeyData::__ID__ => Ok(std::sync::Arc::new(crate::ai::FloatKeyData::new(buf)?)),
             crate::ai::IntKeyData::__ID__ => Ok(std::sync::Arc::new(crate::ai::IntKeyData::new(buf)?)),
@@ -269,7 +281,7 @@ pub struct BlackboardKeyData {
 
 impl BlackboardKeyData{
     pub fn new(mut buf: &mut ByteBuf) -> Result<BlackboardKeyData, LubanError> {
-        let value = buf.read_string();
+        let value = buf.try_read_string()?;
         
         Ok(BlackboardKeyData { value, })
     }
@@ -285,7 +297,7 @@ pub struct FloatKeyData {
 
 impl FloatKeyData{
     pub fn new(mut buf: &mut ByteBuf) -> Result<FloatKeyData, LubanError> {
-        let value = buf.read_float();
+        let value = buf.try_read_float()?;
         
         Ok(FloatKeyData { value, })
     }
@@ -301,7 +313,7 @@ pub struct IntKeyData {
 
 impl IntKeyData{
     pub fn new(mut buf: &mut ByteBuf) -> Result<IntKeyData, LubanError> {
-        let value = buf.read_int();
+        let value = buf.try_read_int()?;
         
         Ok(IntKeyData { value, })
     }
@@ -317,7 +329,7 @@ pub struct StringKeyData {
 
 impl StringKeyData{
     pub fn new(mut buf: &mut ByteBuf) -> Result<StringKeyData, LubanError> {
-        let value = buf.read_string();
+        let value = buf.try_read_string()?;
         
         Ok(StringKeyData { value, })
     }
@@ -331,7 +343,7 @@ pub struct KeyQueryOperator {
 
 impl KeyQueryOperator {
     pub fn new(mut buf: &mut ByteBuf) -> Result<std::sync::Arc<AbstractBase>, LubanError> {
-        let type_id = buf.read_int();
+        let type_id = buf.try_read_int()?;
         match type_id {
             crate::ai::IsSet2::__ID__ => Ok(std::sync::Arc::new(crate::ai::IsSet2::new(buf)?)),
             crate::ai::IsNotSet::__ID__ => Ok(std::sync::Arc::new(crate::ai::IsNotSet::new(buf)?)),
@@ -381,7 +393,7 @@ pub struct BinaryOperator {
 
 impl BinaryOperator{
     pub fn new(mut buf: &mut ByteBuf) -> Result<BinaryOperator, LubanError> {
-        let oper = buf.read_int().into();
+        let oper = buf.try_read_enum()?;
         let data = crate::ai::KeyData::new(&mut buf)?;
         
         Ok(BinaryOperator { oper, data, })
@@ -426,7 +438,7 @@ pub struct Node {
 
 impl Node {
     pub fn new(mut buf: &mut ByteBuf) -> Result<std::sync::Arc<AbstractBase>, LubanError> {
-        let type_id = buf.read_int();
+        let type_id = buf.try_read_int()?;
         match type_id {
             crate::ai::UeSetDefaultFocus::__ID__ => Ok(std::sync::Arc::new(crate::ai::UeSetDefaultFocus::new(buf)?)),
             crate::ai::ExecuteTimeStatistic::__ID__ => Ok(std::sync::Arc::new(crate::ai::ExecuteTimeStatistic::new(buf)?)),
@@ -777,7 +789,7 @@ pub struct Decorator {
 
 impl Decorator {
     pub fn new(mut buf: &mut ByteBuf) -> Result<std::sync::Arc<AbstractBase>, LubanError> {
-        let type_id = buf.read_int();
+        let type_id = buf.try_read_int()?;
         match type_id {
             crate::ai::UeLoop::__ID__ => Ok(std::sync::Arc::new(crate::ai::UeLoop::new(buf)?)),
             crate::ai::UeCooldown::__ID__ => Ok(std::sync::Arc::new(crate::ai::UeCooldown::new(buf)?)),
@@ -930,13 +942,13 @@ pub struct DistanceLessThan {
 
 impl DistanceLessThan{
     pub fn new(mut buf: &mut ByteBuf) -> Result<DistanceLessThan, LubanError> {
-        let id = buf.read_int();
-        let node_name = buf.read_string();
-        let flow_abort_mode = buf.read_int().into();
-        let actor1_key = buf.read_string();
-        let actor2_key = buf.read_string();
-        let distance = buf.read_float();
-        let reverse_result = buf.read_bool();
+        let id = buf.try_read_int()?;
+        let node_name = buf.try_read_string()?;
+        let flow_abort_mode = buf.try_read_enum()?;
+        let actor1_key = buf.try_read_string()?;
+        let actor2_key = buf.try_read_string()?;
+        let distance = buf.try_read_float()?;
+        let reverse_result = buf.try_read_bool()?;
         
         Ok(DistanceLessThan { id, node_name, flow_abort_mode, actor1_key, actor2_key, distance, reverse_result, })
     }
@@ -957,12 +969,12 @@ pub struct IsAtLocation {
 
 impl IsAtLocation{
     pub fn new(mut buf: &mut ByteBuf) -> Result<IsAtLocation, LubanError> {
-        let id = buf.read_int();
-        let node_name = buf.read_string();
-        let flow_abort_mode = buf.read_int().into();
-        let acceptable_radius = buf.read_float();
-        let keyboard_key = buf.read_string();
-        let inverse_condition = buf.read_bool();
+        let id = buf.try_read_int()?;
+        let node_name = buf.try_read_string()?;
+        let flow_abort_mode = buf.try_read_enum()?;
+        let acceptable_radius = buf.try_read_float()?;
+        let keyboard_key = buf.try_read_string()?;
+        let inverse_condition = buf.try_read_bool()?;
         
         Ok(IsAtLocation { id, node_name, flow_abort_mode, acceptable_radius, keyboard_key, inverse_condition, })
     }
@@ -983,11 +995,11 @@ pub struct UeBlackboard {
 
 impl UeBlackboard{
     pub fn new(mut buf: &mut ByteBuf) -> Result<UeBlackboard, LubanError> {
-        let id = buf.read_int();
-        let node_name = buf.read_string();
-        let flow_abort_mode = buf.read_int().into();
-        let notify_observer = buf.read_int().into();
-        let blackboard_key = buf.read_string();
+        let id = buf.try_read_int()?;
+        let node_name = buf.try_read_string()?;
+        let flow_abort_mode = buf.try_read_enum()?;
+        let notify_observer = buf.try_read_enum()?;
+        let blackboard_key = buf.try_read_string()?;
         let key_query = crate::ai::KeyQueryOperator::new(&mut buf)?;
         
         Ok(UeBlackboard { id, node_name, flow_abort_mode, notify_observer, blackboard_key, key_query, })
@@ -1007,10 +1019,10 @@ pub struct UeCooldown {
 
 impl UeCooldown{
     pub fn new(mut buf: &mut ByteBuf) -> Result<UeCooldown, LubanError> {
-        let id = buf.read_int();
-        let node_name = buf.read_string();
-        let flow_abort_mode = buf.read_int().into();
-        let cooldown_time = buf.read_float();
+        let id = buf.try_read_int()?;
+        let node_name = buf.try_read_string()?;
+        let flow_abort_mode = buf.try_read_enum()?;
+        let cooldown_time = buf.try_read_float()?;
         
         Ok(UeCooldown { id, node_name, flow_abort_mode, cooldown_time, })
     }
@@ -1028,9 +1040,9 @@ pub struct UeForceSuccess {
 
 impl UeForceSuccess{
     pub fn new(mut buf: &mut ByteBuf) -> Result<UeForceSuccess, LubanError> {
-        let id = buf.read_int();
-        let node_name = buf.read_string();
-        let flow_abort_mode = buf.read_int().into();
+        let id = buf.try_read_int()?;
+        let node_name = buf.try_read_string()?;
+        let flow_abort_mode = buf.try_read_enum()?;
         
         Ok(UeForceSuccess { id, node_name, flow_abort_mode, })
     }
@@ -1051,12 +1063,12 @@ pub struct UeLoop {
 
 impl UeLoop{
     pub fn new(mut buf: &mut ByteBuf) -> Result<UeLoop, LubanError> {
-        let id = buf.read_int();
-        let node_name = buf.read_string();
-        let flow_abort_mode = buf.read_int().into();
-        let num_loops = buf.read_int();
-        let infinite_loop = buf.read_bool();
-        let infinite_loop_timeout_time = buf.read_float();
+        let id = buf.try_read_int()?;
+        let node_name = buf.try_read_string()?;
+        let flow_abort_mode = buf.try_read_enum()?;
+        let num_loops = buf.try_read_int()?;
+        let infinite_loop = buf.try_read_bool()?;
+        let infinite_loop_timeout_time = buf.try_read_float()?;
         
         Ok(UeLoop { id, node_name, flow_abort_mode, num_loops, infinite_loop, infinite_loop_timeout_time, })
     }
@@ -1075,10 +1087,10 @@ pub struct UeTimeLimit {
 
 impl UeTimeLimit{
     pub fn new(mut buf: &mut ByteBuf) -> Result<UeTimeLimit, LubanError> {
-        let id = buf.read_int();
-        let node_name = buf.read_string();
-        let flow_abort_mode = buf.read_int().into();
-        let limit_time = buf.read_float();
+        let id = buf.try_read_int()?;
+        let node_name = buf.try_read_string()?;
+        let flow_abort_mode = buf.try_read_enum()?;
+        let limit_time = buf.try_read_float()?;
         
         Ok(UeTimeLimit { id, node_name, flow_abort_mode, limit_time, })
     }
@@ -1097,7 +1109,7 @@ pub struct FlowNode {
 
 impl FlowNode {
     pub fn new(mut buf: &mut ByteBuf) -> Result<std::sync::Arc<AbstractBase>, LubanError> {
-        let type_id = buf.read_int();
+        let type_id = buf.try_read_int()?;
         match type_id {
             crate::ai::Sequence::__ID__ => Ok(std::sync::Arc::new(crate::ai::Sequence::new(buf)?)),
             crate::ai::Selector::__ID__ => Ok(std::sync::Arc::new(crate::ai::Selector::new(buf)?)),
@@ -1329,7 +1341,7 @@ pub struct ComposeNode {
 
 impl ComposeNode {
     pub fn new(mut buf: &mut ByteBuf) -> Result<std::sync::Arc<AbstractBase>, LubanError> {
-        let type_id = buf.read_int();
+        let type_id = buf.try_read_int()?;
         match type_id {
             crate::ai::Sequence::__ID__ => Ok(std::sync::Arc::new(crate::ai::Sequence::new(buf)?)),
             crate::ai::Selector::__ID__ => Ok(std::sync::Arc::new(crate::ai::Selector::new(buf)?)),
@@ -1422,11 +1434,11 @@ pub struct Selector {
 
 impl Selector{
     pub fn new(mut buf: &mut ByteBuf) -> Result<Selector, LubanError> {
-        let id = buf.read_int();
-        let node_name = buf.read_string();
-        let decorators = {let n0 = std::cmp::min(buf.read_size(), buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(crate::ai::Decorator::new(&mut buf)?); } _e0 };
-        let services = {let n0 = std::cmp::min(buf.read_size(), buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(crate::ai::Service::new(&mut buf)?); } _e0 };
-        let children = {let n0 = std::cmp::min(buf.read_size(), buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(crate::ai::FlowNode::new(&mut buf)?); } _e0 };
+        let id = buf.try_read_int()?;
+        let node_name = buf.try_read_string()?;
+        let decorators = {let n0 = std::cmp::min(buf.try_read_size()?, buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(crate::ai::Decorator::new(&mut buf)?); } _e0 };
+        let services = {let n0 = std::cmp::min(buf.try_read_size()?, buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(crate::ai::Service::new(&mut buf)?); } _e0 };
+        let children = {let n0 = std::cmp::min(buf.try_read_size()?, buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(crate::ai::FlowNode::new(&mut buf)?); } _e0 };
         
         Ok(Selector { id, node_name, decorators, services, children, })
     }
@@ -1446,11 +1458,11 @@ pub struct Sequence {
 
 impl Sequence{
     pub fn new(mut buf: &mut ByteBuf) -> Result<Sequence, LubanError> {
-        let id = buf.read_int();
-        let node_name = buf.read_string();
-        let decorators = {let n0 = std::cmp::min(buf.read_size(), buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(crate::ai::Decorator::new(&mut buf)?); } _e0 };
-        let services = {let n0 = std::cmp::min(buf.read_size(), buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(crate::ai::Service::new(&mut buf)?); } _e0 };
-        let children = {let n0 = std::cmp::min(buf.read_size(), buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(crate::ai::FlowNode::new(&mut buf)?); } _e0 };
+        let id = buf.try_read_int()?;
+        let node_name = buf.try_read_string()?;
+        let decorators = {let n0 = std::cmp::min(buf.try_read_size()?, buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(crate::ai::Decorator::new(&mut buf)?); } _e0 };
+        let services = {let n0 = std::cmp::min(buf.try_read_size()?, buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(crate::ai::Service::new(&mut buf)?); } _e0 };
+        let children = {let n0 = std::cmp::min(buf.try_read_size()?, buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(crate::ai::FlowNode::new(&mut buf)?); } _e0 };
         
         Ok(Sequence { id, node_name, decorators, services, children, })
     }
@@ -1472,11 +1484,11 @@ pub struct SimpleParallel {
 
 impl SimpleParallel{
     pub fn new(mut buf: &mut ByteBuf) -> Result<SimpleParallel, LubanError> {
-        let id = buf.read_int();
-        let node_name = buf.read_string();
-        let decorators = {let n0 = std::cmp::min(buf.read_size(), buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(crate::ai::Decorator::new(&mut buf)?); } _e0 };
-        let services = {let n0 = std::cmp::min(buf.read_size(), buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(crate::ai::Service::new(&mut buf)?); } _e0 };
-        let finish_mode = buf.read_int().into();
+        let id = buf.try_read_int()?;
+        let node_name = buf.try_read_string()?;
+        let decorators = {let n0 = std::cmp::min(buf.try_read_size()?, buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(crate::ai::Decorator::new(&mut buf)?); } _e0 };
+        let services = {let n0 = std::cmp::min(buf.try_read_size()?, buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(crate::ai::Service::new(&mut buf)?); } _e0 };
+        let finish_mode = buf.try_read_enum()?;
         let main_task = crate::ai::Task::new(&mut buf)?;
         let background_node = crate::ai::FlowNode::new(&mut buf)?;
         
@@ -1498,7 +1510,7 @@ pub struct Task {
 
 impl Task {
     pub fn new(mut buf: &mut ByteBuf) -> Result<std::sync::Arc<AbstractBase>, LubanError> {
-        let type_id = buf.read_int();
+        let type_id = buf.try_read_int()?;
         match type_id {
             crate::ai::UeWait::__ID__ => Ok(std::sync::Arc::new(crate::ai::UeWait::new(buf)?)),
             crate::ai::UeWaitBlackboardTime::__ID__ => Ok(std::sync::Arc::new(crate::ai::UeWaitBlackboardTime::new(buf)?)),
@@ -1695,13 +1707,13 @@ pub struct ChooseSkill {
 
 impl ChooseSkill{
     pub fn new(mut buf: &mut ByteBuf) -> Result<ChooseSkill, LubanError> {
-        let id = buf.read_int();
-        let node_name = buf.read_string();
-        let decorators = {let n0 = std::cmp::min(buf.read_size(), buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(crate::ai::Decorator::new(&mut buf)?); } _e0 };
-        let services = {let n0 = std::cmp::min(buf.read_size(), buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(crate::ai::Service::new(&mut buf)?); } _e0 };
-        let ignore_restart_self = buf.read_bool();
-        let target_actor_key = buf.read_string();
-        let result_skill_id_key = buf.read_string();
+        let id = buf.try_read_int()?;
+        let node_name = buf.try_read_string()?;
+        let decorators = {let n0 = std::cmp::min(buf.try_read_size()?, buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(crate::ai::Decorator::new(&mut buf)?); } _e0 };
+        let services = {let n0 = std::cmp::min(buf.try_read_size()?, buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(crate::ai::Service::new(&mut buf)?); } _e0 };
+        let ignore_restart_self = buf.try_read_bool()?;
+        let target_actor_key = buf.try_read_string()?;
+        let result_skill_id_key = buf.try_read_string()?;
         
         Ok(ChooseSkill { id, node_name, decorators, services, ignore_restart_self, target_actor_key, result_skill_id_key, })
     }
@@ -1722,12 +1734,12 @@ pub struct DebugPrint {
 
 impl DebugPrint{
     pub fn new(mut buf: &mut ByteBuf) -> Result<DebugPrint, LubanError> {
-        let id = buf.read_int();
-        let node_name = buf.read_string();
-        let decorators = {let n0 = std::cmp::min(buf.read_size(), buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(crate::ai::Decorator::new(&mut buf)?); } _e0 };
-        let services = {let n0 = std::cmp::min(buf.read_size(), buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(crate::ai::Service::new(&mut buf)?); } _e0 };
-        let ignore_restart_self = buf.read_bool();
-        let text = buf.read_string();
+        let id = buf.try_read_int()?;
+        let node_name = buf.try_read_string()?;
+        let decorators = {let n0 = std::cmp::min(buf.try_read_size()?, buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(crate::ai::Decorator::new(&mut buf)?); } _e0 };
+        let services = {let n0 = std::cmp::min(buf.try_read_size()?, buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(crate::ai::Service::new(&mut buf)?); } _e0 };
+        let ignore_restart_self = buf.try_read_bool()?;
+        let text = buf.try_read_string()?;
         
         Ok(DebugPrint { id, node_name, decorators, services, ignore_restart_self, text, })
     }
@@ -1748,12 +1760,12 @@ pub struct MoveToLocation {
 
 impl MoveToLocation{
     pub fn new(mut buf: &mut ByteBuf) -> Result<MoveToLocation, LubanError> {
-        let id = buf.read_int();
-        let node_name = buf.read_string();
-        let decorators = {let n0 = std::cmp::min(buf.read_size(), buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(crate::ai::Decorator::new(&mut buf)?); } _e0 };
-        let services = {let n0 = std::cmp::min(buf.read_size(), buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(crate::ai::Service::new(&mut buf)?); } _e0 };
-        let ignore_restart_self = buf.read_bool();
-        let acceptable_radius = buf.read_float();
+        let id = buf.try_read_int()?;
+        let node_name = buf.try_read_string()?;
+        let decorators = {let n0 = std::cmp::min(buf.try_read_size()?, buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(crate::ai::Decorator::new(&mut buf)?); } _e0 };
+        let services = {let n0 = std::cmp::min(buf.try_read_size()?, buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(crate::ai::Service::new(&mut buf)?); } _e0 };
+        let ignore_restart_self = buf.try_read_bool()?;
+        let acceptable_radius = buf.try_read_float()?;
         
         Ok(MoveToLocation { id, node_name, decorators, services, ignore_restart_self, acceptable_radius, })
     }
@@ -1775,13 +1787,13 @@ pub struct MoveToRandomLocation {
 
 impl MoveToRandomLocation{
     pub fn new(mut buf: &mut ByteBuf) -> Result<MoveToRandomLocation, LubanError> {
-        let id = buf.read_int();
-        let node_name = buf.read_string();
-        let decorators = {let n0 = std::cmp::min(buf.read_size(), buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(crate::ai::Decorator::new(&mut buf)?); } _e0 };
-        let services = {let n0 = std::cmp::min(buf.read_size(), buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(crate::ai::Service::new(&mut buf)?); } _e0 };
-        let ignore_restart_self = buf.read_bool();
-        let origin_position_key = buf.read_string();
-        let radius = buf.read_float();
+        let id = buf.try_read_int()?;
+        let node_name = buf.try_read_string()?;
+        let decorators = {let n0 = std::cmp::min(buf.try_read_size()?, buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(crate::ai::Decorator::new(&mut buf)?); } _e0 };
+        let services = {let n0 = std::cmp::min(buf.try_read_size()?, buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(crate::ai::Service::new(&mut buf)?); } _e0 };
+        let ignore_restart_self = buf.try_read_bool()?;
+        let origin_position_key = buf.try_read_string()?;
+        let radius = buf.try_read_float()?;
         
         Ok(MoveToRandomLocation { id, node_name, decorators, services, ignore_restart_self, origin_position_key, radius, })
     }
@@ -1803,13 +1815,13 @@ pub struct MoveToTarget {
 
 impl MoveToTarget{
     pub fn new(mut buf: &mut ByteBuf) -> Result<MoveToTarget, LubanError> {
-        let id = buf.read_int();
-        let node_name = buf.read_string();
-        let decorators = {let n0 = std::cmp::min(buf.read_size(), buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(crate::ai::Decorator::new(&mut buf)?); } _e0 };
-        let services = {let n0 = std::cmp::min(buf.read_size(), buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(crate::ai::Service::new(&mut buf)?); } _e0 };
-        let ignore_restart_self = buf.read_bool();
-        let target_actor_key = buf.read_string();
-        let acceptable_radius = buf.read_float();
+        let id = buf.try_read_int()?;
+        let node_name = buf.try_read_string()?;
+        let decorators = {let n0 = std::cmp::min(buf.try_read_size()?, buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(crate::ai::Decorator::new(&mut buf)?); } _e0 };
+        let services = {let n0 = std::cmp::min(buf.try_read_size()?, buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(crate::ai::Service::new(&mut buf)?); } _e0 };
+        let ignore_restart_self = buf.try_read_bool()?;
+        let target_actor_key = buf.try_read_string()?;
+        let acceptable_radius = buf.try_read_float()?;
         
         Ok(MoveToTarget { id, node_name, decorators, services, ignore_restart_self, target_actor_key, acceptable_radius, })
     }
@@ -1831,13 +1843,13 @@ pub struct UeWait {
 
 impl UeWait{
     pub fn new(mut buf: &mut ByteBuf) -> Result<UeWait, LubanError> {
-        let id = buf.read_int();
-        let node_name = buf.read_string();
-        let decorators = {let n0 = std::cmp::min(buf.read_size(), buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(crate::ai::Decorator::new(&mut buf)?); } _e0 };
-        let services = {let n0 = std::cmp::min(buf.read_size(), buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(crate::ai::Service::new(&mut buf)?); } _e0 };
-        let ignore_restart_self = buf.read_bool();
-        let wait_time = buf.read_float();
-        let random_deviation = buf.read_float();
+        let id = buf.try_read_int()?;
+        let node_name = buf.try_read_string()?;
+        let decorators = {let n0 = std::cmp::min(buf.try_read_size()?, buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(crate::ai::Decorator::new(&mut buf)?); } _e0 };
+        let services = {let n0 = std::cmp::min(buf.try_read_size()?, buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(crate::ai::Service::new(&mut buf)?); } _e0 };
+        let ignore_restart_self = buf.try_read_bool()?;
+        let wait_time = buf.try_read_float()?;
+        let random_deviation = buf.try_read_float()?;
         
         Ok(UeWait { id, node_name, decorators, services, ignore_restart_self, wait_time, random_deviation, })
     }
@@ -1858,12 +1870,12 @@ pub struct UeWaitBlackboardTime {
 
 impl UeWaitBlackboardTime{
     pub fn new(mut buf: &mut ByteBuf) -> Result<UeWaitBlackboardTime, LubanError> {
-        let id = buf.read_int();
-        let node_name = buf.read_string();
-        let decorators = {let n0 = std::cmp::min(buf.read_size(), buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(crate::ai::Decorator::new(&mut buf)?); } _e0 };
-        let services = {let n0 = std::cmp::min(buf.read_size(), buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(crate::ai::Service::new(&mut buf)?); } _e0 };
-        let ignore_restart_self = buf.read_bool();
-        let blackboard_key = buf.read_string();
+        let id = buf.try_read_int()?;
+        let node_name = buf.try_read_string()?;
+        let decorators = {let n0 = std::cmp::min(buf.try_read_size()?, buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(crate::ai::Decorator::new(&mut buf)?); } _e0 };
+        let services = {let n0 = std::cmp::min(buf.try_read_size()?, buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(crate::ai::Service::new(&mut buf)?); } _e0 };
+        let ignore_restart_self = buf.try_read_bool()?;
+        let blackboard_key = buf.try_read_string()?;
         
         Ok(UeWaitBlackboardTime { id, node_name, decorators, services, ignore_restart_self, blackboard_key, })
     }
@@ -1880,7 +1892,7 @@ pub struct Service {
 
 impl Service {
     pub fn new(mut buf: &mut ByteBuf) -> Result<std::sync::Arc<AbstractBase>, LubanError> {
-        let type_id = buf.read_int();
+        let type_id = buf.try_read_int()?;
         match type_id {
             crate::ai::UeSetDefaultFocus::__ID__ => Ok(std::sync::Arc::new(crate::ai::UeSetDefaultFocus::new(buf)?)),
             crate::ai::ExecuteTimeStatistic::__ID__ => Ok(std::sync::Arc::new(crate::ai::ExecuteTimeStatistic::new(buf)?)),
@@ -1993,9 +2005,9 @@ pub struct ChooseTarget {
 
 impl ChooseTarget{
     pub fn new(mut buf: &mut ByteBuf) -> Result<ChooseTarget, LubanError> {
-        let id = buf.read_int();
-        let node_name = buf.read_string();
-        let result_target_key = buf.read_string();
+        let id = buf.try_read_int()?;
+        let node_name = buf.try_read_string()?;
+        let result_target_key = buf.try_read_string()?;
         
         Ok(ChooseTarget { id, node_name, result_target_key, })
     }
@@ -2012,8 +2024,8 @@ pub struct ExecuteTimeStatistic {
 
 impl ExecuteTimeStatistic{
     pub fn new(mut buf: &mut ByteBuf) -> Result<ExecuteTimeStatistic, LubanError> {
-        let id = buf.read_int();
-        let node_name = buf.read_string();
+        let id = buf.try_read_int()?;
+        let node_name = buf.try_read_string()?;
         
         Ok(ExecuteTimeStatistic { id, node_name, })
     }
@@ -2031,9 +2043,9 @@ pub struct GetOwnerPlayer {
 
 impl GetOwnerPlayer{
     pub fn new(mut buf: &mut ByteBuf) -> Result<GetOwnerPlayer, LubanError> {
-        let id = buf.read_int();
-        let node_name = buf.read_string();
-        let player_actor_key = buf.read_string();
+        let id = buf.try_read_int()?;
+        let node_name = buf.try_read_string()?;
+        let player_actor_key = buf.try_read_string()?;
         
         Ok(GetOwnerPlayer { id, node_name, player_actor_key, })
     }
@@ -2051,9 +2063,9 @@ pub struct KeepFaceTarget {
 
 impl KeepFaceTarget{
     pub fn new(mut buf: &mut ByteBuf) -> Result<KeepFaceTarget, LubanError> {
-        let id = buf.read_int();
-        let node_name = buf.read_string();
-        let target_actor_key = buf.read_string();
+        let id = buf.try_read_int()?;
+        let node_name = buf.try_read_string()?;
+        let target_actor_key = buf.try_read_string()?;
         
         Ok(KeepFaceTarget { id, node_name, target_actor_key, })
     }
@@ -2071,9 +2083,9 @@ pub struct UeSetDefaultFocus {
 
 impl UeSetDefaultFocus{
     pub fn new(mut buf: &mut ByteBuf) -> Result<UeSetDefaultFocus, LubanError> {
-        let id = buf.read_int();
-        let node_name = buf.read_string();
-        let keyboard_key = buf.read_string();
+        let id = buf.try_read_int()?;
+        let node_name = buf.try_read_string()?;
+        let keyboard_key = buf.try_read_string()?;
         
         Ok(UeSetDefaultFocus { id, node_name, keyboard_key, })
     }
@@ -2099,17 +2111,17 @@ pub struct UpdateDailyBehaviorProps {
 
 impl UpdateDailyBehaviorProps{
     pub fn new(mut buf: &mut ByteBuf) -> Result<UpdateDailyBehaviorProps, LubanError> {
-        let id = buf.read_int();
-        let node_name = buf.read_string();
-        let satiety_key = buf.read_string();
-        let energy_key = buf.read_string();
-        let mood_key = buf.read_string();
-        let satiety_lower_threshold_key = buf.read_string();
-        let satiety_upper_threshold_key = buf.read_string();
-        let energy_lower_threshold_key = buf.read_string();
-        let energy_upper_threshold_key = buf.read_string();
-        let mood_lower_threshold_key = buf.read_string();
-        let mood_upper_threshold_key = buf.read_string();
+        let id = buf.try_read_int()?;
+        let node_name = buf.try_read_string()?;
+        let satiety_key = buf.try_read_string()?;
+        let energy_key = buf.try_read_string()?;
+        let mood_key = buf.try_read_string()?;
+        let satiety_lower_threshold_key = buf.try_read_string()?;
+        let satiety_upper_threshold_key = buf.try_read_string()?;
+        let energy_lower_threshold_key = buf.try_read_string()?;
+        let energy_upper_threshold_key = buf.try_read_string()?;
+        let mood_lower_threshold_key = buf.try_read_string()?;
+        let mood_upper_threshold_key = buf.try_read_string()?;
         
         Ok(UpdateDailyBehaviorProps { id, node_name, satiety_key, energy_key, mood_key, satiety_lower_threshold_key, satiety_upper_threshold_key, energy_lower_threshold_key, energy_upper_threshold_key, mood_lower_threshold_key, mood_upper_threshold_key, })
     }
@@ -2129,7 +2141,7 @@ impl TbBlackboard {
         let mut data_map: std::collections::HashMap<String, std::sync::Arc<crate::ai::Blackboard>> = Default::default();
         let mut data_list: Vec<std::sync::Arc<crate::ai::Blackboard>> = vec![];
 
-        for x in (0..buf.read_size()).rev() {
+        for x in (0..buf.try_read_size()?).rev() {
             let row = std::sync::Arc::new(crate::ai::Blackboard::new(&mut buf)?);
             data_list.push(row.clone());
             data_map.insert(row.name.clone(), row.clone());
@@ -2163,7 +2175,7 @@ impl TbBehaviorTree {
         let mut data_map: std::collections::HashMap<i32, std::sync::Arc<crate::ai::BehaviorTree>> = Default::default();
         let mut data_list: Vec<std::sync::Arc<crate::ai::BehaviorTree>> = vec![];
 
-        for x in (0..buf.read_size()).rev() {
+        for x in (0..buf.try_read_size()?).rev() {
             let row = std::sync::Arc::new(crate::ai::BehaviorTree::new(&mut buf)?);
             data_list.push(row.clone());
             data_map.insert(row.id.clone(), row.clone());
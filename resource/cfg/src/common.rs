@@ -17,12 +17,14 @@ pub enum EBoolOperator {
     OR = 1,
 }
 
-impl From<i32> for EBoolOperator {
-    fn from(value: i32) -> Self {
+impl TryFrom<i32> for EBoolOperator {
+    type Error = LubanError;
+
+    fn try_from(value: i32) -> Result<Self, LubanError> {
         match value { 
-            0 => EBoolOperator::AND,
-            1 => EBoolOperator::OR,
-            _ => panic!("Invalid value for EBoolOperator:{}", value),
+            0 => Ok(EBoolOperator::AND),
+            1 => Ok(EBoolOperator::OR),
+            _ => Err(LubanError::Bean(format!("Invalid value for EBoolOperator:{}", value))),
         }
     }
 }
@@ -35,8 +37,8 @@ pub struct DateTimeRange {
 
 impl DateTimeRange{
     pub fn new(mut buf: &mut ByteBuf) -> Result<DateTimeRange, LubanError> {
-        let mut start_time = if buf.read_bool() { Some(buf.read_ulong()) } else { None };
-        let mut end_time = if buf.read_bool() { Some(buf.read_ulong()) } else { None };
+        let mut start_time = if buf.try_read_bool()? { Some(buf.try_read_ulong()?) } else { None };
+        let mut end_time = if buf.try_read_bool()? { Some(buf.try_read_ulong()?) } else { None };
         
         Ok(DateTimeRange { start_time, end_time, })
     }
@@ -52,8 +54,8 @@ pub struct FloatRange {
 
 impl FloatRange{
     pub fn new(mut buf: &mut ByteBuf) -> Result<FloatRange, LubanError> {
-        let min = buf.read_float();
-        let max = buf.read_float();
+        let min = buf.try_read_float()?;
+        let max = buf.try_read_float()?;
         
         Ok(FloatRange { min, max, })
     }
@@ -75,13 +77,13 @@ pub struct GlobalConfig {
 
 impl GlobalConfig{
     pub fn new(mut buf: &mut ByteBuf) -> Result<GlobalConfig, LubanError> {
-        let x1 = buf.read_int();
-        let x2 = buf.read_int();
-        let x3 = buf.read_int();
-        let x4 = buf.read_int();
-        let x5 = buf.read_int();
-        let x6 = buf.read_int();
-        let x7 = {let n0 = std::cmp::min(buf.read_size(), buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(buf.read_int()); } _e0 };
+        let x1 = buf.try_read_int()?;
+        let x2 = buf.try_read_int()?;
+        let x3 = buf.try_read_int()?;
+        let x4 = buf.try_read_int()?;
+        let x5 = buf.try_read_int()?;
+        let x6 = buf.try_read_int()?;
+        let x7 = {let n0 = std::cmp::min(buf.try_read_size()?, buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(buf.try_read_int()?); } _e0 };
         
         Ok(GlobalConfig { x1, x2, x3, x4, x5, x6, x7, })
     }
@@ -97,8 +99,8 @@ pub struct IntRange {
 
 impl IntRange{
     pub fn new(mut buf: &mut ByteBuf) -> Result<IntRange, LubanError> {
-        let min = buf.read_int();
-        let max = buf.read_int();
+        let min = buf.try_read_int()?;
+        let max = buf.try_read_int()?;
         
         Ok(IntRange { min, max, })
     }
@@ -132,9 +134,9 @@ pub struct TimeOfDay {
 
 impl TimeOfDay{
     pub fn new(mut buf: &mut ByteBuf) -> Result<TimeOfDay, LubanError> {
-        let hour = buf.read_int();
-        let minute = buf.read_int();
-        let second = buf.read_int();
+        let hour = buf.try_read_int()?;
+        let minute = buf.try_read_int()?;
+        let second = buf.try_read_int()?;
         
         Ok(TimeOfDay { hour, minute, second, })
     }
@@ -150,7 +152,7 @@ pub struct TbGlobalConfig {
 
 impl TbGlobalConfig {
     pub fn new(mut buf: ByteBuf) -> Result<std::sync::Arc<TbGlobalConfig>, LubanError> {
-        let n = buf.read_size();
+        let n = buf.try_read_size()?;
         if n != 1 { return Err(LubanError::Table(format!("table mode=one, but size != 1"))); }
         let data = crate::common::GlobalConfig::new(&mut buf)?;
         Ok(std::sync::Arc::new(TbGlobalConfig { data }))
@@ -35,20 +35,22 @@ pub enum EClothersStarQualityType {
     TEN = 10,
 }
 
-impl From<i32> for EClothersStarQualityType {
-    fn from(value: i32) -> Self {
+impl TryFrom<i32> for EClothersStarQualityType {
+    type Error = LubanError;
+
+    fn try_from(value: i32) -> Result<Self, LubanError> {
         match value { 
-            1 => EClothersStarQualityType::ONE,
-            2 => EClothersStarQualityType::TWO,
-            3 => EClothersStarQualityType::THREE,
-            4 => EClothersStarQualityType::FOUR,
-            5 => EClothersStarQualityType::FIVE,
-            6 => EClothersStarQualityType::SIX,
-            7 => EClothersStarQualityType::SEVEN,
-            8 => EClothersStarQualityType::EIGHT,
-            9 => EClothersStarQualityType::NINE,
-            10 => EClothersStarQualityType::TEN,
-            _ => panic!("Invalid value for EClothersStarQualityType:{}", value),
+            1 => Ok(EClothersStarQualityType::ONE),
+            2 => Ok(EClothersStarQualityType::TWO),
+            3 => Ok(EClothersStarQualityType::THREE),
+            4 => Ok(EClothersStarQualityType::FOUR),
+            5 => Ok(EClothersStarQualityType::FIVE),
+            6 => Ok(EClothersStarQualityType::SIX),
+            7 => Ok(EClothersStarQualityType::SEVEN),
+            8 => Ok(EClothersStarQualityType::EIGHT),
+            9 => Ok(EClothersStarQualityType::NINE),
+            10 => Ok(EClothersStarQualityType::TEN),
+            _ => Err(LubanError::Bean(format!("Invalid value for EClothersStarQualityType:{}", value))),
         }
     }
 }
@@ -61,12 +63,14 @@ pub enum EClothersTag {
     WU_ZHE = 2,
 }
 
-impl From<i32> for EClothersTag {
-    fn from(value: i32) -> Self {
+impl TryFrom<i32> for EClothersTag {
+    type Error = LubanError;
+
+    fn try_from(value: i32) -> Result<Self, LubanError> {
         match value { 
-            1 => EClothersTag::FANG_SHAI,
-            2 => EClothersTag::WU_ZHE,
-            _ => panic!("Invalid value for EClothersTag:{}", value),
+            1 => Ok(EClothersTag::FANG_SHAI),
+            2 => Ok(EClothersTag::WU_ZHE),
+            _ => Err(LubanError::Bean(format!("Invalid value for EClothersTag:{}", value))),
         }
     }
 }
@@ -91,18 +95,20 @@ pub enum EClothesHidePartType {
     LEG_LOWER = 7,
 }
 
-impl From<i32> for EClothesHidePartType {
-    fn from(value: i32) -> Self {
+impl TryFrom<i32> for EClothesHidePartType {
+    type Error = LubanError;
+
+    fn try_from(value: i32) -> Result<Self, LubanError> {
         match value { 
-            0 => EClothesHidePartType::CHEST,
-            1 => EClothesHidePartType::HEAD,
-            2 => EClothesHidePartType::SPINE_UPPER,
-            3 => EClothesHidePartType::SPINE_LOWER,
-            4 => EClothesHidePartType::HIP,
-            5 => EClothesHidePartType::LEG_UPPER,
-            6 => EClothesHidePartType::LEG_MIDDLE,
-            7 => EClothesHidePartType::LEG_LOWER,
-            _ => panic!("Invalid value for EClothesHidePartType:{}", value),
+            0 => Ok(EClothesHidePartType::CHEST),
+            1 => Ok(EClothesHidePartType::HEAD),
+            2 => Ok(EClothesHidePartType::SPINE_UPPER),
+            3 => Ok(EClothesHidePartType::SPINE_LOWER),
+            4 => Ok(EClothesHidePartType::HIP),
+            5 => Ok(EClothesHidePartType::LEG_UPPER),
+            6 => Ok(EClothesHidePartType::LEG_MIDDLE),
+            7 => Ok(EClothesHidePartType::LEG_LOWER),
+            _ => Err(LubanError::Bean(format!("Invalid value for EClothesHidePartType:{}", value))),
         }
     }
 }
@@ -131,20 +137,22 @@ pub enum EClothesPropertyType {
     BAO_NUAN = 10,
 }
 
-impl From<i32> for EClothesPropertyType {
-    fn from(value: i32) -> Self {
+impl TryFrom<i32> for EClothesPropertyType {
+    type Error = LubanError;
+
+    fn try_from(value: i32) -> Result<Self, LubanError> {
         match value { 
-            1 => EClothesPropertyType::JIAN_YUE,
-            2 => EClothesPropertyType::HUA_LI,
-            3 => EClothesPropertyType::KE_AI,
-            4 => EClothesPropertyType::CHENG_SHU,
-            5 => EClothesPropertyType::HUO_PO,
-            6 => EClothesPropertyType::YOU_YA,
-            7 => EClothesPropertyType::QING_CHUN,
-            8 => EClothesPropertyType::XING_GAN,
-            9 => EClothesPropertyType::QING_LIANG,
-            10 => EClothesPropertyType::BAO_NUAN,
-            _ => panic!("Invalid value for EClothesPropertyType:{}", value),
+            1 => Ok(EClothesPropertyType::JIAN_YUE),
+            2 => Ok(EClothesPropertyType::HUA_LI),
+            3 => Ok(EClothesPropertyType::KE_AI),
+            4 => Ok(EClothesPropertyType::CHENG_SHU),
+            5 => Ok(EClothesPropertyType::HUO_PO),
+            6 => Ok(EClothesPropertyType::YOU_YA),
+            7 => Ok(EClothesPropertyType::QING_CHUN),
+            8 => Ok(EClothesPropertyType::XING_GAN),
+            9 => Ok(EClothesPropertyType::QING_LIANG),
+            10 => Ok(EClothesPropertyType::BAO_NUAN),
+            _ => Err(LubanError::Bean(format!("Invalid value for EClothesPropertyType:{}", value))),
         }
     }
 }
@@ -163,15 +171,17 @@ pub enum ECurrencyType {
     POWER_POINT = 5,
 }
 
-impl From<i32> for ECurrencyType {
-    fn from(value: i32) -> Self {
+impl TryFrom<i32> for ECurrencyType {
+    type Error = LubanError;
+
+    fn try_from(value: i32) -> Result<Self, LubanError> {
         match value { 
-            1 => ECurrencyType::DIAMOND,
-            2 => ECurrencyType::GOLD,
-            3 => ECurrencyType::SILVER,
-            4 => ECurrencyType::EXP,
-            5 => ECurrencyType::POWER_POINT,
-            _ => panic!("Invalid value for ECurrencyType:{}", value),
+            1 => Ok(ECurrencyType::DIAMOND),
+            2 => Ok(ECurrencyType::GOLD),
+            3 => Ok(ECurrencyType::SILVER),
+            4 => Ok(ECurrencyType::EXP),
+            5 => Ok(ECurrencyType::POWER_POINT),
+            _ => Err(LubanError::Bean(format!("Invalid value for ECurrencyType:{}", value))),
         }
     }
 }
@@ -191,15 +201,17 @@ pub enum EItemQuality {
     GOLDEN = 4,
 }
 
-impl From<i32> for EItemQuality {
-    fn from(value: i32) -> Self {
+impl TryFrom<i32> for EItemQuality {
+    type Error = LubanError;
+
+    fn try_from(value: i32) -> Result<Self, LubanError> {
         match value { 
-            0 => EItemQuality::WHITE,
-            1 => EItemQuality::GREEN,
-            2 => EItemQuality::BLUE,
-            3 => EItemQuality::PURPLE,
-            4 => EItemQuality::GOLDEN,
-            _ => panic!("Invalid value for EItemQuality:{}", value),
+            0 => Ok(EItemQuality::WHITE),
+            1 => Ok(EItemQuality::GREEN),
+            2 => Ok(EItemQuality::BLUE),
+            3 => Ok(EItemQuality::PURPLE),
+            4 => Ok(EItemQuality::GOLDEN),
+            _ => Err(LubanError::Bean(format!("Invalid value for EItemQuality:{}", value))),
         }
     }
 }
@@ -230,21 +242,23 @@ pub enum EMajorType {
     MATERIAL = 11,
 }
 
-impl From<i32> for EMajorType {
-    fn from(value: i32) -> Self {
+impl TryFrom<i32> for EMajorType {
+    type Error = LubanError;
+
+    fn try_from(value: i32) -> Result<Self, LubanError> {
         match value { 
-            1 => EMajorType::CURRENCY,
-            2 => EMajorType::CLOTH,
-            3 => EMajorType::QUEST,
-            4 => EMajorType::CONSUMABLES,
-            5 => EMajorType::TREASURE_BOX,
-            6 => EMajorType::ACHIEVEMENT_AND_TITLE,
-            7 => EMajorType::HEAD_FRAME,
-            8 => EMajorType::VOICE,
-            9 => EMajorType::ACTION,
-            10 => EMajorType::EXPANSION,
-            11 => EMajorType::MATERIAL,
-            _ => panic!("Invalid value for EMajorType:{}", value),
+            1 => Ok(EMajorType::CURRENCY),
+            2 => Ok(EMajorType::CLOTH),
+            3 => Ok(EMajorType::QUEST),
+            4 => Ok(EMajorType::CONSUMABLES),
+            5 => Ok(EMajorType::TREASURE_BOX),
+            6 => Ok(EMajorType::ACHIEVEMENT_AND_TITLE),
+            7 => Ok(EMajorType::HEAD_FRAME),
+            8 => Ok(EMajorType::VOICE),
+            9 => Ok(EMajorType::ACTION),
+            10 => Ok(EMajorType::EXPANSION),
+            11 => Ok(EMajorType::MATERIAL),
+            _ => Err(LubanError::Bean(format!("Invalid value for EMajorType:{}", value))),
         }
     }
 }
@@ -351,59 +365,61 @@ pub enum EMinorType {
     DESIGN_DRAWING = 1102,
 }
 
-impl From<i32> for EMinorType {
-    fn from(value: i32) -> Self {
+impl TryFrom<i32> for EMinorType {
+    type Error = LubanError;
+
+    fn try_from(value: i32) -> Result<Self, LubanError> {
         match value { 
-            101 => EMinorType::DIAMOND,
-            102 => EMinorType::GOLD,
-            103 => EMinorType::SILVER,
-            104 => EMinorType::EXP,
-            105 => EMinorType::POWER_POINT,
-            210 => EMinorType::HAIR_STYLE,
-            220 => EMinorType::COAT,
-            230 => EMinorType::UPPER_JACKET,
-            241 => EMinorType::TROUSERS,
-            242 => EMinorType::SKIRT,
-            250 => EMinorType::SOCKS,
-            260 => EMinorType::SHOES,
-            271 => EMinorType::HAIR_ACCESSORY,
-            272 => EMinorType::HAT,
-            273 => EMinorType::EARRING,
-            274 => EMinorType::NECKLACE,
-            275 => EMinorType::BRACELET,
-            276 => EMinorType::HAIR_CLASP,
-            277 => EMinorType::GLOVE,
-            278 => EMinorType::HANDHELD_OBJECT,
-            279 => EMinorType::SPECIAL,
-            281 => EMinorType::BASE_COSMETIC,
-            282 => EMinorType::EYEBROW_COSMETIC,
-            283 => EMinorType::EYELASH,
-            284 => EMinorType::COSMETIC_CONTACT_LENSES,
-            285 => EMinorType::LIP_COSMETIC,
-            286 => EMinorType::SKIN_COLOR,
-            290 => EMinorType::ONE_PIECE_DRESS,
-            291 => EMinorType::SWITCH_CLOTHES_SCENE,
-            301 => EMinorType::QUEST,
-            401 => EMinorType::CAST,
-            421 => EMinorType::SWORD,
-            422 => EMinorType::BOW_ARROW,
-            423 => EMinorType::WANDS,
-            424 => EMinorType::SPECIAL_TOOL,
-            403 => EMinorType::FOOD,
-            501 => EMinorType::TREASURE_BOX,
-            502 => EMinorType::KEY,
-            503 => EMinorType::MULTI_CHOOSE_TREASURE_BOX,
-            601 => EMinorType::ACHIEVEMENT,
-            602 => EMinorType::TITLE,
-            701 => EMinorType::AVATAR_FRAME,
-            801 => EMinorType::VOICE,
-            901 => EMinorType::IDLE_POSE,
-            902 => EMinorType::PHOTO_POSE,
-            1001 => EMinorType::BAG,
-            1002 => EMinorType::FRIEND_CAPACITY,
-            1101 => EMinorType::CONSTRUCTION_MATERIAL,
-            1102 => EMinorType::DESIGN_DRAWING,
-            _ => panic!("Invalid value for EMinorType:{}", value),
+            101 => Ok(EMinorType::DIAMOND),
+            102 => Ok(EMinorType::GOLD),
+            103 => Ok(EMinorType::SILVER),
+            104 => Ok(EMinorType::EXP),
+            105 => Ok(EMinorType::POWER_POINT),
+            210 => Ok(EMinorType::HAIR_STYLE),
+            220 => Ok(EMinorType::COAT),
+            230 => Ok(EMinorType::UPPER_JACKET),
+            241 => Ok(EMinorType::TROUSERS),
+            242 => Ok(EMinorType::SKIRT),
+            250 => Ok(EMinorType::SOCKS),
+            260 => Ok(EMinorType::SHOES),
+            271 => Ok(EMinorType::HAIR_ACCESSORY),
+            272 => Ok(EMinorType::HAT),
+            273 => Ok(EMinorType::EARRING),
+            274 => Ok(EMinorType::NECKLACE),
+            275 => Ok(EMinorType::BRACELET),
+            276 => Ok(EMinorType::HAIR_CLASP),
+            277 => Ok(EMinorType::GLOVE),
+            278 => Ok(EMinorType::HANDHELD_OBJECT),
+            279 => Ok(EMinorType::SPECIAL),
+            281 => Ok(EMinorType::BASE_COSMETIC),
+            282 => Ok(EMinorType::EYEBROW_COSMETIC),
+            283 => Ok(EMinorType::EYELASH),
+            284 => Ok(EMinorType::COSMETIC_CONTACT_LENSES),
+            285 => Ok(EMinorType::LIP_COSMETIC),
+            286 => Ok(EMinorType::SKIN_COLOR),
+            290 => Ok(EMinorType::ONE_PIECE_DRESS),
+            291 => Ok(EMinorType::SWITCH_CLOTHES_SCENE),
+            301 => Ok(EMinorType::QUEST),
+            401 => Ok(EMinorType::CAST),
+            421 => Ok(EMinorType::SWORD),
+            422 => Ok(EMinorType::BOW_ARROW),
+            423 => Ok(EMinorType::WANDS),
+            424 => Ok(EMinorType::SPECIAL_TOOL),
+            403 => Ok(EMinorType::FOOD),
+            501 => Ok(EMinorType::TREASURE_BOX),
+            502 => Ok(EMinorType::KEY),
+            503 => Ok(EMinorType::MULTI_CHOOSE_TREASURE_BOX),
+            601 => Ok(EMinorType::ACHIEVEMENT),
+            602 => Ok(EMinorType::TITLE),
+            701 => Ok(EMinorType::AVATAR_FRAME),
+            801 => Ok(EMinorType::VOICE),
+            901 => Ok(EMinorType::IDLE_POSE),
+            902 => Ok(EMinorType::PHOTO_POSE),
+            1001 => Ok(EMinorType::BAG),
+            1002 => Ok(EMinorType::FRIEND_CAPACITY),
+            1101 => Ok(EMinorType::CONSTRUCTION_MATERIAL),
+            1102 => Ok(EMinorType::DESIGN_DRAWING),
+            _ => Err(LubanError::Bean(format!("Invalid value for EMinorType:{}", value))),
         }
     }
 }
@@ -416,12 +432,14 @@ pub enum EUseType {
     AUTO = 1,
 }
 
-impl From<i32> for EUseType {
-    fn from(value: i32) -> Self {
+impl TryFrom<i32> for EUseType {
+    type Error = LubanError;
+
+    fn try_from(value: i32) -> Result<Self, LubanError> {
         match value { 
-            0 => EUseType::MANUAL,
-            1 => EUseType::AUTO,
-            _ => panic!("Invalid value for EUseType:{}", value),
+            0 => Ok(EUseType::MANUAL),
+            1 => Ok(EUseType::AUTO),
+            _ => Err(LubanError::Bean(format!("Invalid value for EUseType:{}", value))),
         }
     }
 }
@@ -444,17 +462,17 @@ pub struct Item {
 
 impl Item{
     pub fn new(mut buf: &mut ByteBuf) -> Result<Item, LubanError> {
-        let id = buf.read_int();
-        let name = buf.read_string();
-        let major_type = buf.read_int().into();
-        let minor_type = buf.read_int().into();
-        let max_pile_num = buf.read_int();
-        let quality = buf.read_int().into();
-        let icon = buf.read_string();
-        let icon_backgroud = buf.read_string();
-        let icon_mask = buf.read_string();
-        let desc = buf.read_string();
-        let show_order = buf.read_int();
+        let id = buf.try_read_int()?;
+        let name = buf.try_read_string()?;
+        let major_type = buf.try_read_enum()?;
+        let minor_type = buf.try_read_enum()?;
+        let max_pile_num = buf.try_read_int()?;
+        let quality = buf.try_read_enum()?;
+        let icon = buf.try_read_string()?;
+        let icon_backgroud = buf.try_read_string()?;
+        let icon_mask = buf.try_read_string()?;
+        let desc = buf.try_read_string()?;
+        let show_order = buf.try_read_int()?;
         
         Ok(Item { id, name, major_type, minor_type, max_pile_num, quality, icon, icon_backgroud, icon_mask, desc, show_order, })
     }
@@ -475,7 +493,7 @@ impl TbItem {
         let mut data_map: std::collections::HashMap<i32, std::sync::Arc<crate::item::Item>> = Default::default();
         let mut data_list: Vec<std::sync::Arc<crate::item::Item>> = vec![];
 
-        for x in (0..buf.read_size()).rev() {
+        for x in (0..buf.try_read_size()?).rev() {
             let row = std::sync::Arc::new(crate::item::Item::new(&mut buf)?);
             data_list.push(row.clone());
             data_map.insert(row.id.clone(), row.clone());
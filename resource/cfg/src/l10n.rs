@@ -19,8 +19,8 @@ pub struct L10NDemo {
 
 impl L10NDemo{
     pub fn new(mut buf: &mut ByteBuf) -> Result<L10NDemo, LubanError> {
-        let id = buf.read_int();
-        let text = buf.read_string();
+        let id = buf.try_read_int()?;
+        let text = buf.try_read_string()?;
         
         Ok(L10NDemo { id, text, })
     }
@@ -36,8 +36,8 @@ pub struct PatchDemo {
 
 impl PatchDemo{
     pub fn new(mut buf: &mut ByteBuf) -> Result<PatchDemo, LubanError> {
-        let id = buf.read_int();
-        let value = buf.read_int();
+        let id = buf.try_read_int()?;
+        let value = buf.try_read_int()?;
         
         Ok(PatchDemo { id, value, })
     }
@@ -57,7 +57,7 @@ impl TbL10NDemo {
         let mut data_map: std::collections::HashMap<i32, std::sync::Arc<crate::l10n::L10NDemo>> = Default::default();
         let mut data_list: Vec<std::sync::Arc<crate::l10n::L10NDemo>> = vec![];
 
-        for x in (0..buf.read_size()).rev() {
+        for x in (0..buf.try_read_size()?).rev() {
             let row = std::sync::Arc::new(crate::l10n::L10NDemo::new(&mut buf)?);
             data_list.push(row.clone());
             data_map.insert(row.id.clone(), row.clone());
@@ -91,7 +91,7 @@ impl TbPatchDemo {
         let mut data_map: std::collections::HashMap<i32, std::sync::Arc<crate::l10n::PatchDemo>> = Default::default();
         let mut data_list: Vec<std::sync::Arc<crate::l10n::PatchDemo>> = vec![];
 
-        for x in (0..buf.read_size()).rev() {
+        for x in (0..buf.try_read_size()?).rev() {
             let row = std::sync::Arc::new(crate::l10n::PatchDemo::new(&mut buf)?);
             data_list.push(row.clone());
             data_map.insert(row.id.clone(), row.clone());
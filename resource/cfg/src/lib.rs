@@ -24,26 +24,7 @@ pub trait GetBase<'a, T> {
     fn get_base(&'a self) -> Result<T, LubanError>;
 }
 
-#[derive(Debug)]
-pub enum LubanError {
-    Loader(String),
-    Table(String),
-    Bean(String),
-    Polymorphic(String),
-    Unknown(String),
-}
-
-impl std::fmt::Display for LubanError {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.write_str(match self {
-            LubanError::Loader(msg) |
-            LubanError::Table(msg) |
-            LubanError::Bean(msg) |
-            LubanError::Polymorphic(msg) |
-            LubanError::Unknown(msg) => msg,
-        })
-    }
-}
+pub use luban_lib::LubanError;
 #[derive(Debug)]
 pub struct Tables{
     pub TbBlackboard: std::sync::Arc<crate::ai::TbBlackboard>,
@@ -99,54 +80,54 @@ pub struct Tables{
 impl Tables {
     pub fn new<T: Fn(&str) -> Result<ByteBuf, LubanError>>(loader: T) -> Result<Tables, LubanError> {
         Ok(Tables {
-            TbBlackboard: crate::ai::TbBlackboard::new(loader("ai_tbblackboard")?)?,
-            TbBehaviorTree: crate::ai::TbBehaviorTree::new(loader("ai_tbbehaviortree")?)?,
-            TbGlobalConfig: crate::common::TbGlobalConfig::new(loader("common_tbglobalconfig")?)?,
-            TbItem: crate::item::TbItem::new(loader("item_tbitem")?)?,
-            TbL10NDemo: crate::l10n::TbL10NDemo::new(loader("l10n_tbl10ndemo")?)?,
-            TbPatchDemo: crate::l10n::TbPatchDemo::new(loader("l10n_tbpatchdemo")?)?,
-            TbTestTag: crate::tag::TbTestTag::new(loader("tag_tbtesttag")?)?,
-            TbFullTypes: crate::test::TbFullTypes::new(loader("test_tbfulltypes")?)?,
-            TbSingleton: crate::test::TbSingleton::new(loader("test_tbsingleton")?)?,
-            TbNotIndexList: crate::test::TbNotIndexList::new(loader("test_tbnotindexlist")?)?,
-            TbMultiUnionIndexList: crate::test::TbMultiUnionIndexList::new(loader("test_tbmultiunionindexlist")?)?,
-            TbMultiIndexList: crate::test::TbMultiIndexList::new(loader("test_tbmultiindexlist")?)?,
-            TbDataFromMisc: crate::test::TbDataFromMisc::new(loader("test_tbdatafrommisc")?)?,
-            TbMultiRowRecord: crate::test::TbMultiRowRecord::new(loader("test_tbmultirowrecord")?)?,
-            TbTestMultiColumn: crate::test::TbTestMultiColumn::new(loader("test_tbtestmulticolumn")?)?,
-            TbMultiRowTitle: crate::test::TbMultiRowTitle::new(loader("test_tbmultirowtitle")?)?,
-            TbTestNull: crate::test::TbTestNull::new(loader("test_tbtestnull")?)?,
-            TbDemoPrimitive: crate::test::TbDemoPrimitive::new(loader("test_tbdemoprimitive")?)?,
-            TbTestString: crate::test::TbTestString::new(loader("test_tbteststring")?)?,
-            TbDemoGroup: crate::test::TbDemoGroup::new(loader("test_tbdemogroup")?)?,
-            TbDemoGroup_C: crate::test::TbDemoGroup_C::new(loader("test_tbdemogroup_c")?)?,
-            TbDemoGroup_S: crate::test::TbDemoGroup_S::new(loader("test_tbdemogroup_s")?)?,
-            TbDemoGroup_E: crate::test::TbDemoGroup_E::new(loader("test_tbdemogroup_e")?)?,
-            TbTestGlobal: crate::test::TbTestGlobal::new(loader("test_tbtestglobal")?)?,
-            TbTestBeRef: crate::test::TbTestBeRef::new(loader("test_tbtestberef")?)?,
-            TbTestBeRef2: crate::test::TbTestBeRef2::new(loader("test_tbtestberef2")?)?,
-            TbTestRef: crate::test::TbTestRef::new(loader("test_tbtestref")?)?,
-            TbTestSize: crate::test::TbTestSize::new(loader("test_tbtestsize")?)?,
-            TbTestSet: crate::test::TbTestSet::new(loader("test_tbtestset")?)?,
-            TbTestRange: crate::test::TbTestRange::new(loader("test_tbtestrange")?)?,
-            TbDetectCsvEncoding: crate::test::TbDetectCsvEncoding::new(loader("test_tbdetectcsvencoding")?)?,
-            TbItem2: crate::test::TbItem2::new(loader("test_tbitem2")?)?,
-            TbTestIndex: crate::test::TbTestIndex::new(loader("test_tbtestindex")?)?,
-            TbTestMap: crate::test::TbTestMap::new(loader("test_tbtestmap")?)?,
-            TbExcelFromJson: crate::test::TbExcelFromJson::new(loader("test_tbexcelfromjson")?)?,
-            TbCompositeJsonTable1: crate::test::TbCompositeJsonTable1::new(loader("test_tbcompositejsontable1")?)?,
-            TbCompositeJsonTable2: crate::test::TbCompositeJsonTable2::new(loader("test_tbcompositejsontable2")?)?,
-            TbCompositeJsonTable3: crate::test::TbCompositeJsonTable3::new(loader("test_tbcompositejsontable3")?)?,
-            TbExcelFromJsonMultiRow: crate::test::TbExcelFromJsonMultiRow::new(loader("test_tbexcelfromjsonmultirow")?)?,
-            TbTestScriptableObject: crate::test::TbTestScriptableObject::new(loader("test_tbtestscriptableobject")?)?,
-            TbPath: crate::test::TbPath::new(loader("test_tbpath")?)?,
-            TbTestFieldAlias: crate::test::TbTestFieldAlias::new(loader("test_tbtestfieldalias")?)?,
-            TbTestFieldVariant: crate::test::TbTestFieldVariant::new(loader("test_tbtestfieldvariant")?)?,
-            TbTestFieldVariant2: crate::test::TbTestFieldVariant2::new(loader("test_tbtestfieldvariant2")?)?,
-            TbTestMapper: crate::test::TbTestMapper::new(loader("test_tbtestmapper")?)?,
-            TbDefineFromExcel2: crate::test::TbDefineFromExcel2::new(loader("test_tbdefinefromexcel2")?)?,
-            TbAutoImport1: crate::TbAutoImport1::new(loader("tbautoimport1")?)?,
-            TbAutoImport2: crate::test::TbAutoImport2::new(loader("test_tbautoimport2")?)?,
+            TbBlackboard: crate::ai::TbBlackboard::new(loader("ai_tbblackboard")?).map_err(|e| e.with_table("ai_tbblackboard"))?,
+            TbBehaviorTree: crate::ai::TbBehaviorTree::new(loader("ai_tbbehaviortree")?).map_err(|e| e.with_table("ai_tbbehaviortree"))?,
+            TbGlobalConfig: crate::common::TbGlobalConfig::new(loader("common_tbglobalconfig")?).map_err(|e| e.with_table("common_tbglobalconfig"))?,
+            TbItem: crate::item::TbItem::new(loader("item_tbitem")?).map_err(|e| e.with_table("item_tbitem"))?,
+            TbL10NDemo: crate::l10n::TbL10NDemo::new(loader("l10n_tbl10ndemo")?).map_err(|e| e.with_table("l10n_tbl10ndemo"))?,
+            TbPatchDemo: crate::l10n::TbPatchDemo::new(loader("l10n_tbpatchdemo")?).map_err(|e| e.with_table("l10n_tbpatchdemo"))?,
+            TbTestTag: crate::tag::TbTestTag::new(loader("tag_tbtesttag")?).map_err(|e| e.with_table("tag_tbtesttag"))?,
+            TbFullTypes: crate::test::TbFullTypes::new(loader("test_tbfulltypes")?).map_err(|e| e.with_table("test_tbfulltypes"))?,
+            TbSingleton: crate::test::TbSingleton::new(loader("test_tbsingleton")?).map_err(|e| e.with_table("test_tbsingleton"))?,
+            TbNotIndexList: crate::test::TbNotIndexList::new(loader("test_tbnotindexlist")?).map_err(|e| e.with_table("test_tbnotindexlist"))?,
+            TbMultiUnionIndexList: crate::test::TbMultiUnionIndexList::new(loader("test_tbmultiunionindexlist")?).map_err(|e| e.with_table("test_tbmultiunionindexlist"))?,
+            TbMultiIndexList: crate::test::TbMultiIndexList::new(loader("test_tbmultiindexlist")?).map_err(|e| e.with_table("test_tbmultiindexlist"))?,
+            TbDataFromMisc: crate::test::TbDataFromMisc::new(loader("test_tbdatafrommisc")?).map_err(|e| e.with_table("test_tbdatafrommisc"))?,
+            TbMultiRowRecord: crate::test::TbMultiRowRecord::new(loader("test_tbmultirowrecord")?).map_err(|e| e.with_table("test_tbmultirowrecord"))?,
+            TbTestMultiColumn: crate::test::TbTestMultiColumn::new(loader("test_tbtestmulticolumn")?).map_err(|e| e.with_table("test_tbtestmulticolumn"))?,
+            TbMultiRowTitle: crate::test::TbMultiRowTitle::new(loader("test_tbmultirowtitle")?).map_err(|e| e.with_table("test_tbmultirowtitle"))?,
+            TbTestNull: crate::test::TbTestNull::new(loader("test_tbtestnull")?).map_err(|e| e.with_table("test_tbtestnull"))?,
+            TbDemoPrimitive: crate::test::TbDemoPrimitive::new(loader("test_tbdemoprimitive")?).map_err(|e| e.with_table("test_tbdemoprimitive"))?,
+            TbTestString: crate::test::TbTestString::new(loader("test_tbteststring")?).map_err(|e| e.with_table("test_tbteststring"))?,
+            TbDemoGroup: crate::test::TbDemoGroup::new(loader("test_tbdemogroup")?).map_err(|e| e.with_table("test_tbdemogroup"))?,
+            TbDemoGroup_C: crate::test::TbDemoGroup_C::new(loader("test_tbdemogroup_c")?).map_err(|e| e.with_table("test_tbdemogroup_c"))?,
+            TbDemoGroup_S: crate::test::TbDemoGroup_S::new(loader("test_tbdemogroup_s")?).map_err(|e| e.with_table("test_tbdemogroup_s"))?,
+            TbDemoGroup_E: crate::test::TbDemoGroup_E::new(loader("test_tbdemogroup_e")?).map_err(|e| e.with_table("test_tbdemogroup_e"))?,
+            TbTestGlobal: crate::test::TbTestGlobal::new(loader("test_tbtestglobal")?).map_err(|e| e.with_table("test_tbtestglobal"))?,
+            TbTestBeRef: crate::test::TbTestBeRef::new(loader("test_tbtestberef")?).map_err(|e| e.with_table("test_tbtestberef"))?,
+            TbTestBeRef2: crate::test::TbTestBeRef2::new(loader("test_tbtestberef2")?).map_err(|e| e.with_table("test_tbtestberef2"))?,
+            TbTestRef: crate::test::TbTestRef::new(loader("test_tbtestref")?).map_err(|e| e.with_table("test_tbtestref"))?,
+            TbTestSize: crate::test::TbTestSize::new(loader("test_tbtestsize")?).map_err(|e| e.with_table("test_tbtestsize"))?,
+            TbTestSet: crate::test::TbTestSet::new(loader("test_tbtestset")?).map_err(|e| e.with_table("test_tbtestset"))?,
+            TbTestRange: crate::test::TbTestRange::new(loader("test_tbtestrange")?).map_err(|e| e.with_table("test_tbtestrange"))?,
+            TbDetectCsvEncoding: crate::test::TbDetectCsvEncoding::new(loader("test_tbdetectcsvencoding")?).map_err(|e| e.with_table("test_tbdetectcsvencoding"))?,
+            TbItem2: crate::test::TbItem2::new(loader("test_tbitem2")?).map_err(|e| e.with_table("test_tbitem2"))?,
+            TbTestIndex: crate::test::TbTestIndex::new(loader("test_tbtestindex")?).map_err(|e| e.with_table("test_tbtestindex"))?,
+            TbTestMap: crate::test::TbTestMap::new(loader("test_tbtestmap")?).map_err(|e| e.with_table("test_tbtestmap"))?,
+            TbExcelFromJson: crate::test::TbExcelFromJson::new(loader("test_tbexcelfromjson")?).map_err(|e| e.with_table("test_tbexcelfromjson"))?,
+            TbCompositeJsonTable1: crate::test::TbCompositeJsonTable1::new(loader("test_tbcompositejsontable1")?).map_err(|e| e.with_table("test_tbcompositejsontable1"))?,
+            TbCompositeJsonTable2: crate::test::TbCompositeJsonTable2::new(loader("test_tbcompositejsontable2")?).map_err(|e| e.with_table("test_tbcompositejsontable2"))?,
+            TbCompositeJsonTable3: crate::test::TbCompositeJsonTable3::new(loader("test_tbcompositejsontable3")?).map_err(|e| e.with_table("test_tbcompositejsontable3"))?,
+            TbExcelFromJsonMultiRow: crate::test::TbExcelFromJsonMultiRow::new(loader("test_tbexcelfromjsonmultirow")?).map_err(|e| e.with_table("test_tbexcelfromjsonmultirow"))?,
+            TbTestScriptableObject: crate::test::TbTestScriptableObject::new(loader("test_tbtestscriptableobject")?).map_err(|e| e.with_table("test_tbtestscriptableobject"))?,
+            TbPath: crate::test::TbPath::new(loader("test_tbpath")?).map_err(|e| e.with_table("test_tbpath"))?,
+            TbTestFieldAlias: crate::test::TbTestFieldAlias::new(loader("test_tbtestfieldalias")?).map_err(|e| e.with_table("test_tbtestfieldalias"))?,
+            TbTestFieldVariant: crate::test::TbTestFieldVariant::new(loader("test_tbtestfieldvariant")?).map_err(|e| e.with_table("test_tbtestfieldvariant"))?,
+            TbTestFieldVariant2: crate::test::TbTestFieldVariant2::new(loader("test_tbtestfieldvariant2")?).map_err(|e| e.with_table("test_tbtestfieldvariant2"))?,
+            TbTestMapper: crate::test::TbTestMapper::new(loader("test_tbtestmapper")?).map_err(|e| e.with_table("test_tbtestmapper"))?,
+            TbDefineFromExcel2: crate::test::TbDefineFromExcel2::new(loader("test_tbdefinefromexcel2")?).map_err(|e| e.with_table("test_tbdefinefromexcel2"))?,
+            TbAutoImport1: crate::TbAutoImport1::new(loader("tbautoimport1")?).map_err(|e| e.with_table("tbautoimport1"))?,
+            TbAutoImport2: crate::test::TbAutoImport2::new(loader("test_tbautoimport2")?).map_err(|e| e.with_table("test_tbautoimport2"))?,
         })
     }
 }
@@ -167,13 +148,15 @@ pub enum AudioType {
     AIFF = 2,
 }
 
-impl From<i32> for AudioType {
-    fn from(value: i32) -> Self {
+impl TryFrom<i32> for AudioType {
+    type Error = LubanError;
+
+    fn try_from(value: i32) -> Result<Self, LubanError> {
         match value { 
-            0 => AudioType::UNKNOWN,
-            1 => AudioType::ACC,
-            2 => AudioType::AIFF,
-            _ => panic!("Invalid value for AudioType:{}", value),
+            0 => Ok(AudioType::UNKNOWN),
+            1 => Ok(AudioType::ACC),
+            2 => Ok(AudioType::AIFF),
+            _ => Err(LubanError::Bean(format!("Invalid value for AudioType:{}", value))),
         }
     }
 }
@@ -205,25 +188,25 @@ pub struct AutoImport1 {
 
 impl AutoImport1{
     pub fn new(mut buf: &mut ByteBuf) -> Result<AutoImport1, LubanError> {
-        let id = buf.read_int();
-        let x1 = buf.read_bool();
-        let x5 = buf.read_long();
-        let x6 = buf.read_float();
-        let x8 = buf.read_int();
-        let x10 = buf.read_string();
-        let x13 = buf.read_int().into();
-        let x13_2 = crate::test::DemoFlag::from_bits_truncate(buf.read_uint());
+        let id = buf.try_read_int()?;
+        let x1 = buf.try_read_bool()?;
+        let x5 = buf.try_read_long()?;
+        let x6 = buf.try_read_float()?;
+        let x8 = buf.try_read_int()?;
+        let x10 = buf.try_read_string()?;
+        let x13 = buf.try_read_enum()?;
+        let x13_2 = crate::test::DemoFlag::from_bits_truncate(buf.try_read_uint()?);
         let x14 = crate::test::DemoDynamic::new(&mut buf)?;
         let x15 = crate::test::Shape::new(&mut buf)?;
         let v2 = crate::vec2::new(&mut buf)?;
-        let t1 = buf.read_ulong();
-        let k1 = {let n0 = std::cmp::min(buf.read_size(), buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(buf.read_int()); } _e0 };
-        let k2 = {let n0 = std::cmp::min(buf.read_size(), buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(buf.read_int()); } _e0 };
-        let k8 = {let n0 = std::cmp::min(buf.read_size(), buf.size()); let mut _e0 = std::collections::HashMap::with_capacity(n0 * 3 / 2);for i0 in 0..n0 { let _k0 = buf.read_int(); let _v0 = buf.read_int(); _e0.insert(_k0, _v0);} _e0 };
-        let k9 = {let n0 = std::cmp::min(buf.read_size(), buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(crate::test::DemoE2::new(&mut buf)?); } _e0 };
-        let k10 = {let n0 = std::cmp::min(buf.read_size(), buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(crate::vec3::new(&mut buf)?); } _e0 };
-        let k11 = {let n0 = std::cmp::min(buf.read_size(), buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(crate::vec4::new(&mut buf)?); } _e0 };
-        let mut v11 = if buf.read_bool() { Some(crate::vec3::new(&mut buf)?) } else { None };
+        let t1 = buf.try_read_ulong()?;
+        let k1 = {let n0 = std::cmp::min(buf.try_read_size()?, buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(buf.try_read_int()?); } _e0 };
+        let k2 = {let n0 = std::cmp::min(buf.try_read_size()?, buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(buf.try_read_int()?); } _e0 };
+        let k8 = {let n0 = std::cmp::min(buf.try_read_size()?, buf.size()); let mut _e0 = std::collections::HashMap::with_capacity(n0 * 3 / 2);for i0 in 0..n0 { let _k0 = buf.try_read_int()?; let _v0 = buf.try_read_int()?; _e0.insert(_k0, _v0);} _e0 };
+        let k9 = {let n0 = std::cmp::min(buf.try_read_size()?, buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(crate::test::DemoE2::new(&mut buf)?); } _e0 };
+        let k10 = {let n0 = std::cmp::min(buf.try_read_size()?, buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(crate::vec3::new(&mut buf)?); } _e0 };
+        let k11 = {let n0 = std::cmp::min(buf.try_read_size()?, buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(crate::vec4::new(&mut buf)?); } _e0 };
+        let mut v11 = if buf.try_read_bool()? { Some(crate::vec3::new(&mut buf)?) } else { None };
         
         Ok(AutoImport1 { id, x1, x5, x6, x8, x10, x13, x13_2, x14, x15, v2, t1, k1, k2, k8, k9, k10, k11, v11, })
     }
@@ -239,8 +222,8 @@ pub struct vec2 {
 
 impl vec2{
     pub fn new(mut buf: &mut ByteBuf) -> Result<vec2, LubanError> {
-        let x = buf.read_float();
-        let y = buf.read_float();
+        let x = buf.try_read_float()?;
+        let y = buf.try_read_float()?;
         
         Ok(vec2 { x, y, })
     }
@@ -257,9 +240,9 @@ pub struct vec3 {
 
 impl vec3{
     pub fn new(mut buf: &mut ByteBuf) -> Result<vec3, LubanError> {
-        let x = buf.read_float();
-        let y = buf.read_float();
-        let z = buf.read_float();
+        let x = buf.try_read_float()?;
+        let y = buf.try_read_float()?;
+        let z = buf.try_read_float()?;
         
         Ok(vec3 { x, y, z, })
     }
@@ -277,10 +260,10 @@ pub struct vec4 {
 
 impl vec4{
     pub fn new(mut buf: &mut ByteBuf) -> Result<vec4, LubanError> {
-        let x = buf.read_float();
-        let y = buf.read_float();
-        let z = buf.read_float();
-        let w = buf.read_float();
+        let x = buf.try_read_float()?;
+        let y = buf.try_read_float()?;
+        let z = buf.try_read_float()?;
+        let w = buf.try_read_float()?;
         
         Ok(vec4 { x, y, z, w, })
     }
@@ -300,7 +283,7 @@ impl TbAutoImport1 {
         let mut data_map: std::collections::HashMap<i32, std::sync::Arc<crate::AutoImport1>> = Default::default();
         let mut data_list: Vec<std::sync::Arc<crate::AutoImport1>> = vec![];
 
-        for x in (0..buf.read_size()).rev() {
+        for x in (0..buf.try_read_size()?).rev() {
             let row = std::sync::Arc::new(crate::AutoImport1::new(&mut buf)?);
             data_list.push(row.clone());
             data_map.insert(row.id.clone(), row.clone());
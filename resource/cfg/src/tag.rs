@@ -19,8 +19,8 @@ pub struct TestTag {
 
 impl TestTag{
     pub fn new(mut buf: &mut ByteBuf) -> Result<TestTag, LubanError> {
-        let id = buf.read_int();
-        let value = buf.read_string();
+        let id = buf.try_read_int()?;
+        let value = buf.try_read_string()?;
         
         Ok(TestTag { id, value, })
     }
@@ -40,7 +40,7 @@ impl TbTestTag {
         let mut data_map: std::collections::HashMap<i32, std::sync::Arc<crate::tag::TestTag>> = Default::default();
         let mut data_list: Vec<std::sync::Arc<crate::tag::TestTag>> = vec![];
 
-        for x in (0..buf.read_size()).rev() {
+        for x in (0..buf.try_read_size()?).rev() {
             let row = std::sync::Arc::new(crate::tag::TestTag::new(&mut buf)?);
             data_list.push(row.clone());
             data_map.insert(row.id.clone(), row.clone());
@@ -21,9 +21,9 @@ pub struct RoleInfo {
 
 impl RoleInfo{
     pub fn new(mut buf: &mut ByteBuf) -> Result<RoleInfo, LubanError> {
-        let x1 = buf.read_int();
-        let x3 = buf.read_int();
-        let role_id = buf.read_long();
+        let x1 = buf.try_read_int()?;
+        let x3 = buf.try_read_int()?;
+        let role_id = buf.try_read_long()?;
         
         Ok(RoleInfo { x1, x3, role_id, })
     }
@@ -29,13 +29,15 @@ pub enum AudioType2 {
     AIFF = 2,
 }
 
-impl From<i32> for AudioType2 {
-    fn from(value: i32) -> Self {
+impl TryFrom<i32> for AudioType2 {
+    type Error = LubanError;
+
+    fn try_from(value: i32) -> Result<Self, LubanError> {
         match value { 
-            0 => AudioType2::UNKNOWN,
-            1 => AudioType2::ACC,
-            2 => AudioType2::AIFF,
-            _ => panic!("Invalid value for AudioType2:{}", value),
+            0 => Ok(AudioType2::UNKNOWN),
+            1 => Ok(AudioType2::ACC),
+            2 => Ok(AudioType2::AIFF),
+            _ => Err(LubanError::Bean(format!("Invalid value for AudioType2:{}", value))),
         }
     }
 }
@@ -54,16 +56,18 @@ pub enum DemoEnum {
     Any = 6,
 }
 
-impl From<i32> for DemoEnum {
-    fn from(value: i32) -> Self {
+impl TryFrom<i32> for DemoEnum {
+    type Error = LubanError;
+
+    fn try_from(value: i32) -> Result<Self, LubanError> {
         match value { 
-            0 => DemoEnum::NONE,
-            1 => DemoEnum::A,
-            2 => DemoEnum::B,
-            4 => DemoEnum::C,
-            5 => DemoEnum::D,
-            6 => DemoEnum::Any,
-            _ => panic!("Invalid value for DemoEnum:{}", value),
+            0 => Ok(DemoEnum::NONE),
+            1 => Ok(DemoEnum::A),
+            2 => Ok(DemoEnum::B),
+            4 => Ok(DemoEnum::C),
+            5 => Ok(DemoEnum::D),
+            6 => Ok(DemoEnum::Any),
+            _ => Err(LubanError::Bean(format!("Invalid value for DemoEnum:{}", value))),
         }
     }
 }
@@ -82,10 +86,12 @@ pub enum ETestEmptyEnum {
     None
 }
 
-impl From<i32> for ETestEmptyEnum {
-    fn from(value: i32) -> Self {
+impl TryFrom<i32> for ETestEmptyEnum {
+    type Error = LubanError;
+
+    fn try_from(value: i32) -> Result<Self, LubanError> {
         match value { 
-            _ => panic!("Invalid value for ETestEmptyEnum:{}", value),
+            _ => Err(LubanError::Bean(format!("Invalid value for ETestEmptyEnum:{}", value))),
         }
     }
 }
@@ -97,13 +103,15 @@ pub enum ETestEmptyEnum2 {
     X_257 = 257,
 }
 
-impl From<i32> for ETestEmptyEnum2 {
-    fn from(value: i32) -> Self {
+impl TryFrom<i32> for ETestEmptyEnum2 {
+    type Error = LubanError;
+
+    fn try_from(value: i32) -> Result<Self, LubanError> {
         match value { 
-            255 => ETestEmptyEnum2::SMALL_THAN_256,
-            256 => ETestEmptyEnum2::X_256,
-            257 => ETestEmptyEnum2::X_257,
-            _ => panic!("Invalid value for ETestEmptyEnum2:{}", value),
+            255 => Ok(ETestEmptyEnum2::SMALL_THAN_256),
+            256 => Ok(ETestEmptyEnum2::X_256),
+            257 => Ok(ETestEmptyEnum2::X_257),
+            _ => Err(LubanError::Bean(format!("Invalid value for ETestEmptyEnum2:{}", value))),
         }
     }
 }
@@ -120,14 +128,16 @@ pub enum ETestQuality {
     D = 4,
 }
 
-impl From<i32> for ETestQuality {
-    fn from(value: i32) -> Self {
+impl TryFrom<i32> for ETestQuality {
+    type Error = LubanError;
+
+    fn try_from(value: i32) -> Result<Self, LubanError> {
         match value { 
-            1 => ETestQuality::A,
-            2 => ETestQuality::B,
-            3 => ETestQuality::C,
-            4 => ETestQuality::D,
-            _ => panic!("Invalid value for ETestQuality:{}", value),
+            1 => Ok(ETestQuality::A),
+            2 => Ok(ETestQuality::B),
+            3 => Ok(ETestQuality::C),
+            4 => Ok(ETestQuality::D),
+            _ => Err(LubanError::Bean(format!("Invalid value for ETestQuality:{}", value))),
         }
     }
 }
@@ -139,12 +149,14 @@ pub enum ETestUeType {
     BLACK = 1,
 }
 
-impl From<i32> for ETestUeType {
-    fn from(value: i32) -> Self {
+impl TryFrom<i32> for ETestUeType {
+    type Error = LubanError;
+
+    fn try_from(value: i32) -> Result<Self, LubanError> {
         match value { 
-            0 => ETestUeType::WHITE,
-            1 => ETestUeType::BLACK,
-            _ => panic!("Invalid value for ETestUeType:{}", value),
+            0 => Ok(ETestUeType::WHITE),
+            1 => Ok(ETestUeType::BLACK),
+            _ => Err(LubanError::Bean(format!("Invalid value for ETestUeType:{}", value))),
         }
     }
 }
@@ -176,25 +188,25 @@ pub struct AutoImport2 {
 
 impl AutoImport2{
     pub fn new(mut buf: &mut ByteBuf) -> Result<AutoImport2, LubanError> {
-        let id = buf.read_int();
-        let x1 = buf.read_bool();
-        let x5 = buf.read_long();
-        let x6 = buf.read_float();
-        let x8 = buf.read_int();
-        let x10 = buf.read_string();
-        let x13 = buf.read_int().into();
-        let x13_2 = crate::test::DemoFlag::from_bits_truncate(buf.read_uint());
+        let id = buf.try_read_int()?;
+        let x1 = buf.try_read_bool()?;
+        let x5 = buf.try_read_long()?;
+        let x6 = buf.try_read_float()?;
+        let x8 = buf.try_read_int()?;
+        let x10 = buf.try_read_string()?;
+        let x13 = buf.try_read_enum()?;
+        let x13_2 = crate::test::DemoFlag::from_bits_truncate(buf.try_read_uint()?);
         let x14 = crate::test::DemoDynamic::new(&mut buf)?;
         let x15 = crate::test::Shape::new(&mut buf)?;
         let v2 = crate::vec2::new(&mut buf)?;
-        let t1 = buf.read_ulong();
-        let k1 = {let n0 = std::cmp::min(buf.read_size(), buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(buf.read_int()); } _e0 };
-        let k2 = {let n0 = std::cmp::min(buf.read_size(), buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(buf.read_int()); } _e0 };
-        let k8 = {let n0 = std::cmp::min(buf.read_size(), buf.size()); let mut _e0 = std::collections::HashMap::with_capacity(n0 * 3 / 2);for i0 in 0..n0 { let _k0 = buf.read_int(); let _v0 = buf.read_int(); _e0.insert(_k0, _v0);} _e0 };
-        let k9 = {let n0 = std::cmp::min(buf.read_size(), buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(crate::test::DemoE2::new(&mut buf)?); } _e0 };
-        let k10 = {let n0 = std::cmp::min(buf.read_size(), buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(crate::vec3::new(&mut buf)?); } _e0 };
-        let k11 = {let n0 = std::cmp::min(buf.read_size(), buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(crate::vec4::new(&mut buf)?); } _e0 };
-        let mut v11 = if buf.read_bool() { Some(crate::vec3::new(&mut buf)?) } else { None };
+        let t1 = buf.try_read_ulong()?;
+        let k1 = {let n0 = std::cmp::min(buf.try_read_size()?, buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(buf.try_read_int()?); } _e0 };
+        let k2 = {let n0 = std::cmp::min(buf.try_read_size()?, buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(buf.try_read_int()?); } _e0 };
+        let k8 = {let n0 = std::cmp::min(buf.try_read_size()?, buf.size()); let mut _e0 = std::collections::HashMap::with_capacity(n0 * 3 / 2);for i0 in 0..n0 { let _k0 = buf.try_read_int()?; let _v0 = buf.try_read_int()?; _e0.insert(_k0, _v0);} _e0 };
+        let k9 = {let n0 = std::cmp::min(buf.try_read_size()?, buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(crate::test::DemoE2::new(&mut buf)?); } _e0 };
+        let k10 = {let n0 = std::cmp::min(buf.try_read_size()?, buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(crate::vec3::new(&mut buf)?); } _e0 };
+        let k11 = {let n0 = std::cmp::min(buf.try_read_size()?, buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(crate::vec4::new(&mut buf)?); } _e0 };
+        let mut v11 = if buf.try_read_bool()? { Some(crate::vec3::new(&mut buf)?) } else { None };
         
         Ok(AutoImport2 { id, x1, x5, x6, x8, x10, x13, x13_2, x14, x15, v2, t1, k1, k2, k8, k9, k10, k11, v11, })
     }
@@ -211,9 +223,9 @@ pub struct CompactString {
 
 impl CompactString{
     pub fn new(mut buf: &mut ByteBuf) -> Result<CompactString, LubanError> {
-        let id = buf.read_int();
-        let s2 = buf.read_string();
-        let s3 = buf.read_string();
+        let id = buf.try_read_int()?;
+        let s2 = buf.try_read_string()?;
+        let s3 = buf.try_read_string()?;
         
         Ok(CompactString { id, s2, s3, })
     }
@@ -229,8 +241,8 @@ pub struct CompositeJsonTable1 {
 
 impl CompositeJsonTable1{
     pub fn new(mut buf: &mut ByteBuf) -> Result<CompositeJsonTable1, LubanError> {
-        let id = buf.read_int();
-        let x = buf.read_string();
+        let id = buf.try_read_int()?;
+        let x = buf.try_read_string()?;
         
         Ok(CompositeJsonTable1 { id, x, })
     }
@@ -246,8 +258,8 @@ pub struct CompositeJsonTable2 {
 
 impl CompositeJsonTable2{
     pub fn new(mut buf: &mut ByteBuf) -> Result<CompositeJsonTable2, LubanError> {
-        let id = buf.read_int();
-        let y = buf.read_int();
+        let id = buf.try_read_int()?;
+        let y = buf.try_read_int()?;
         
         Ok(CompositeJsonTable2 { id, y, })
     }
@@ -263,8 +275,8 @@ pub struct CompositeJsonTable3 {
 
 impl CompositeJsonTable3{
     pub fn new(mut buf: &mut ByteBuf) -> Result<CompositeJsonTable3, LubanError> {
-        let a = buf.read_int();
-        let b = buf.read_int();
+        let a = buf.try_read_int()?;
+        let b = buf.try_read_int()?;
         
         Ok(CompositeJsonTable3 { a, b, })
     }
@@ -280,8 +292,8 @@ pub struct DateTimeRange {
 
 impl DateTimeRange{
     pub fn new(mut buf: &mut ByteBuf) -> Result<DateTimeRange, LubanError> {
-        let start_time = buf.read_ulong();
-        let end_time = buf.read_ulong();
+        let start_time = buf.try_read_ulong()?;
+        let end_time = buf.try_read_ulong()?;
         
         Ok(DateTimeRange { start_time, end_time, })
     }
@@ -317,26 +329,26 @@ pub struct DefineFromExcel2 {
 
 impl DefineFromExcel2{
     pub fn new(mut buf: &mut ByteBuf) -> Result<DefineFromExcel2, LubanError> {
-        let id = buf.read_int();
-        let x1 = buf.read_bool();
-        let x5 = buf.read_long();
-        let x6 = buf.read_float();
-        let x8 = buf.read_int();
-        let x10 = buf.read_string();
-        let x13 = buf.read_int().into();
-        let x13_2 = crate::test::DemoFlag::from_bits_truncate(buf.read_uint());
-        let x13_3 = crate::test::DemoFlag::from_bits_truncate(buf.read_uint());
+        let id = buf.try_read_int()?;
+        let x1 = buf.try_read_bool()?;
+        let x5 = buf.try_read_long()?;
+        let x6 = buf.try_read_float()?;
+        let x8 = buf.try_read_int()?;
+        let x10 = buf.try_read_string()?;
+        let x13 = buf.try_read_enum()?;
+        let x13_2 = crate::test::DemoFlag::from_bits_truncate(buf.try_read_uint()?);
+        let x13_3 = crate::test::DemoFlag::from_bits_truncate(buf.try_read_uint()?);
         let x14 = crate::test::DemoDynamic::new(&mut buf)?;
         let x15 = crate::test::Shape::new(&mut buf)?;
         let v2 = crate::vec2::new(&mut buf)?;
-        let t1 = buf.read_ulong();
-        let k1 = {let n0 = std::cmp::min(buf.read_size(), buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(buf.read_int()); } _e0 };
-        let k2 = {let n0 = std::cmp::min(buf.read_size(), buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(buf.read_int()); } _e0 };
-        let k8 = {let n0 = std::cmp::min(buf.read_size(), buf.size()); let mut _e0 = std::collections::HashMap::with_capacity(n0 * 3 / 2);for i0 in 0..n0 { let _k0 = buf.read_int(); let _v0 = buf.read_int(); _e0.insert(_k0, _v0);} _e0 };
-        let k9 = {let n0 = std::cmp::min(buf.read_size(), buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(crate::test::DemoE2::new(&mut buf)?); } _e0 };
-        let k10 = {let n0 = std::cmp::min(buf.read_size(), buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(crate::vec3::new(&mut buf)?); } _e0 };
-        let k11 = {let n0 = std::cmp::min(buf.read_size(), buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(crate::vec4::new(&mut buf)?); } _e0 };
-        let mut v11 = if buf.read_bool() { Some(crate::vec3::new(&mut buf)?) } else { None };
+        let t1 = buf.try_read_ulong()?;
+        let k1 = {let n0 = std::cmp::min(buf.try_read_size()?, buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(buf.try_read_int()?); } _e0 };
+        let k2 = {let n0 = std::cmp::min(buf.try_read_size()?, buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(buf.try_read_int()?); } _e0 };
+        let k8 = {let n0 = std::cmp::min(buf.try_read_size()?, buf.size()); let mut _e0 = std::collections::HashMap::with_capacity(n0 * 3 / 2);for i0 in 0..n0 { let _k0 = buf.try_read_int()?; let _v0 = buf.try_read_int()?; _e0.insert(_k0, _v0);} _e0 };
+        let k9 = {let n0 = std::cmp::min(buf.try_read_size()?, buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(crate::test::DemoE2::new(&mut buf)?); } _e0 };
+        let k10 = {let n0 = std::cmp::min(buf.try_read_size()?, buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(crate::vec3::new(&mut buf)?); } _e0 };
+        let k11 = {let n0 = std::cmp::min(buf.try_read_size()?, buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(crate::vec4::new(&mut buf)?); } _e0 };
+        let mut v11 = if buf.try_read_bool()? { Some(crate::vec3::new(&mut buf)?) } else { None };
         
         Ok(DefineFromExcel2 { id, x1, x5, x6, x8, x10, x13, x13_2, x13_3, x14, x15, v2, t1, k1, k2, k8, k9, k10, k11, v11, })
     }
@@ -351,7 +363,7 @@ pub struct DemoDynamic {
 
 impl DemoDynamic {
     pub fn new(mut buf: &mut ByteBuf) -> Result<std::sync::Arc<AbstractBase>, LubanError> {
-        let type_id = buf.read_int();
+        let type_id = buf.try_read_int()?;
         match type_id {
             crate::test::DemoD2::__ID__ => Ok(std::sync::Arc::new(crate::test::DemoD2::new(buf)?)),
             crate::test::DemoE1::__ID__ => Ok(std::sync::Arc::new(crate::test::DemoE1::new(buf)?)),
@@ -422,8 +434,8 @@ pub struct DemoD2 {
 
 impl DemoD2{
     pub fn new(mut buf: &mut ByteBuf) -> Result<DemoD2, LubanError> {
-        let x1 = buf.read_int();
-        let x2 = buf.read_int();
+        let x1 = buf.try_read_int()?;
+        let x2 = buf.try_read_int()?;
         
         Ok(DemoD2 { x1, x2, })
     }
@@ -440,7 +452,7 @@ pub struct DemoD3 {
 
 impl DemoD3 {
     pub fn new(mut buf: &mut ByteBuf) -> Result<std::sync::Arc<AbstractBase>, LubanError> {
-        let type_id = buf.read_int();
+        let type_id = buf.try_read_int()?;
         match type_id {
             crate::test::DemoE1::__ID__ => Ok(std::sync::Arc::new(crate::test::DemoE1::new(buf)?)),
             crate::test::login::RoleInfo::__ID__ => Ok(std::sync::Arc::new(crate::test::login::RoleInfo::new(buf)?)),
@@ -497,9 +509,9 @@ pub struct DemoE1 {
 
 impl DemoE1{
     pub fn new(mut buf: &mut ByteBuf) -> Result<DemoE1, LubanError> {
-        let x1 = buf.read_int();
-        let x3 = buf.read_int();
-        let x4 = buf.read_int();
+        let x1 = buf.try_read_int()?;
+        let x3 = buf.try_read_int()?;
+        let x4 = buf.try_read_int()?;
         
         Ok(DemoE1 { x1, x3, x4, })
     }
@@ -516,7 +528,7 @@ pub struct DemoD5 {
 
 impl DemoD5{
     pub fn new(mut buf: &mut ByteBuf) -> Result<DemoD5, LubanError> {
-        let x1 = buf.read_int();
+        let x1 = buf.try_read_int()?;
         let time = crate::test::DateTimeRange::new(&mut buf)?;
         
         Ok(DemoD5 { x1, time, })
@@ -533,8 +545,8 @@ pub struct DemoE2 {
 
 impl DemoE2{
     pub fn new(mut buf: &mut ByteBuf) -> Result<DemoE2, LubanError> {
-        let mut y1 = if buf.read_bool() { Some(buf.read_int()) } else { None };
-        let y2 = buf.read_bool();
+        let mut y1 = if buf.try_read_bool()? { Some(buf.try_read_int()?) } else { None };
+        let y2 = buf.try_read_bool()?;
         
         Ok(DemoE2 { y1, y2, })
     }
@@ -555,13 +567,13 @@ pub struct DemoExplicitType {
 
 impl DemoExplicitType{
     pub fn new(mut buf: &mut ByteBuf) -> Result<DemoExplicitType, LubanError> {
-        let x1 = buf.read_byte();
-        let x2 = buf.read_short();
-        let x3 = buf.read_int();
-        let x4 = buf.read_long();
-        let x5 = buf.read_float();
-        let x6 = buf.read_double();
-        let x7 = buf.read_long();
+        let x1 = buf.try_read_byte()?;
+        let x2 = buf.try_read_short()?;
+        let x3 = buf.try_read_int()?;
+        let x4 = buf.try_read_long()?;
+        let x5 = buf.try_read_float()?;
+        let x6 = buf.try_read_double()?;
+        let x7 = buf.try_read_long()?;
         
         Ok(DemoExplicitType { x1, x2, x3, x4, x5, x6, x7, })
     }
@@ -581,11 +593,11 @@ pub struct DemoGroup {
 
 impl DemoGroup{
     pub fn new(mut buf: &mut ByteBuf) -> Result<DemoGroup, LubanError> {
-        let id = buf.read_int();
-        let x1 = buf.read_int();
-        let x2 = buf.read_int();
-        let x3 = buf.read_int();
-        let x4 = buf.read_int();
+        let id = buf.try_read_int()?;
+        let x1 = buf.try_read_int()?;
+        let x2 = buf.try_read_int()?;
+        let x3 = buf.try_read_int()?;
+        let x4 = buf.try_read_int()?;
         let x5 = crate::test::InnerGroup::new(&mut buf)?;
         
         Ok(DemoGroup { id, x1, x2, x3, x4, x5, })
@@ -613,19 +625,19 @@ pub struct DemoPrimitiveTypesTable {
 
 impl DemoPrimitiveTypesTable{
     pub fn new(mut buf: &mut ByteBuf) -> Result<DemoPrimitiveTypesTable, LubanError> {
-        let x1 = buf.read_bool();
-        let x2 = buf.read_byte();
-        let x3 = buf.read_short();
-        let x4 = buf.read_int();
-        let x5 = buf.read_long();
-        let x6 = buf.read_float();
-        let x7 = buf.read_double();
-        let s1 = buf.read_string();
-        let s2 = buf.read_string();
+        let x1 = buf.try_read_bool()?;
+        let x2 = buf.try_read_byte()?;
+        let x3 = buf.try_read_short()?;
+        let x4 = buf.try_read_int()?;
+        let x5 = buf.try_read_long()?;
+        let x6 = buf.try_read_float()?;
+        let x7 = buf.try_read_double()?;
+        let s1 = buf.try_read_string()?;
+        let s2 = buf.try_read_string()?;
         let v2 = crate::vec2::new(&mut buf)?;
         let v3 = crate::vec3::new(&mut buf)?;
         let v4 = crate::vec4::new(&mut buf)?;
-        let t1 = buf.read_ulong();
+        let t1 = buf.try_read_ulong()?;
         
         Ok(DemoPrimitiveTypesTable { x1, x2, x3, x4, x5, x6, x7, s1, s2, v2, v3, v4, t1, })
     }
@@ -642,8 +654,8 @@ pub struct DemoSingletonType {
 
 impl DemoSingletonType{
     pub fn new(mut buf: &mut ByteBuf) -> Result<DemoSingletonType, LubanError> {
-        let id = buf.read_int();
-        let name = buf.read_string();
+        let id = buf.try_read_int()?;
+        let name = buf.try_read_string()?;
         let date = crate::test::DemoDynamic::new(&mut buf)?;
         
         Ok(DemoSingletonType { id, name, date, })
@@ -659,7 +671,7 @@ pub struct DemoType1 {
 
 impl DemoType1{
     pub fn new(mut buf: &mut ByteBuf) -> Result<DemoType1, LubanError> {
-        let x1 = buf.read_int();
+        let x1 = buf.try_read_int()?;
         
         Ok(DemoType1 { x1, })
     }
@@ -695,28 +707,28 @@ pub struct DemoType2 {
 
 impl DemoType2{
     pub fn new(mut buf: &mut ByteBuf) -> Result<DemoType2, LubanError> {
-        let x4 = buf.read_int();
-        let x1 = buf.read_bool();
-        let x2 = buf.read_byte();
-        let x3 = buf.read_short();
-        let x5 = buf.read_long();
-        let x6 = buf.read_float();
-        let x7 = buf.read_double();
-        let x8_0 = buf.read_short();
-        let x8 = buf.read_int();
-        let x9 = buf.read_long();
-        let x10 = buf.read_string();
+        let x4 = buf.try_read_int()?;
+        let x1 = buf.try_read_bool()?;
+        let x2 = buf.try_read_byte()?;
+        let x3 = buf.try_read_short()?;
+        let x5 = buf.try_read_long()?;
+        let x6 = buf.try_read_float()?;
+        let x7 = buf.try_read_double()?;
+        let x8_0 = buf.try_read_short()?;
+        let x8 = buf.try_read_int()?;
+        let x9 = buf.try_read_long()?;
+        let x10 = buf.try_read_string()?;
         let x12 = crate::test::DemoType1::new(&mut buf)?;
-        let x13 = buf.read_int().into();
+        let x13 = buf.try_read_enum()?;
         let x14 = crate::test::DemoDynamic::new(&mut buf)?;
-        let s1 = buf.read_string();
-        let t1 = buf.read_ulong();
-        let k1 = {let n0 = std::cmp::min(buf.read_size(), buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(buf.read_int()); } _e0 };
-        let k2 = {let n0 = std::cmp::min(buf.read_size(), buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(buf.read_int()); } _e0 };
-        let k5 = {let n0 = std::cmp::min(buf.read_size(), buf.size());let mut _e0 = std::collections::HashSet::default(); for i0 in 0..n0 { _e0.insert(buf.read_int()); } _e0 };
-        let k8 = {let n0 = std::cmp::min(buf.read_size(), buf.size()); let mut _e0 = std::collections::HashMap::with_capacity(n0 * 3 / 2);for i0 in 0..n0 { let _k0 = buf.read_int(); let _v0 = buf.read_int(); _e0.insert(_k0, _v0);} _e0 };
-        let k9 = {let n0 = std::cmp::min(buf.read_size(), buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(crate::test::DemoE2::new(&mut buf)?); } _e0 };
-        let k15 = {let n0 = std::cmp::min(buf.read_size(), buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(crate::test::DemoDynamic::new(&mut buf)?); } _e0 };
+        let s1 = buf.try_read_string()?;
+        let t1 = buf.try_read_ulong()?;
+        let k1 = {let n0 = std::cmp::min(buf.try_read_size()?, buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(buf.try_read_int()?); } _e0 };
+        let k2 = {let n0 = std::cmp::min(buf.try_read_size()?, buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(buf.try_read_int()?); } _e0 };
+        let k5 = {let n0 = std::cmp::min(buf.try_read_size()?, buf.size());let mut _e0 = std::collections::HashSet::default(); for i0 in 0..n0 { _e0.insert(buf.try_read_int()?); } _e0 };
+        let k8 = {let n0 = std::cmp::min(buf.try_read_size()?, buf.size()); let mut _e0 = std::collections::HashMap::with_capacity(n0 * 3 / 2);for i0 in 0..n0 { let _k0 = buf.try_read_int()?; let _v0 = buf.try_read_int()?; _e0.insert(_k0, _v0);} _e0 };
+        let k9 = {let n0 = std::cmp::min(buf.try_read_size()?, buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(crate::test::DemoE2::new(&mut buf)?); } _e0 };
+        let k15 = {let n0 = std::cmp::min(buf.try_read_size()?, buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(crate::test::DemoDynamic::new(&mut buf)?); } _e0 };
         
         Ok(DemoType2 { x4, x1, x2, x3, x5, x6, x7, x8_0, x8, x9, x10, x12, x13, x14, s1, t1, k1, k2, k5, k8, k9, k15, })
     }
@@ -732,8 +744,8 @@ pub struct DetectEncoding {
 
 impl DetectEncoding{
     pub fn new(mut buf: &mut ByteBuf) -> Result<DetectEncoding, LubanError> {
-        let id = buf.read_int();
-        let name = buf.read_string();
+        let id = buf.try_read_int()?;
+        let name = buf.try_read_string()?;
         
         Ok(DetectEncoding { id, name, })
     }
@@ -761,20 +773,20 @@ pub struct ExcelFromJson {
 
 impl ExcelFromJson{
     pub fn new(mut buf: &mut ByteBuf) -> Result<ExcelFromJson, LubanError> {
-        let x4 = buf.read_int();
-        let x1 = buf.read_bool();
-        let x5 = buf.read_long();
-        let x6 = buf.read_float();
-        let s1 = buf.read_string();
-        let s2 = buf.read_string();
-        let t1 = buf.read_ulong();
+        let x4 = buf.try_read_int()?;
+        let x1 = buf.try_read_bool()?;
+        let x5 = buf.try_read_long()?;
+        let x6 = buf.try_read_float()?;
+        let s1 = buf.try_read_string()?;
+        let s2 = buf.try_read_string()?;
+        let t1 = buf.try_read_ulong()?;
         let x12 = crate::test::DemoType1::new(&mut buf)?;
-        let x13 = buf.read_int().into();
+        let x13 = buf.try_read_enum()?;
         let x14 = crate::test::DemoDynamic::new(&mut buf)?;
-        let k1 = {let n0 = std::cmp::min(buf.read_size(), buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(buf.read_int()); } _e0 };
-        let k8 = {let n0 = std::cmp::min(buf.read_size(), buf.size()); let mut _e0 = std::collections::HashMap::with_capacity(n0 * 3 / 2);for i0 in 0..n0 { let _k0 = buf.read_int(); let _v0 = buf.read_int(); _e0.insert(_k0, _v0);} _e0 };
-        let k9 = {let n0 = std::cmp::min(buf.read_size(), buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(crate::test::DemoE2::new(&mut buf)?); } _e0 };
-        let k15 = {let n0 = std::cmp::min(buf.read_size(), buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(crate::test::DemoDynamic::new(&mut buf)?); } _e0 };
+        let k1 = {let n0 = std::cmp::min(buf.try_read_size()?, buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(buf.try_read_int()?); } _e0 };
+        let k8 = {let n0 = std::cmp::min(buf.try_read_size()?, buf.size()); let mut _e0 = std::collections::HashMap::with_capacity(n0 * 3 / 2);for i0 in 0..n0 { let _k0 = buf.try_read_int()?; let _v0 = buf.try_read_int()?; _e0.insert(_k0, _v0);} _e0 };
+        let k9 = {let n0 = std::cmp::min(buf.try_read_size()?, buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(crate::test::DemoE2::new(&mut buf)?); } _e0 };
+        let k15 = {let n0 = std::cmp::min(buf.try_read_size()?, buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(crate::test::DemoDynamic::new(&mut buf)?); } _e0 };
         
         Ok(ExcelFromJson { x4, x1, x5, x6, s1, s2, t1, x12, x13, x14, k1, k8, k9, k15, })
     }
@@ -791,9 +803,9 @@ pub struct ExcelFromJsonMultiRow {
 
 impl ExcelFromJsonMultiRow{
     pub fn new(mut buf: &mut ByteBuf) -> Result<ExcelFromJsonMultiRow, LubanError> {
-        let id = buf.read_int();
-        let x = buf.read_int();
-        let items = {let n0 = std::cmp::min(buf.read_size(), buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(crate::test::TestRow::new(&mut buf)?); } _e0 };
+        let id = buf.try_read_int()?;
+        let x = buf.try_read_int()?;
+        let items = {let n0 = std::cmp::min(buf.try_read_size()?, buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(crate::test::TestRow::new(&mut buf)?); } _e0 };
         
         Ok(ExcelFromJsonMultiRow { id, x, items, })
     }
@@ -810,9 +822,9 @@ pub struct Foo {
 
 impl Foo{
     pub fn new(mut buf: &mut ByteBuf) -> Result<Foo, LubanError> {
-        let y1 = buf.read_int();
-        let y2 = buf.read_int();
-        let y3 = buf.read_int();
+        let y1 = buf.try_read_int()?;
+        let y2 = buf.try_read_int()?;
+        let y3 = buf.try_read_int()?;
         
         Ok(Foo { y1, y2, y3, })
     }
@@ -829,7 +841,7 @@ pub struct H1 {
 impl H1{
     pub fn new(mut buf: &mut ByteBuf) -> Result<H1, LubanError> {
         let y2 = crate::test::H2::new(&mut buf)?;
-        let y3 = buf.read_int();
+        let y3 = buf.try_read_int()?;
         
         Ok(H1 { y2, y3, })
     }
@@ -845,8 +857,8 @@ pub struct H2 {
 
 impl H2{
     pub fn new(mut buf: &mut ByteBuf) -> Result<H2, LubanError> {
-        let z2 = buf.read_int();
-        let z3 = buf.read_int();
+        let z2 = buf.try_read_int()?;
+        let z3 = buf.try_read_int()?;
         
         Ok(H2 { z2, z3, })
     }
@@ -864,10 +876,10 @@ pub struct InnerGroup {
 
 impl InnerGroup{
     pub fn new(mut buf: &mut ByteBuf) -> Result<InnerGroup, LubanError> {
-        let y1 = buf.read_int();
-        let y2 = buf.read_int();
-        let y3 = buf.read_int();
-        let y4 = buf.read_int();
+        let y1 = buf.try_read_int()?;
+        let y2 = buf.try_read_int()?;
+        let y3 = buf.try_read_int()?;
+        let y4 = buf.try_read_int()?;
         
         Ok(InnerGroup { y1, y2, y3, y4, })
     }
@@ -884,7 +896,7 @@ pub struct ItemBase {
 
 impl ItemBase {
     pub fn new(mut buf: &mut ByteBuf) -> Result<std::sync::Arc<AbstractBase>, LubanError> {
-        let type_id = buf.read_int();
+        let type_id = buf.try_read_int()?;
         match type_id {
             crate::test::Item::__ID__ => Ok(std::sync::Arc::new(crate::test::Item::new(buf)?)),
             crate::test::Equipment::__ID__ => Ok(std::sync::Arc::new(crate::test::Equipment::new(buf)?)),
@@ -966,10 +978,10 @@ pub struct Decorator {
 
 impl Decorator{
     pub fn new(mut buf: &mut ByteBuf) -> Result<Decorator, LubanError> {
-        let id = buf.read_int();
-        let name = buf.read_string();
-        let desc = buf.read_string();
-        let duration = buf.read_int();
+        let id = buf.try_read_int()?;
+        let name = buf.try_read_string()?;
+        let desc = buf.try_read_string()?;
+        let duration = buf.try_read_int()?;
         
         Ok(Decorator { id, name, desc, duration, })
     }
@@ -989,11 +1001,11 @@ pub struct Equipment {
 
 impl Equipment{
     pub fn new(mut buf: &mut ByteBuf) -> Result<Equipment, LubanError> {
-        let id = buf.read_int();
-        let name = buf.read_string();
-        let desc = buf.read_string();
-        let attr = buf.read_int().into();
-        let value = buf.read_int();
+        let id = buf.try_read_int()?;
+        let name = buf.try_read_string()?;
+        let desc = buf.try_read_string()?;
+        let attr = buf.try_read_enum()?;
+        let value = buf.try_read_int()?;
         
         Ok(Equipment { id, name, desc, attr, value, })
     }
@@ -1013,11 +1025,11 @@ pub struct Item {
 
 impl Item{
     pub fn new(mut buf: &mut ByteBuf) -> Result<Item, LubanError> {
-        let id = buf.read_int();
-        let name = buf.read_string();
-        let desc = buf.read_string();
-        let num = buf.read_int();
-        let price = buf.read_int();
+        let id = buf.try_read_int()?;
+        let name = buf.try_read_string()?;
+        let desc = buf.try_read_string()?;
+        let num = buf.try_read_int()?;
+        let price = buf.try_read_int()?;
         
         Ok(Item { id, name, desc, num, price, })
     }
@@ -1036,11 +1048,11 @@ pub struct MultiIndexList {
 
 impl MultiIndexList{
     pub fn new(mut buf: &mut ByteBuf) -> Result<MultiIndexList, LubanError> {
-        let id1 = buf.read_int();
-        let id2 = buf.read_long();
-        let id3 = buf.read_string();
-        let num = buf.read_int();
-        let desc = buf.read_string();
+        let id1 = buf.try_read_int()?;
+        let id2 = buf.try_read_long()?;
+        let id3 = buf.try_read_string()?;
+        let num = buf.try_read_int()?;
+        let desc = buf.try_read_string()?;
         
         Ok(MultiIndexList { id1, id2, id3, num, desc, })
     }
@@ -1063,15 +1075,15 @@ pub struct MultiRowRecord {
 
 impl MultiRowRecord{
     pub fn new(mut buf: &mut ByteBuf) -> Result<MultiRowRecord, LubanError> {
-        let id = buf.read_int();
-        let name = buf.read_string();
-        let one_rows = {let n0 = std::cmp::min(buf.read_size(), buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(crate::test::MultiRowType1::new(&mut buf)?); } _e0 };
-        let multi_rows1 = {let n0 = std::cmp::min(buf.read_size(), buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(crate::test::MultiRowType1::new(&mut buf)?); } _e0 };
-        let multi_rows2 = {let n0 = std::cmp::min(buf.read_size(), buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(crate::test::MultiRowType1::new(&mut buf)?); } _e0 };
-        let multi_rows4 = {let n0 = std::cmp::min(buf.read_size(), buf.size()); let mut _e0 = std::collections::HashMap::with_capacity(n0 * 3 / 2);for i0 in 0..n0 { let _k0 = buf.read_int(); let _v0 = crate::test::MultiRowType2::new(&mut buf)?; _e0.insert(_k0, _v0);} _e0 };
-        let multi_rows5 = {let n0 = std::cmp::min(buf.read_size(), buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(crate::test::MultiRowType3::new(&mut buf)?); } _e0 };
-        let multi_rows6 = {let n0 = std::cmp::min(buf.read_size(), buf.size()); let mut _e0 = std::collections::HashMap::with_capacity(n0 * 3 / 2);for i0 in 0..n0 { let _k0 = buf.read_int(); let _v0 = crate::test::MultiRowType2::new(&mut buf)?; _e0.insert(_k0, _v0);} _e0 };
-        let multi_rows7 = {let n0 = std::cmp::min(buf.read_size(), buf.size()); let mut _e0 = std::collections::HashMap::with_capacity(n0 * 3 / 2);for i0 in 0..n0 { let _k0 = buf.read_int(); let _v0 = buf.read_int(); _e0.insert(_k0, _v0);} _e0 };
+        let id = buf.try_read_int()?;
+        let name = buf.try_read_string()?;
+        let one_rows = {let n0 = std::cmp::min(buf.try_read_size()?, buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(crate::test::MultiRowType1::new(&mut buf)?); } _e0 };
+        let multi_rows1 = {let n0 = std::cmp::min(buf.try_read_size()?, buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(crate::test::MultiRowType1::new(&mut buf)?); } _e0 };
+        let multi_rows2 = {let n0 = std::cmp::min(buf.try_read_size()?, buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(crate::test::MultiRowType1::new(&mut buf)?); } _e0 };
+        let multi_rows4 = {let n0 = std::cmp::min(buf.try_read_size()?, buf.size()); let mut _e0 = std::collections::HashMap::with_capacity(n0 * 3 / 2);for i0 in 0..n0 { let _k0 = buf.try_read_int()?; let _v0 = crate::test::MultiRowType2::new(&mut buf)?; _e0.insert(_k0, _v0);} _e0 };
+        let multi_rows5 = {let n0 = std::cmp::min(buf.try_read_size()?, buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(crate::test::MultiRowType3::new(&mut buf)?); } _e0 };
+        let multi_rows6 = {let n0 = std::cmp::min(buf.try_read_size()?, buf.size()); let mut _e0 = std::collections::HashMap::with_capacity(n0 * 3 / 2);for i0 in 0..n0 { let _k0 = buf.try_read_int()?; let _v0 = crate::test::MultiRowType2::new(&mut buf)?; _e0.insert(_k0, _v0);} _e0 };
+        let multi_rows7 = {let n0 = std::cmp::min(buf.try_read_size()?, buf.size()); let mut _e0 = std::collections::HashMap::with_capacity(n0 * 3 / 2);for i0 in 0..n0 { let _k0 = buf.try_read_int()?; let _v0 = buf.try_read_int()?; _e0.insert(_k0, _v0);} _e0 };
         
         Ok(MultiRowRecord { id, name, one_rows, multi_rows1, multi_rows2, multi_rows4, multi_rows5, multi_rows6, multi_rows7, })
     }
@@ -1092,13 +1104,13 @@ pub struct MultiRowTitle {
 
 impl MultiRowTitle{
     pub fn new(mut buf: &mut ByteBuf) -> Result<MultiRowTitle, LubanError> {
-        let id = buf.read_int();
-        let name = buf.read_string();
+        let id = buf.try_read_int()?;
+        let name = buf.try_read_string()?;
         let x1 = crate::test::H1::new(&mut buf)?;
-        let mut x2_0 = if buf.read_bool() { Some(crate::test::H2::new(&mut buf)?) } else { None };
-        let x2 = {let n0 = std::cmp::min(buf.read_size(), buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(crate::test::H2::new(&mut buf)?); } _e0 };
-        let x3 = {let n0 = std::cmp::min(buf.read_size(), buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(crate::test::H2::new(&mut buf)?); } _e0 };
-        let x4 = {let n0 = std::cmp::min(buf.read_size(), buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(crate::test::H2::new(&mut buf)?); } _e0 };
+        let mut x2_0 = if buf.try_read_bool()? { Some(crate::test::H2::new(&mut buf)?) } else { None };
+        let x2 = {let n0 = std::cmp::min(buf.try_read_size()?, buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(crate::test::H2::new(&mut buf)?); } _e0 };
+        let x3 = {let n0 = std::cmp::min(buf.try_read_size()?, buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(crate::test::H2::new(&mut buf)?); } _e0 };
+        let x4 = {let n0 = std::cmp::min(buf.try_read_size()?, buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(crate::test::H2::new(&mut buf)?); } _e0 };
         
         Ok(MultiRowTitle { id, name, x1, x2_0, x2, x3, x4, })
     }
@@ -1114,8 +1126,8 @@ pub struct MultiRowType1 {
 
 impl MultiRowType1{
     pub fn new(mut buf: &mut ByteBuf) -> Result<MultiRowType1, LubanError> {
-        let id = buf.read_int();
-        let x = buf.read_int();
+        let id = buf.try_read_int()?;
+        let x = buf.try_read_int()?;
         
         Ok(MultiRowType1 { id, x, })
     }
@@ -1132,9 +1144,9 @@ pub struct MultiRowType2 {
 
 impl MultiRowType2{
     pub fn new(mut buf: &mut ByteBuf) -> Result<MultiRowType2, LubanError> {
-        let id = buf.read_int();
-        let x = buf.read_int();
-        let y = buf.read_float();
+        let id = buf.try_read_int()?;
+        let x = buf.try_read_int()?;
+        let y = buf.try_read_float()?;
         
         Ok(MultiRowType2 { id, x, y, })
     }
@@ -1150,8 +1162,8 @@ pub struct MultiRowType3 {
 
 impl MultiRowType3{
     pub fn new(mut buf: &mut ByteBuf) -> Result<MultiRowType3, LubanError> {
-        let id = buf.read_int();
-        let items = {let n0 = std::cmp::min(buf.read_size(), buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(crate::test::MultiRowType1::new(&mut buf)?); } _e0 };
+        let id = buf.try_read_int()?;
+        let items = {let n0 = std::cmp::min(buf.try_read_size()?, buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(crate::test::MultiRowType1::new(&mut buf)?); } _e0 };
         
         Ok(MultiRowType3 { id, items, })
     }
@@ -1170,11 +1182,11 @@ pub struct MultiUnionIndexList {
 
 impl MultiUnionIndexList{
     pub fn new(mut buf: &mut ByteBuf) -> Result<MultiUnionIndexList, LubanError> {
-        let id1 = buf.read_int();
-        let id2 = buf.read_long();
-        let id3 = buf.read_string();
-        let num = buf.read_int();
-        let desc = buf.read_string();
+        let id1 = buf.try_read_int()?;
+        let id2 = buf.try_read_long()?;
+        let id3 = buf.try_read_string()?;
+        let num = buf.try_read_int()?;
+        let desc = buf.try_read_string()?;
         
         Ok(MultiUnionIndexList { id1, id2, id3, num, desc, })
     }
@@ -1190,8 +1202,8 @@ pub struct NotIndexList {
 
 impl NotIndexList{
     pub fn new(mut buf: &mut ByteBuf) -> Result<NotIndexList, LubanError> {
-        let x = buf.read_int();
-        let y = buf.read_int();
+        let x = buf.try_read_int()?;
+        let y = buf.try_read_int()?;
         
         Ok(NotIndexList { x, y, })
     }
@@ -1207,8 +1219,8 @@ pub struct Path {
 
 impl Path{
     pub fn new(mut buf: &mut ByteBuf) -> Result<Path, LubanError> {
-        let id = buf.read_int();
-        let res = buf.read_string();
+        let id = buf.try_read_int()?;
+        let res = buf.try_read_string()?;
         
         Ok(Path { id, res, })
     }
@@ -1223,7 +1235,7 @@ pub struct RefDynamicBase {
 
 impl RefDynamicBase {
     pub fn new(mut buf: &mut ByteBuf) -> Result<std::sync::Arc<AbstractBase>, LubanError> {
-        let type_id = buf.read_int();
+        let type_id = buf.try_read_int()?;
         match type_id {
             crate::test::RefBean::__ID__ => Ok(std::sync::Arc::new(crate::test::RefBean::new(buf)?)),
             _ => Err(LubanError::Bean(format!("Invalid type for RefDynamicBase:{}", type_id)))
@@ -1261,8 +1273,8 @@ pub struct RefBean {
 
 impl RefBean{
     pub fn new(mut buf: &mut ByteBuf) -> Result<RefBean, LubanError> {
-        let x = buf.read_int();
-        let arr = {let n0 = std::cmp::min(buf.read_size(), buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(buf.read_int()); } _e0 };
+        let x = buf.try_read_int()?;
+        let arr = {let n0 = std::cmp::min(buf.try_read_size()?, buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(buf.try_read_int()?); } _e0 };
         
         Ok(RefBean { x, arr, })
     }
@@ -1279,9 +1291,9 @@ pub struct SepBean1 {
 
 impl SepBean1{
     pub fn new(mut buf: &mut ByteBuf) -> Result<SepBean1, LubanError> {
-        let a = buf.read_int();
-        let b = buf.read_int();
-        let c = buf.read_string();
+        let a = buf.try_read_int()?;
+        let b = buf.try_read_int()?;
+        let c = buf.try_read_string()?;
         
         Ok(SepBean1 { a, b, c, })
     }
@@ -1298,9 +1310,9 @@ pub struct SepVector {
 
 impl SepVector{
     pub fn new(mut buf: &mut ByteBuf) -> Result<SepVector, LubanError> {
-        let x = buf.read_int();
-        let y = buf.read_int();
-        let z = buf.read_int();
+        let x = buf.try_read_int()?;
+        let y = buf.try_read_int()?;
+        let z = buf.try_read_int()?;
         
         Ok(SepVector { x, y, z, })
     }
@@ -1314,7 +1326,7 @@ pub struct Shape {
 
 impl Shape {
     pub fn new(mut buf: &mut ByteBuf) -> Result<std::sync::Arc<AbstractBase>, LubanError> {
-        let type_id = buf.read_int();
+        let type_id = buf.try_read_int()?;
         match type_id {
             crate::test::Circle::__ID__ => Ok(std::sync::Arc::new(crate::test::Circle::new(buf)?)),
             crate::test2::Rectangle::__ID__ => Ok(std::sync::Arc::new(crate::test2::Rectangle::new(buf)?)),
@@ -1356,7 +1368,7 @@ pub struct Circle {
 
 impl Circle{
     pub fn new(mut buf: &mut ByteBuf) -> Result<Circle, LubanError> {
-        let radius = buf.read_float();
+        let radius = buf.try_read_float()?;
         
         Ok(Circle { radius, })
     }
@@ -1372,8 +1384,8 @@ pub struct Test3 {
 
 impl Test3{
     pub fn new(mut buf: &mut ByteBuf) -> Result<Test3, LubanError> {
-        let x = buf.read_int();
-        let y = buf.read_int();
+        let x = buf.try_read_int()?;
+        let y = buf.try_read_int()?;
         
         Ok(Test3 { x, y, })
     }
@@ -1389,8 +1401,8 @@ pub struct TestBeRef {
 
 impl TestBeRef{
     pub fn new(mut buf: &mut ByteBuf) -> Result<TestBeRef, LubanError> {
-        let id = buf.read_int();
-        let count = buf.read_int();
+        let id = buf.try_read_int()?;
+        let count = buf.try_read_int()?;
         
         Ok(TestBeRef { id, count, })
     }
@@ -1412,10 +1424,10 @@ pub struct TestExcelBean1 {
 
 impl TestExcelBean1{
     pub fn new(mut buf: &mut ByteBuf) -> Result<TestExcelBean1, LubanError> {
-        let x1 = buf.read_int();
-        let x2 = buf.read_string();
-        let x3 = buf.read_int();
-        let x4 = buf.read_float();
+        let x1 = buf.try_read_int()?;
+        let x2 = buf.try_read_string()?;
+        let x3 = buf.try_read_int()?;
+        let x4 = buf.try_read_float()?;
         
         Ok(TestExcelBean1 { x1, x2, x3, x4, })
     }
@@ -1435,9 +1447,9 @@ pub struct TestExcelBean2 {
 
 impl TestExcelBean2{
     pub fn new(mut buf: &mut ByteBuf) -> Result<TestExcelBean2, LubanError> {
-        let y1 = buf.read_int();
-        let y2 = buf.read_string();
-        let y3 = buf.read_float();
+        let y1 = buf.try_read_int()?;
+        let y2 = buf.try_read_string()?;
+        let y3 = buf.try_read_float()?;
         
         Ok(TestExcelBean2 { y1, y2, y3, })
     }
@@ -1453,8 +1465,8 @@ pub struct TestFieldAlias {
 
 impl TestFieldAlias{
     pub fn new(mut buf: &mut ByteBuf) -> Result<TestFieldAlias, LubanError> {
-        let id = buf.read_int();
-        let name = buf.read_string();
+        let id = buf.try_read_int()?;
+        let name = buf.try_read_string()?;
         
         Ok(TestFieldAlias { id, name, })
     }
@@ -1470,8 +1482,8 @@ pub struct TestFieldVariant {
 
 impl TestFieldVariant{
     pub fn new(mut buf: &mut ByteBuf) -> Result<TestFieldVariant, LubanError> {
-        let id = buf.read_int();
-        let name = buf.read_string();
+        let id = buf.try_read_int()?;
+        let name = buf.try_read_string()?;
         
         Ok(TestFieldVariant { id, name, })
     }
@@ -1487,8 +1499,8 @@ pub struct TestFieldVariant2 {
 
 impl TestFieldVariant2{
     pub fn new(mut buf: &mut ByteBuf) -> Result<TestFieldVariant2, LubanError> {
-        let id = buf.read_int();
-        let name = buf.read_string();
+        let id = buf.try_read_int()?;
+        let name = buf.try_read_string()?;
         
         Ok(TestFieldVariant2 { id, name, })
     }
@@ -1504,8 +1516,8 @@ pub struct TestGlobal {
 
 impl TestGlobal{
     pub fn new(mut buf: &mut ByteBuf) -> Result<TestGlobal, LubanError> {
-        let unlock_equip = buf.read_int();
-        let unlock_hero = buf.read_int();
+        let unlock_equip = buf.try_read_int()?;
+        let unlock_hero = buf.try_read_int()?;
         
         Ok(TestGlobal { unlock_equip, unlock_hero, })
     }
@@ -1521,8 +1533,8 @@ pub struct TestIndex {
 
 impl TestIndex{
     pub fn new(mut buf: &mut ByteBuf) -> Result<TestIndex, LubanError> {
-        let id = buf.read_int();
-        let eles = {let n0 = std::cmp::min(buf.read_size(), buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(crate::test::DemoType1::new(&mut buf)?); } _e0 };
+        let id = buf.try_read_int()?;
+        let eles = {let n0 = std::cmp::min(buf.try_read_size()?, buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(crate::test::DemoType1::new(&mut buf)?); } _e0 };
         
         Ok(TestIndex { id, eles, })
     }
@@ -1541,11 +1553,11 @@ pub struct TestMap {
 
 impl TestMap{
     pub fn new(mut buf: &mut ByteBuf) -> Result<TestMap, LubanError> {
-        let id = buf.read_int();
-        let x1 = {let n0 = std::cmp::min(buf.read_size(), buf.size()); let mut _e0 = std::collections::HashMap::with_capacity(n0 * 3 / 2);for i0 in 0..n0 { let _k0 = buf.read_int(); let _v0 = buf.read_int(); _e0.insert(_k0, _v0);} _e0 };
-        let x2 = {let n0 = std::cmp::min(buf.read_size(), buf.size()); let mut _e0 = std::collections::HashMap::with_capacity(n0 * 3 / 2);for i0 in 0..n0 { let _k0 = buf.read_long(); let _v0 = buf.read_int(); _e0.insert(_k0, _v0);} _e0 };
-        let x3 = {let n0 = std::cmp::min(buf.read_size(), buf.size()); let mut _e0 = std::collections::HashMap::with_capacity(n0 * 3 / 2);for i0 in 0..n0 { let _k0 = buf.read_string(); let _v0 = buf.read_int(); _e0.insert(_k0, _v0);} _e0 };
-        let x4 = {let n0 = std::cmp::min(buf.read_size(), buf.size()); let mut _e0 = std::collections::HashMap::with_capacity(n0 * 3 / 2);for i0 in 0..n0 { let _k0 = buf.read_int().into(); let _v0 = buf.read_int(); _e0.insert(_k0, _v0);} _e0 };
+        let id = buf.try_read_int()?;
+        let x1 = {let n0 = std::cmp::min(buf.try_read_size()?, buf.size()); let mut _e0 = std::collections::HashMap::with_capacity(n0 * 3 / 2);for i0 in 0..n0 { let _k0 = buf.try_read_int()?; let _v0 = buf.try_read_int()?; _e0.insert(_k0, _v0);} _e0 };
+        let x2 = {let n0 = std::cmp::min(buf.try_read_size()?, buf.size()); let mut _e0 = std::collections::HashMap::with_capacity(n0 * 3 / 2);for i0 in 0..n0 { let _k0 = buf.try_read_long()?; let _v0 = buf.try_read_int()?; _e0.insert(_k0, _v0);} _e0 };
+        let x3 = {let n0 = std::cmp::min(buf.try_read_size()?, buf.size()); let mut _e0 = std::collections::HashMap::with_capacity(n0 * 3 / 2);for i0 in 0..n0 { let _k0 = buf.try_read_string()?; let _v0 = buf.try_read_int()?; _e0.insert(_k0, _v0);} _e0 };
+        let x4 = {let n0 = std::cmp::min(buf.try_read_size()?, buf.size()); let mut _e0 = std::collections::HashMap::with_capacity(n0 * 3 / 2);for i0 in 0..n0 { let _k0 = buf.try_read_enum()?; let _v0 = buf.try_read_int()?; _e0.insert(_k0, _v0);} _e0 };
         
         Ok(TestMap { id, x1, x2, x3, x4, })
     }
@@ -1562,8 +1574,8 @@ pub struct TestMapper {
 
 impl TestMapper{
     pub fn new(mut buf: &mut ByteBuf) -> Result<TestMapper, LubanError> {
-        let id = buf.read_int();
-        let audio_type = buf.read_int().into();
+        let id = buf.try_read_int()?;
+        let audio_type = buf.try_read_enum()?;
         let v2 = crate::vec2::new(&mut buf)?;
         
         Ok(TestMapper { id, audio_type, v2, })
@@ -1582,7 +1594,7 @@ pub struct TestMultiColumn {
 
 impl TestMultiColumn{
     pub fn new(mut buf: &mut ByteBuf) -> Result<TestMultiColumn, LubanError> {
-        let id = buf.read_int();
+        let id = buf.try_read_int()?;
         let a = crate::test::Foo::new(&mut buf)?;
         let b = crate::test::Foo::new(&mut buf)?;
         let c = crate::test::Foo::new(&mut buf)?;
@@ -1606,13 +1618,13 @@ pub struct TestNull {
 
 impl TestNull{
     pub fn new(mut buf: &mut ByteBuf) -> Result<TestNull, LubanError> {
-        let id = buf.read_int();
-        let mut x1 = if buf.read_bool() { Some(buf.read_int()) } else { None };
-        let mut x2 = if buf.read_bool() { Some(buf.read_int().into()) } else { None };
-        let mut x3 = if buf.read_bool() { Some(crate::test::DemoType1::new(&mut buf)?) } else { None };
-        let mut x4 = if buf.read_bool() { Some(crate::test::DemoDynamic::new(&mut buf)?) } else { None };
-        let mut s1 = if buf.read_bool() { Some(buf.read_string()) } else { None };
-        let mut s2 = if buf.read_bool() { Some(buf.read_string()) } else { None };
+        let id = buf.try_read_int()?;
+        let mut x1 = if buf.try_read_bool()? { Some(buf.try_read_int()?) } else { None };
+        let mut x2 = if buf.try_read_bool()? { Some(buf.try_read_enum()?) } else { None };
+        let mut x3 = if buf.try_read_bool()? { Some(crate::test::DemoType1::new(&mut buf)?) } else { None };
+        let mut x4 = if buf.try_read_bool()? { Some(crate::test::DemoDynamic::new(&mut buf)?) } else { None };
+        let mut s1 = if buf.try_read_bool()? { Some(buf.try_read_string()?) } else { None };
+        let mut s2 = if buf.try_read_bool()? { Some(buf.try_read_string()?) } else { None };
         
         Ok(TestNull { id, x1, x2, x3, x4, s1, s2, })
     }
@@ -1639,19 +1651,19 @@ pub struct TestRange {
 
 impl TestRange{
     pub fn new(mut buf: &mut ByteBuf) -> Result<TestRange, LubanError> {
-        let id = buf.read_int();
-        let f1 = buf.read_float();
-        let f2 = buf.read_float();
-        let d1 = buf.read_double();
-        let d2 = buf.read_double();
-        let i1 = buf.read_int();
-        let i2 = buf.read_int();
-        let i3 = buf.read_int();
-        let i4 = buf.read_int();
-        let l1 = buf.read_long();
-        let l2 = buf.read_long();
-        let l3 = buf.read_long();
-        let l4 = buf.read_long();
+        let id = buf.try_read_int()?;
+        let f1 = buf.try_read_float()?;
+        let f2 = buf.try_read_float()?;
+        let d1 = buf.try_read_double()?;
+        let d2 = buf.try_read_double()?;
+        let i1 = buf.try_read_int()?;
+        let i2 = buf.try_read_int()?;
+        let i3 = buf.try_read_int()?;
+        let i4 = buf.try_read_int()?;
+        let l1 = buf.try_read_long()?;
+        let l2 = buf.try_read_long()?;
+        let l3 = buf.try_read_long()?;
+        let l4 = buf.try_read_long()?;
         
         Ok(TestRange { id, f1, f2, d1, d2, i1, i2, i3, i4, l1, l2, l3, l4, })
     }
@@ -1686,26 +1698,26 @@ pub struct TestRef {
 
 impl TestRef{
     pub fn new(mut buf: &mut ByteBuf) -> Result<TestRef, LubanError> {
-        let id = buf.read_int();
-        let x1 = buf.read_int();
-        let x1_2 = buf.read_int();
-        let x2 = buf.read_int();
-        let x3 = buf.read_int();
-        let x4 = buf.read_int();
-        let a1 = {let n0 = std::cmp::min(buf.read_size(), buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(buf.read_int()); } _e0 };
-        let a2 = {let n0 = std::cmp::min(buf.read_size(), buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(buf.read_int()); } _e0 };
-        let b1 = {let n0 = std::cmp::min(buf.read_size(), buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(buf.read_int()); } _e0 };
-        let b2 = {let n0 = std::cmp::min(buf.read_size(), buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(buf.read_int()); } _e0 };
-        let c1 = {let n0 = std::cmp::min(buf.read_size(), buf.size());let mut _e0 = std::collections::HashSet::default(); for i0 in 0..n0 { _e0.insert(buf.read_int()); } _e0 };
-        let c2 = {let n0 = std::cmp::min(buf.read_size(), buf.size());let mut _e0 = std::collections::HashSet::default(); for i0 in 0..n0 { _e0.insert(buf.read_int()); } _e0 };
-        let d1 = {let n0 = std::cmp::min(buf.read_size(), buf.size()); let mut _e0 = std::collections::HashMap::with_capacity(n0 * 3 / 2);for i0 in 0..n0 { let _k0 = buf.read_int(); let _v0 = buf.read_int(); _e0.insert(_k0, _v0);} _e0 };
-        let d2 = {let n0 = std::cmp::min(buf.read_size(), buf.size()); let mut _e0 = std::collections::HashMap::with_capacity(n0 * 3 / 2);for i0 in 0..n0 { let _k0 = buf.read_int(); let _v0 = buf.read_int(); _e0.insert(_k0, _v0);} _e0 };
-        let e1 = buf.read_int();
-        let e2 = buf.read_long();
-        let e3 = buf.read_string();
-        let f1 = buf.read_int();
-        let f2 = buf.read_long();
-        let f3 = buf.read_string();
+        let id = buf.try_read_int()?;
+        let x1 = buf.try_read_int()?;
+        let x1_2 = buf.try_read_int()?;
+        let x2 = buf.try_read_int()?;
+        let x3 = buf.try_read_int()?;
+        let x4 = buf.try_read_int()?;
+        let a1 = {let n0 = std::cmp::min(buf.try_read_size()?, buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(buf.try_read_int()?); } _e0 };
+        let a2 = {let n0 = std::cmp::min(buf.try_read_size()?, buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(buf.try_read_int()?); } _e0 };
+        let b1 = {let n0 = std::cmp::min(buf.try_read_size()?, buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(buf.try_read_int()?); } _e0 };
+        let b2 = {let n0 = std::cmp::min(buf.try_read_size()?, buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(buf.try_read_int()?); } _e0 };
+        let c1 = {let n0 = std::cmp::min(buf.try_read_size()?, buf.size());let mut _e0 = std::collections::HashSet::default(); for i0 in 0..n0 { _e0.insert(buf.try_read_int()?); } _e0 };
+        let c2 = {let n0 = std::cmp::min(buf.try_read_size()?, buf.size());let mut _e0 = std::collections::HashSet::default(); for i0 in 0..n0 { _e0.insert(buf.try_read_int()?); } _e0 };
+        let d1 = {let n0 = std::cmp::min(buf.try_read_size()?, buf.size()); let mut _e0 = std::collections::HashMap::with_capacity(n0 * 3 / 2);for i0 in 0..n0 { let _k0 = buf.try_read_int()?; let _v0 = buf.try_read_int()?; _e0.insert(_k0, _v0);} _e0 };
+        let d2 = {let n0 = std::cmp::min(buf.try_read_size()?, buf.size()); let mut _e0 = std::collections::HashMap::with_capacity(n0 * 3 / 2);for i0 in 0..n0 { let _k0 = buf.try_read_int()?; let _v0 = buf.try_read_int()?; _e0.insert(_k0, _v0);} _e0 };
+        let e1 = buf.try_read_int()?;
+        let e2 = buf.try_read_long()?;
+        let e3 = buf.try_read_string()?;
+        let f1 = buf.try_read_int()?;
+        let f2 = buf.try_read_long()?;
+        let f3 = buf.try_read_string()?;
         let s1 = crate::test::RefDynamicBase::new(&mut buf)?;
         
         Ok(TestRef { id, x1, x1_2, x2, x3, x4, a1, a2, b1, b2, c1, c2, d1, d2, e1, e2, e3, f1, f2, f3, s1, })
@@ -1725,11 +1737,11 @@ pub struct TestRow {
 
 impl TestRow{
     pub fn new(mut buf: &mut ByteBuf) -> Result<TestRow, LubanError> {
-        let x = buf.read_int();
-        let y = buf.read_bool();
-        let z = buf.read_string();
+        let x = buf.try_read_int()?;
+        let y = buf.try_read_bool()?;
+        let z = buf.try_read_string()?;
         let a = crate::test::Test3::new(&mut buf)?;
-        let b = {let n0 = std::cmp::min(buf.read_size(), buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(buf.read_int()); } _e0 };
+        let b = {let n0 = std::cmp::min(buf.try_read_size()?, buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(buf.try_read_int()?); } _e0 };
         
         Ok(TestRow { x, y, z, a, b, })
     }
@@ -1750,10 +1762,10 @@ pub struct TestScriptableObject {
 
 impl TestScriptableObject{
     pub fn new(mut buf: &mut ByteBuf) -> Result<TestScriptableObject, LubanError> {
-        let id = buf.read_int();
-        let desc = buf.read_string();
-        let rate = buf.read_float();
-        let num = buf.read_int();
+        let id = buf.try_read_int()?;
+        let desc = buf.try_read_string()?;
+        let rate = buf.try_read_float()?;
+        let num = buf.try_read_int()?;
         let v2 = crate::vec2::new(&mut buf)?;
         let v3 = crate::vec3::new(&mut buf)?;
         let v4 = crate::vec4::new(&mut buf)?;
@@ -1776,12 +1788,12 @@ pub struct TestSet {
 
 impl TestSet{
     pub fn new(mut buf: &mut ByteBuf) -> Result<TestSet, LubanError> {
-        let id = buf.read_int();
-        let x0 = buf.read_string();
-        let x1 = {let n0 = std::cmp::min(buf.read_size(), buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(buf.read_int()); } _e0 };
-        let x2 = {let n0 = std::cmp::min(buf.read_size(), buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(buf.read_long()); } _e0 };
-        let x3 = {let n0 = std::cmp::min(buf.read_size(), buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(buf.read_string()); } _e0 };
-        let x4 = {let n0 = std::cmp::min(buf.read_size(), buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(buf.read_int().into()); } _e0 };
+        let id = buf.try_read_int()?;
+        let x0 = buf.try_read_string()?;
+        let x1 = {let n0 = std::cmp::min(buf.try_read_size()?, buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(buf.try_read_int()?); } _e0 };
+        let x2 = {let n0 = std::cmp::min(buf.try_read_size()?, buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(buf.try_read_long()?); } _e0 };
+        let x3 = {let n0 = std::cmp::min(buf.try_read_size()?, buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(buf.try_read_string()?); } _e0 };
+        let x4 = {let n0 = std::cmp::min(buf.try_read_size()?, buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(buf.try_read_enum()?); } _e0 };
         
         Ok(TestSet { id, x0, x1, x2, x3, x4, })
     }
@@ -1800,11 +1812,11 @@ pub struct TestSize {
 
 impl TestSize{
     pub fn new(mut buf: &mut ByteBuf) -> Result<TestSize, LubanError> {
-        let id = buf.read_int();
-        let x1 = {let n0 = std::cmp::min(buf.read_size(), buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(buf.read_int()); } _e0 };
-        let x2 = {let n0 = std::cmp::min(buf.read_size(), buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(buf.read_int()); } _e0 };
-        let x3 = {let n0 = std::cmp::min(buf.read_size(), buf.size());let mut _e0 = std::collections::HashSet::default(); for i0 in 0..n0 { _e0.insert(buf.read_int()); } _e0 };
-        let x4 = {let n0 = std::cmp::min(buf.read_size(), buf.size()); let mut _e0 = std::collections::HashMap::with_capacity(n0 * 3 / 2);for i0 in 0..n0 { let _k0 = buf.read_int(); let _v0 = buf.read_int(); _e0.insert(_k0, _v0);} _e0 };
+        let id = buf.try_read_int()?;
+        let x1 = {let n0 = std::cmp::min(buf.try_read_size()?, buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(buf.try_read_int()?); } _e0 };
+        let x2 = {let n0 = std::cmp::min(buf.try_read_size()?, buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(buf.try_read_int()?); } _e0 };
+        let x3 = {let n0 = std::cmp::min(buf.try_read_size()?, buf.size());let mut _e0 = std::collections::HashSet::default(); for i0 in 0..n0 { _e0.insert(buf.try_read_int()?); } _e0 };
+        let x4 = {let n0 = std::cmp::min(buf.try_read_size()?, buf.size()); let mut _e0 = std::collections::HashMap::with_capacity(n0 * 3 / 2);for i0 in 0..n0 { let _k0 = buf.try_read_int()?; let _v0 = buf.try_read_int()?; _e0.insert(_k0, _v0);} _e0 };
         
         Ok(TestSize { id, x1, x2, x3, x4, })
     }
@@ -1823,9 +1835,9 @@ pub struct TestString {
 
 impl TestString{
     pub fn new(mut buf: &mut ByteBuf) -> Result<TestString, LubanError> {
-        let id = buf.read_string();
-        let s1 = buf.read_string();
-        let s2 = buf.read_string();
+        let id = buf.try_read_string()?;
+        let s1 = buf.try_read_string()?;
+        let s2 = buf.try_read_string()?;
         let cs1 = crate::test::CompactString::new(&mut buf)?;
         let cs2 = crate::test::CompactString::new(&mut buf)?;
         
@@ -1856,21 +1868,21 @@ pub struct TestUeType {
 
 impl TestUeType{
     pub fn new(mut buf: &mut ByteBuf) -> Result<TestUeType, LubanError> {
-        let x1 = buf.read_bool();
-        let x2 = buf.read_byte();
-        let x3 = buf.read_short();
-        let x4 = buf.read_int();
-        let x5 = buf.read_long();
-        let x6 = buf.read_float();
-        let x10 = buf.read_string();
+        let x1 = buf.try_read_bool()?;
+        let x2 = buf.try_read_byte()?;
+        let x3 = buf.try_read_short()?;
+        let x4 = buf.try_read_int()?;
+        let x5 = buf.try_read_long()?;
+        let x6 = buf.try_read_float()?;
+        let x10 = buf.try_read_string()?;
         let x12 = crate::test::DemoType1::new(&mut buf)?;
-        let x13 = buf.read_int().into();
-        let t1 = buf.read_ulong();
-        let k1 = {let n0 = std::cmp::min(buf.read_size(), buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(buf.read_int()); } _e0 };
-        let k2 = {let n0 = std::cmp::min(buf.read_size(), buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(buf.read_int()); } _e0 };
-        let k5 = {let n0 = std::cmp::min(buf.read_size(), buf.size());let mut _e0 = std::collections::HashSet::default(); for i0 in 0..n0 { _e0.insert(buf.read_int()); } _e0 };
-        let k8 = {let n0 = std::cmp::min(buf.read_size(), buf.size()); let mut _e0 = std::collections::HashMap::with_capacity(n0 * 3 / 2);for i0 in 0..n0 { let _k0 = buf.read_int(); let _v0 = buf.read_int(); _e0.insert(_k0, _v0);} _e0 };
-        let k9 = {let n0 = std::cmp::min(buf.read_size(), buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(crate::test::DemoE2::new(&mut buf)?); } _e0 };
+        let x13 = buf.try_read_enum()?;
+        let t1 = buf.try_read_ulong()?;
+        let k1 = {let n0 = std::cmp::min(buf.try_read_size()?, buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(buf.try_read_int()?); } _e0 };
+        let k2 = {let n0 = std::cmp::min(buf.try_read_size()?, buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(buf.try_read_int()?); } _e0 };
+        let k5 = {let n0 = std::cmp::min(buf.try_read_size()?, buf.size());let mut _e0 = std::collections::HashSet::default(); for i0 in 0..n0 { _e0.insert(buf.try_read_int()?); } _e0 };
+        let k8 = {let n0 = std::cmp::min(buf.try_read_size()?, buf.size()); let mut _e0 = std::collections::HashMap::with_capacity(n0 * 3 / 2);for i0 in 0..n0 { let _k0 = buf.try_read_int()?; let _v0 = buf.try_read_int()?; _e0.insert(_k0, _v0);} _e0 };
+        let k9 = {let n0 = std::cmp::min(buf.try_read_size()?, buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(crate::test::DemoE2::new(&mut buf)?); } _e0 };
         
         Ok(TestUeType { x1, x2, x3, x4, x5, x6, x10, x12, x13, t1, k1, k2, k5, k8, k9, })
     }
@@ -1890,7 +1902,7 @@ impl TbFullTypes {
         let mut data_map: std::collections::HashMap<i32, std::sync::Arc<crate::test::DemoType2>> = Default::default();
         let mut data_list: Vec<std::sync::Arc<crate::test::DemoType2>> = vec![];
 
-        for x in (0..buf.read_size()).rev() {
+        for x in (0..buf.try_read_size()?).rev() {
             let row = std::sync::Arc::new(crate::test::DemoType2::new(&mut buf)?);
             data_list.push(row.clone());
             data_map.insert(row.x4.clone(), row.clone());
@@ -1920,7 +1932,7 @@ pub struct TbSingleton {
 
 impl TbSingleton {
     pub fn new(mut buf: ByteBuf) -> Result<std::sync::Arc<TbSingleton>, LubanError> {
-        let n = buf.read_size();
+        let n = buf.try_read_size()?;
         if n != 1 { return Err(LubanError::Table(format!("table mode=one, but size != 1"))); }
         let data = crate::test::DemoSingletonType::new(&mut buf)?;
         Ok(std::sync::Arc::new(TbSingleton { data }))
@@ -1937,7 +1949,7 @@ impl TbNotIndexList {
     pub fn new(mut buf: ByteBuf) -> Result<std::sync::Arc<TbNotIndexList>, LubanError> {
         let mut data_list: Vec<std::sync::Arc<crate::test::NotIndexList>> = vec![];
 
-        for x in (0..buf.read_size()).rev() {
+        for x in (0..buf.try_read_size()?).rev() {
             let row = std::sync::Arc::new(crate::test::NotIndexList::new(&mut buf)?);
             data_list.push(row.clone());
         }
@@ -1960,7 +1972,7 @@ impl TbMultiUnionIndexList {
     pub fn new(mut buf: ByteBuf) -> Result<std::sync::Arc<TbMultiUnionIndexList>, LubanError> {
         let mut data_list: Vec<std::sync::Arc<crate::test::MultiUnionIndexList>> = vec![];
 
-        for x in (0..buf.read_size()).rev() {
+        for x in (0..buf.try_read_size()?).rev() {
             let row = std::sync::Arc::new(crate::test::MultiUnionIndexList::new(&mut buf)?);
             data_list.push(row.clone());
         }
@@ -1993,7 +2005,7 @@ impl TbMultiIndexList {
     pub fn new(mut buf: ByteBuf) -> Result<std::sync::Arc<TbMultiIndexList>, LubanError> {
         let mut data_list: Vec<std::sync::Arc<crate::test::MultiIndexList>> = vec![];
 
-        for x in (0..buf.read_size()).rev() {
+        for x in (0..buf.try_read_size()?).rev() {
             let row = std::sync::Arc::new(crate::test::MultiIndexList::new(&mut buf)?);
             data_list.push(row.clone());
         }
@@ -2037,7 +2049,7 @@ impl TbDataFromMisc {
         let mut data_map: std::collections::HashMap<i32, std::sync::Arc<crate::test::DemoType2>> = Default::default();
         let mut data_list: Vec<std::sync::Arc<crate::test::DemoType2>> = vec![];
 
-        for x in (0..buf.read_size()).rev() {
+        for x in (0..buf.try_read_size()?).rev() {
             let row = std::sync::Arc::new(crate::test::DemoType2::new(&mut buf)?);
             data_list.push(row.clone());
             data_map.insert(row.x4.clone(), row.clone());
@@ -2071,7 +2083,7 @@ impl TbMultiRowRecord {
         let mut data_map: std::collections::HashMap<i32, std::sync::Arc<crate::test::MultiRowRecord>> = Default::default();
         let mut data_list: Vec<std::sync::Arc<crate::test::MultiRowRecord>> = vec![];
 
-        for x in (0..buf.read_size()).rev() {
+        for x in (0..buf.try_read_size()?).rev() {
             let row = std::sync::Arc::new(crate::test::MultiRowRecord::new(&mut buf)?);
             data_list.push(row.clone());
             data_map.insert(row.id.clone(), row.clone());
@@ -2105,7 +2117,7 @@ impl TbTestMultiColumn {
         let mut data_map: std::collections::HashMap<i32, std::sync::Arc<crate::test::TestMultiColumn>> = Default::default();
         let mut data_list: Vec<std::sync::Arc<crate::test::TestMultiColumn>> = vec![];
 
-        for x in (0..buf.read_size()).rev() {
+        for x in (0..buf.try_read_size()?).rev() {
             let row = std::sync::Arc::new(crate::test::TestMultiColumn::new(&mut buf)?);
             data_list.push(row.clone());
             data_map.insert(row.id.clone(), row.clone());
@@ -2139,7 +2151,7 @@ impl TbMultiRowTitle {
         let mut data_map: std::collections::HashMap<i32, std::sync::Arc<crate::test::MultiRowTitle>> = Default::default();
         let mut data_list: Vec<std::sync::Arc<crate::test::MultiRowTitle>> = vec![];
 
-        for x in (0..buf.read_size()).rev() {
+        for x in (0..buf.try_read_size()?).rev() {
             let row = std::sync::Arc::new(crate::test::MultiRowTitle::new(&mut buf)?);
             data_list.push(row.clone());
             data_map.insert(row.id.clone(), row.clone());
@@ -2173,7 +2185,7 @@ impl TbTestNull {
         let mut data_map: std::collections::HashMap<i32, std::sync::Arc<crate::test::TestNull>> = Default::default();
         let mut data_list: Vec<std::sync::Arc<crate::test::TestNull>> = vec![];
 
-        for x in (0..buf.read_size()).rev() {
+        for x in (0..buf.try_read_size()?).rev() {
             let row = std::sync::Arc::new(crate::test::TestNull::new(&mut buf)?);
             data_list.push(row.clone());
             data_map.insert(row.id.clone(), row.clone());
@@ -2207,7 +2219,7 @@ impl TbDemoPrimitive {
         let mut data_map: std::collections::HashMap<i32, std::sync::Arc<crate::test::DemoPrimitiveTypesTable>> = Default::default();
         let mut data_list: Vec<std::sync::Arc<crate::test::DemoPrimitiveTypesTable>> = vec![];
 
-        for x in (0..buf.read_size()).rev() {
+        for x in (0..buf.try_read_size()?).rev() {
             let row = std::sync::Arc::new(crate::test::DemoPrimitiveTypesTable::new(&mut buf)?);
             data_list.push(row.clone());
             data_map.insert(row.x4.clone(), row.clone());
@@ -2241,7 +2253,7 @@ impl TbTestString {
         let mut data_map: std::collections::HashMap<String, std::sync::Arc<crate::test::TestString>> = Default::default();
         let mut data_list: Vec<std::sync::Arc<crate::test::TestString>> = vec![];
 
-        for x in (0..buf.read_size()).rev() {
+        for x in (0..buf.try_read_size()?).rev() {
             let row = std::sync::Arc::new(crate::test::TestString::new(&mut buf)?);
             data_list.push(row.clone());
             data_map.insert(row.id.clone(), row.clone());
@@ -2275,7 +2287,7 @@ impl TbDemoGroup {
         let mut data_map: std::collections::HashMap<i32, std::sync::Arc<crate::test::DemoGroup>> = Default::default();
         let mut data_list: Vec<std::sync::Arc<crate::test::DemoGroup>> = vec![];
 
-        for x in (0..buf.read_size()).rev() {
+        for x in (0..buf.try_read_size()?).rev() {
             let row = std::sync::Arc::new(crate::test::DemoGroup::new(&mut buf)?);
             data_list.push(row.clone());
             data_map.insert(row.id.clone(), row.clone());
@@ -2309,7 +2321,7 @@ impl TbDemoGroup_C {
         let mut data_map: std::collections::HashMap<i32, std::sync::Arc<crate::test::DemoGroup>> = Default::default();
         let mut data_list: Vec<std::sync::Arc<crate::test::DemoGroup>> = vec![];
 
-        for x in (0..buf.read_size()).rev() {
+        for x in (0..buf.try_read_size()?).rev() {
             let row = std::sync::Arc::new(crate::test::DemoGroup::new(&mut buf)?);
             data_list.push(row.clone());
             data_map.insert(row.id.clone(), row.clone());
@@ -2343,7 +2355,7 @@ impl TbDemoGroup_S {
         let mut data_map: std::collections::HashMap<i32, std::sync::Arc<crate::test::DemoGroup>> = Default::default();
         let mut data_list: Vec<std::sync::Arc<crate::test::DemoGroup>> = vec![];
 
-        for x in (0..buf.read_size()).rev() {
+        for x in (0..buf.try_read_size()?).rev() {
             let row = std::sync::Arc::new(crate::test::DemoGroup::new(&mut buf)?);
             data_list.push(row.clone());
             data_map.insert(row.id.clone(), row.clone());
@@ -2377,7 +2389,7 @@ impl TbDemoGroup_E {
         let mut data_map: std::collections::HashMap<i32, std::sync::Arc<crate::test::DemoGroup>> = Default::default();
         let mut data_list: Vec<std::sync::Arc<crate::test::DemoGroup>> = vec![];
 
-        for x in (0..buf.read_size()).rev() {
+        for x in (0..buf.try_read_size()?).rev() {
             let row = std::sync::Arc::new(crate::test::DemoGroup::new(&mut buf)?);
             data_list.push(row.clone());
             data_map.insert(row.id.clone(), row.clone());
@@ -2407,7 +2419,7 @@ pub struct TbTestGlobal {
 
 impl TbTestGlobal {
     pub fn new(mut buf: ByteBuf) -> Result<std::sync::Arc<TbTestGlobal>, LubanError> {
-        let n = buf.read_size();
+        let n = buf.try_read_size()?;
         if n != 1 { return Err(LubanError::Table(format!("table mode=one, but size != 1"))); }
         let data = crate::test::TestGlobal::new(&mut buf)?;
         Ok(std::sync::Arc::new(TbTestGlobal { data }))
@@ -2426,7 +2438,7 @@ impl TbTestBeRef {
         let mut data_map: std::collections::HashMap<i32, std::sync::Arc<crate::test::TestBeRef>> = Default::default();
         let mut data_list: Vec<std::sync::Arc<crate::test::TestBeRef>> = vec![];
 
-        for x in (0..buf.read_size()).rev() {
+        for x in (0..buf.try_read_size()?).rev() {
             let row = std::sync::Arc::new(crate::test::TestBeRef::new(&mut buf)?);
             data_list.push(row.clone());
             data_map.insert(row.id.clone(), row.clone());
@@ -2460,7 +2472,7 @@ impl TbTestBeRef2 {
         let mut data_map: std::collections::HashMap<i32, std::sync::Arc<crate::test::TestBeRef>> = Default::default();
         let mut data_list: Vec<std::sync::Arc<crate::test::TestBeRef>> = vec![];
 
-        for x in (0..buf.read_size()).rev() {
+        for x in (0..buf.try_read_size()?).rev() {
             let row = std::sync::Arc::new(crate::test::TestBeRef::new(&mut buf)?);
             data_list.push(row.clone());
             data_map.insert(row.id.clone(), row.clone());
@@ -2494,7 +2506,7 @@ impl TbTestRef {
         let mut data_map: std::collections::HashMap<i32, std::sync::Arc<crate::test::TestRef>> = Default::default();
         let mut data_list: Vec<std::sync::Arc<crate::test::TestRef>> = vec![];
 
-        for x in (0..buf.read_size()).rev() {
+        for x in (0..buf.try_read_size()?).rev() {
             let row = std::sync::Arc::new(crate::test::TestRef::new(&mut buf)?);
             data_list.push(row.clone());
             data_map.insert(row.id.clone(), row.clone());
@@ -2528,7 +2540,7 @@ impl TbTestSize {
         let mut data_map: std::collections::HashMap<i32, std::sync::Arc<crate::test::TestSize>> = Default::default();
         let mut data_list: Vec<std::sync::Arc<crate::test::TestSize>> = vec![];
 
-        for x in (0..buf.read_size()).rev() {
+        for x in (0..buf.try_read_size()?).rev() {
             let row = std::sync::Arc::new(crate::test::TestSize::new(&mut buf)?);
             data_list.push(row.clone());
             data_map.insert(row.id.clone(), row.clone());
@@ -2562,7 +2574,7 @@ impl TbTestSet {
         let mut data_map: std::collections::HashMap<i32, std::sync::Arc<crate::test::TestSet>> = Default::default();
         let mut data_list: Vec<std::sync::Arc<crate::test::TestSet>> = vec![];
 
-        for x in (0..buf.read_size()).rev() {
+        for x in (0..buf.try_read_size()?).rev() {
             let row = std::sync::Arc::new(crate::test::TestSet::new(&mut buf)?);
             data_list.push(row.clone());
             data_map.insert(row.id.clone(), row.clone());
@@ -2596,7 +2608,7 @@ impl TbTestRange {
         let mut data_map: std::collections::HashMap<i32, std::sync::Arc<crate::test::TestRange>> = Default::default();
         let mut data_list: Vec<std::sync::Arc<crate::test::TestRange>> = vec![];
 
-        for x in (0..buf.read_size()).rev() {
+        for x in (0..buf.try_read_size()?).rev() {
             let row = std::sync::Arc::new(crate::test::TestRange::new(&mut buf)?);
             data_list.push(row.clone());
             data_map.insert(row.id.clone(), row.clone());
@@ -2630,7 +2642,7 @@ impl TbDetectCsvEncoding {
         let mut data_map: std::collections::HashMap<i32, std::sync::Arc<crate::test::DetectEncoding>> = Default::default();
         let mut data_list: Vec<std::sync::Arc<crate::test::DetectEncoding>> = vec![];
 
-        for x in (0..buf.read_size()).rev() {
+        for x in (0..buf.try_read_size()?).rev() {
             let row = std::sync::Arc::new(crate::test::DetectEncoding::new(&mut buf)?);
             data_list.push(row.clone());
             data_map.insert(row.id.clone(), row.clone());
@@ -2664,7 +2676,7 @@ impl TbItem2 {
         let mut data_map: std::collections::HashMap<i32, std::sync::Arc<AbstractBase>> = Default::default();
         let mut data_list: Vec<std::sync::Arc<AbstractBase>> = vec![];
 
-        for x in (0..buf.read_size()).rev() {
+        for x in (0..buf.try_read_size()?).rev() {
             let row = crate::test::ItemBase::new(&mut buf)?;
             data_list.push(row.clone());
             let key = <AbstractBase as GetBase<&dyn crate::test::TItemBase>>::get_base(std::ops::Deref::deref(&row))?;
@@ -2699,7 +2711,7 @@ impl TbTestIndex {
         let mut data_map: std::collections::HashMap<i32, std::sync::Arc<crate::test::TestIndex>> = Default::default();
         let mut data_list: Vec<std::sync::Arc<crate::test::TestIndex>> = vec![];
 
-        for x in (0..buf.read_size()).rev() {
+        for x in (0..buf.try_read_size()?).rev() {
             let row = std::sync::Arc::new(crate::test::TestIndex::new(&mut buf)?);
             data_list.push(row.clone());
             data_map.insert(row.id.clone(), row.clone());
@@ -2733,7 +2745,7 @@ impl TbTestMap {
         let mut data_map: std::collections::HashMap<i32, std::sync::Arc<crate::test::TestMap>> = Default::default();
         let mut data_list: Vec<std::sync::Arc<crate::test::TestMap>> = vec![];
 
-        for x in (0..buf.read_size()).rev() {
+        for x in (0..buf.try_read_size()?).rev() {
             let row = std::sync::Arc::new(crate::test::TestMap::new(&mut buf)?);
             data_list.push(row.clone());
             data_map.insert(row.id.clone(), row.clone());
@@ -2767,7 +2779,7 @@ impl TbExcelFromJson {
         let mut data_map: std::collections::HashMap<i32, std::sync::Arc<crate::test::ExcelFromJson>> = Default::default();
         let mut data_list: Vec<std::sync::Arc<crate::test::ExcelFromJson>> = vec![];
 
-        for x in (0..buf.read_size()).rev() {
+        for x in (0..buf.try_read_size()?).rev() {
             let row = std::sync::Arc::new(crate::test::ExcelFromJson::new(&mut buf)?);
             data_list.push(row.clone());
             data_map.insert(row.x4.clone(), row.clone());
@@ -2801,7 +2813,7 @@ impl TbCompositeJsonTable1 {
         let mut data_map: std::collections::HashMap<i32, std::sync::Arc<crate::test::CompositeJsonTable1>> = Default::default();
         let mut data_list: Vec<std::sync::Arc<crate::test::CompositeJsonTable1>> = vec![];
 
-        for x in (0..buf.read_size()).rev() {
+        for x in (0..buf.try_read_size()?).rev() {
             let row = std::sync::Arc::new(crate::test::CompositeJsonTable1::new(&mut buf)?);
             data_list.push(row.clone());
             data_map.insert(row.id.clone(), row.clone());
@@ -2835,7 +2847,7 @@ impl TbCompositeJsonTable2 {
         let mut data_map: std::collections::HashMap<i32, std::sync::Arc<crate::test::CompositeJsonTable2>> = Default::default();
         let mut data_list: Vec<std::sync::Arc<crate::test::CompositeJsonTable2>> = vec![];
 
-        for x in (0..buf.read_size()).rev() {
+        for x in (0..buf.try_read_size()?).rev() {
             let row = std::sync::Arc::new(crate::test::CompositeJsonTable2::new(&mut buf)?);
             data_list.push(row.clone());
             data_map.insert(row.id.clone(), row.clone());
@@ -2865,7 +2877,7 @@ pub struct TbCompositeJsonTable3 {
 
 impl TbCompositeJsonTable3 {
     pub fn new(mut buf: ByteBuf) -> Result<std::sync::Arc<TbCompositeJsonTable3>, LubanError> {
-        let n = buf.read_size();
+        let n = buf.try_read_size()?;
         if n != 1 { return Err(LubanError::Table(format!("table mode=one, but size != 1"))); }
         let data = crate::test::CompositeJsonTable3::new(&mut buf)?;
         Ok(std::sync::Arc::new(TbCompositeJsonTable3 { data }))
@@ -2884,7 +2896,7 @@ impl TbExcelFromJsonMultiRow {
         let mut data_map: std::collections::HashMap<i32, std::sync::Arc<crate::test::ExcelFromJsonMultiRow>> = Default::default();
         let mut data_list: Vec<std::sync::Arc<crate::test::ExcelFromJsonMultiRow>> = vec![];
 
-        for x in (0..buf.read_size()).rev() {
+        for x in (0..buf.try_read_size()?).rev() {
             let row = std::sync::Arc::new(crate::test::ExcelFromJsonMultiRow::new(&mut buf)?);
             data_list.push(row.clone());
             data_map.insert(row.id.clone(), row.clone());
@@ -2918,7 +2930,7 @@ impl TbTestScriptableObject {
         let mut data_map: std::collections::HashMap<i32, std::sync::Arc<crate::test::TestScriptableObject>> = Default::default();
         let mut data_list: Vec<std::sync::Arc<crate::test::TestScriptableObject>> = vec![];
 
-        for x in (0..buf.read_size()).rev() {
+        for x in (0..buf.try_read_size()?).rev() {
             let row = std::sync::Arc::new(crate::test::TestScriptableObject::new(&mut buf)?);
             data_list.push(row.clone());
             data_map.insert(row.id.clone(), row.clone());
@@ -2952,7 +2964,7 @@ impl TbPath {
         let mut data_map: std::collections::HashMap<i32, std::sync::Arc<crate::test::Path>> = Default::default();
         let mut data_list: Vec<std::sync::Arc<crate::test::Path>> = vec![];
 
-        for x in (0..buf.read_size()).rev() {
+        for x in (0..buf.try_read_size()?).rev() {
             let row = std::sync::Arc::new(crate::test::Path::new(&mut buf)?);
             data_list.push(row.clone());
             data_map.insert(row.id.clone(), row.clone());
@@ -2986,7 +2998,7 @@ impl TbTestFieldAlias {
         let mut data_map: std::collections::HashMap<i32, std::sync::Arc<crate::test::TestFieldAlias>> = Default::default();
         let mut data_list: Vec<std::sync::Arc<crate::test::TestFieldAlias>> = vec![];
 
-        for x in (0..buf.read_size()).rev() {
+        for x in (0..buf.try_read_size()?).rev() {
             let row = std::sync::Arc::new(crate::test::TestFieldAlias::new(&mut buf)?);
             data_list.push(row.clone());
             data_map.insert(row.id.clone(), row.clone());
@@ -3020,7 +3032,7 @@ impl TbTestFieldVariant {
         let mut data_map: std::collections::HashMap<i32, std::sync::Arc<crate::test::TestFieldVariant>> = Default::default();
         let mut data_list: Vec<std::sync::Arc<crate::test::TestFieldVariant>> = vec![];
 
-        for x in (0..buf.read_size()).rev() {
+        for x in (0..buf.try_read_size()?).rev() {
             let row = std::sync::Arc::new(crate::test::TestFieldVariant::new(&mut buf)?);
             data_list.push(row.clone());
             data_map.insert(row.id.clone(), row.clone());
@@ -3054,7 +3066,7 @@ impl TbTestFieldVariant2 {
         let mut data_map: std::collections::HashMap<i32, std::sync::Arc<crate::test::TestFieldVariant2>> = Default::default();
         let mut data_list: Vec<std::sync::Arc<crate::test::TestFieldVariant2>> = vec![];
 
-        for x in (0..buf.read_size()).rev() {
+        for x in (0..buf.try_read_size()?).rev() {
             let row = std::sync::Arc::new(crate::test::TestFieldVariant2::new(&mut buf)?);
             data_list.push(row.clone());
             data_map.insert(row.id.clone(), row.clone());
@@ -3088,7 +3100,7 @@ impl TbTestMapper {
         let mut data_map: std::collections::HashMap<i32, std::sync::Arc<crate::test::TestMapper>> = Default::default();
         let mut data_list: Vec<std::sync::Arc<crate::test::TestMapper>> = vec![];
 
-        for x in (0..buf.read_size()).rev() {
+        for x in (0..buf.try_read_size()?).rev() {
             let row = std::sync::Arc::new(crate::test::TestMapper::new(&mut buf)?);
             data_list.push(row.clone());
             data_map.insert(row.id.clone(), row.clone());
@@ -3122,7 +3134,7 @@ impl TbDefineFromExcel2 {
         let mut data_map: std::collections::HashMap<i32, std::sync::Arc<crate::test::DefineFromExcel2>> = Default::default();
         let mut data_list: Vec<std::sync::Arc<crate::test::DefineFromExcel2>> = vec![];
 
-        for x in (0..buf.read_size()).rev() {
+        for x in (0..buf.try_read_size()?).rev() {
             let row = std::sync::Arc::new(crate::test::DefineFromExcel2::new(&mut buf)?);
             data_list.push(row.clone());
             data_map.insert(row.id.clone(), row.clone());
@@ -3156,7 +3168,7 @@ impl TbAutoImport2 {
         let mut data_map: std::collections::HashMap<i32, std::sync::Arc<crate::test::AutoImport2>> = Default::default();
         let mut data_list: Vec<std::sync::Arc<crate::test::AutoImport2>> = vec![];
 
-        for x in (0..buf.read_size()).rev() {
+        for x in (0..buf.try_read_size()?).rev() {
             let row = std::sync::Arc::new(crate::test::AutoImport2::new(&mut buf)?);
             data_list.push(row.clone());
             data_map.insert(row.id.clone(), row.clone());
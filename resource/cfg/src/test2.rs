@@ -22,8 +22,8 @@ pub struct Rectangle {
 
 impl Rectangle{
     pub fn new(mut buf: &mut ByteBuf) -> Result<Rectangle, LubanError> {
-        let width = buf.read_float();
-        let height = buf.read_float();
+        let width = buf.try_read_float()?;
+        let height = buf.try_read_float()?;
         
         Ok(Rectangle { width, height, })
     }
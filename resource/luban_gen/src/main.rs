@@ -0,0 +1,136 @@
+//! 把Luban rust-bin生成的代码改成可失败的读取
+//!
+//! Luban原生的代码读数据时用`buf.read_*()`,数据不完整或枚举值非法时会panic。
+//! 重新生成配置代码后在仓库根目录运行:
+//!
+//! ```text
+//! cargo run -p luban_gen -- resource/cfg/src
+//! ```
+//!
+//! - `buf.read_*()`改成`buf.try_read_*()?`,枚举改成`buf.try_read_enum()?`
+//! - 枚举的`From<i32>`改成`TryFrom<i32>`,非法值返回错误
+//! - `Tables::new`加载每张表时用`with_table`带上表名
+//! - `LubanError`使用luban_lib里的定义
+//!
+//! 已经改写过的代码再运行一次不会变化。
+
+use regex::{Captures, Regex};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+struct Rewriter {
+    error: Regex,
+    table: Regex,
+    enum_read: Regex,
+    read: Regex,
+    enum_impl: Regex,
+    enum_arm: Regex,
+}
+
+impl Rewriter {
+    fn new() -> Self {
+        Self {
+            error: Regex::new(r"(?s)#\[derive\(Debug\)\]\npub enum LubanError \{.*?\n\}\n\nimpl std::fmt::Display for LubanError \{.*?\n\}\n").unwrap(),
+            table: Regex::new(r#"::new\(loader\("(\w+)"\)\?\)\?"#).unwrap(),
+            enum_read: Regex::new(r"buf\.read_int\(\)\.into\(\)").unwrap(),
+            read: Regex::new(r"buf\.read_(\w+)\(\)").unwrap(),
+            enum_impl: Regex::new(r#"(?s)impl From<i32> for (\w+) \{\n    fn from\(value: i32\) -> Self \{\n(.*?)            _ => panic!\("Invalid value for \w+:\{\}", value\),"#).unwrap(),
+            enum_arm: Regex::new(r"(-?\d+) => (\w+::\w+),").unwrap(),
+        }
+    }
+
+    fn rewrite(&self, code: &str) -> String {
+        let code = self.error.replace(code, "pub use luban_lib::LubanError;\n");
+        let code = self.table.replace_all(&code, r#"::new(loader("$1")?).map_err(|e| e.with_table("$1"))?"#);
+        let code = self.enum_read.replace_all(&code, "buf.try_read_enum()?");
+        let code = self.read.replace_all(&code, "buf.try_read_$1()?");
+        let code = self.enum_impl.replace_all(&code, |caps: &Captures| {
+            let name = &caps[1];
+            let arms = self.enum_arm.replace_all(&caps[2], "$1 => Ok($2),");
+            format!(
+                "impl TryFrom<i32> for {name} {{\n    type Error = LubanError;\n\n    fn try_from(value: i32) -> Result<Self, LubanError> {{\n{arms}            _ => Err(LubanError::Bean(format!(\"Invalid value for {name}:{{}}\", value))),"
+            )
+        });
+        code.into_owned()
+    }
+}
+
+fn find_rust_files(dir: &Path) -> std::io::Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            files.extend(find_rust_files(&path)?);
+        } else if path.extension().and_then(|s| s.to_str()) == Some("rs") {
+            files.push(path);
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+fn main() -> std::io::Result<()> {
+    let dir = std::env::args().nth(1).unwrap_or_else(|| "resource/cfg/src".to_string());
+    let rewriter = Rewriter::new();
+    for path in find_rust_files(Path::new(&dir))? {
+        let code = fs::read_to_string(&path)?;
+        let rewritten = rewriter.rewrite(&code);
+        if rewritten != code {
+            fs::write(&path, rewritten)?;
+            println!("rewrite {}", path.display());
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn rewrite() {
+        let code = r#"#[derive(Debug)]
+pub enum LubanError {
+    Loader(String),
+}
+
+impl std::fmt::Display for LubanError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("")
+    }
+}
+impl From<i32> for EKeyType {
+    fn from(value: i32) -> Self {
+        match value {
+            -1 => EKeyType::NONE,
+            1 => EKeyType::BOOL,
+            _ => panic!("Invalid value for EKeyType:{}", value),
+        }
+    }
+}
+        let key_type = buf.read_int().into();
+        let x = {let n0 = std::cmp::min(buf.read_size(), buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(buf.read_int()); } _e0 };
+            TbItem: crate::item::TbItem::new(loader("item_tbitem")?)?,
+"#;
+        let expected = r#"pub use luban_lib::LubanError;
+impl TryFrom<i32> for EKeyType {
+    type Error = LubanError;
+
+    fn try_from(value: i32) -> Result<Self, LubanError> {
+        match value {
+            -1 => Ok(EKeyType::NONE),
+            1 => Ok(EKeyType::BOOL),
+            _ => Err(LubanError::Bean(format!("Invalid value for EKeyType:{}", value))),
+        }
+    }
+}
+        let key_type = buf.try_read_enum()?;
+        let x = {let n0 = std::cmp::min(buf.try_read_size()?, buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(buf.try_read_int()?); } _e0 };
+            TbItem: crate::item::TbItem::new(loader("item_tbitem")?).map_err(|e| e.with_table("item_tbitem"))?,
+"#;
+        let rewriter = Rewriter::new();
+        let rewritten = rewriter.rewrite(code);
+        assert_eq!(rewritten, expected);
+        assert_eq!(rewriter.rewrite(&rewritten), expected);
+    }
+}
@@ -1,4 +1,46 @@
-use std::cell::UnsafeCell;
+#[derive(Debug, Clone)]
+pub enum LubanError {
+    Loader(String),
+    Table(String),
+    Bean(String),
+    Polymorphic(String),
+    Unknown(String),
+    //数据不完整或格式错误,offset是出错的值在文件中的位置,table由Tables::new填入
+    Decode {
+        table: String,
+        offset: usize,
+        message: String,
+    },
+}
+
+impl LubanError {
+    //给Decode错误加上表名,其他错误不变
+    pub fn with_table(self, name: &str) -> Self {
+        match self {
+            LubanError::Decode { table, offset, message } if table.is_empty() => LubanError::Decode {
+                table: name.to_string(),
+                offset,
+                message,
+            },
+            other => other,
+        }
+    }
+}
+
+impl std::fmt::Display for LubanError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LubanError::Loader(msg) |
+            LubanError::Table(msg) |
+            LubanError::Bean(msg) |
+            LubanError::Polymorphic(msg) |
+            LubanError::Unknown(msg) => f.write_str(msg),
+            LubanError::Decode { table, offset, message } => write!(f, "{} decode failed at offset {}: {}", table, offset, message),
+        }
+    }
+}
+
+impl std::error::Error for LubanError {}
 
 pub struct ByteBuf {
     pub reader_index: usize,
@@ -44,73 +86,90 @@ impl ByteBuf {
     }
 
     pub fn read_bool(&mut self) -> bool {
-        self.ensure_read(1);
+        self.try_read_bool().unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    pub fn try_read_bool(&mut self) -> Result<bool, LubanError> {
+        self.try_ensure_read(1)?;
         let result = self.bytes[self.reader_index] != 0;
         self.reader_index += 1;
-        result
+        Ok(result)
     }
 
     pub fn read_byte(&mut self) -> u8 {
-        self.ensure_read(1);
+        self.try_read_byte().unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    pub fn try_read_byte(&mut self) -> Result<u8, LubanError> {
+        self.try_ensure_read(1)?;
         let result = self.bytes[self.reader_index];
         self.reader_index += 1;
-        result
+        Ok(result)
     }
 
     pub fn read_short(&mut self) -> i16 {
-        self.ensure_read(1);
+        self.try_read_short().unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    pub fn try_read_short(&mut self) -> Result<i16, LubanError> {
+        self.try_ensure_read(1)?;
         let h = self.bytes[self.reader_index];
         if h < 0x80 {
             self.reader_index += 1;
-            return h as i16;
+            return Ok(h as i16);
         }
 
         if h < 0xc0 {
-            self.ensure_read(2);
+            self.try_ensure_read(2)?;
             let x = (((h & 0x3f) as i16) << 8) | (self.bytes[self.reader_index + 1] as i16);
             self.reader_index += 2;
-            return x;
+            return Ok(x);
         }
 
-        if h < 0xff {
-            self.ensure_read(3);
+        //Luban写入负数和大于0x4000的值时用0xff开头,后面是2字节大端
+        if h == 0xff {
+            self.try_ensure_read(3)?;
             let x = ((self.bytes[self.reader_index + 1] as i16) << 8) | (self.bytes[self.reader_index + 2] as i16);
             self.reader_index += 3;
-            return x;
+            return Ok(x);
         }
 
-        panic!("Invalid data")
+        Err(self.decode_error(format!("invalid short header {:#x}", h)))
     }
 
     pub fn read_uint(&mut self) -> u32 {
-        self.ensure_read(1);
+        self.try_read_uint().unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    pub fn try_read_uint(&mut self) -> Result<u32, LubanError> {
+        self.try_ensure_read(1)?;
         let h = self.bytes[self.reader_index] as u32;
         if h < 0x80 {
             self.reader_index += 1;
-            return h;
+            return Ok(h);
         }
         if h < 0xc0 {
-            self.ensure_read(2);
+            self.try_ensure_read(2)?;
             let x = ((h & 0x3f) << 8) | (self.bytes[self.reader_index + 1] as u32);
             self.reader_index += 2;
-            return x;
+            return Ok(x);
         }
         if h < 0xe0 {
-            self.ensure_read(3);
+            self.try_ensure_read(3)?;
             let x = ((h & 0x1f) << 16) | ((self.bytes[self.reader_index + 1] as u32) << 8) | (self.bytes[self.reader_index + 2] as u32);
             self.reader_index += 3;
-            return x;
+            return Ok(x);
         }
         if h < 0xf0 {
-            self.ensure_read(4);
+            self.try_ensure_read(4)?;
             let x = ((h & 0x0f) << 24) | ((self.bytes[self.reader_index + 1] as u32) << 16) | ((self.bytes[self.reader_index + 2] as u32) << 8) | (self.bytes[self.reader_index + 3] as u32);
             self.reader_index += 4;
-            return x;
+            return Ok(x);
         } else {
-            self.ensure_read(5);
+            self.try_ensure_read(5)?;
             let x = ((self.bytes[self.reader_index + 1] as u32) << 24) | ((self.bytes[self.reader_index + 2] as u32) << 16) | ((self.bytes[self.reader_index + 3] as u32) << 8) | (self.bytes[self.reader_index + 4] as u32);
             self.reader_index += 5;
-            return x;
+            return Ok(x);
         }
     }
 
@@ -118,64 +177,83 @@ impl ByteBuf {
         self.read_uint() as i32
     }
 
+    pub fn try_read_int(&mut self) -> Result<i32, LubanError> {
+        Ok(self.try_read_uint()? as i32)
+    }
+
+    //枚举按int读取,值非法时返回Decode错误,offset是枚举值的位置
+    pub fn try_read_enum<T: TryFrom<i32, Error = LubanError>>(&mut self) -> Result<T, LubanError> {
+        let offset = self.reader_index;
+        let value = self.try_read_int()?;
+        T::try_from(value).map_err(|e| LubanError::Decode {
+            table: String::new(),
+            offset,
+            message: e.to_string(),
+        })
+    }
+
     pub fn read_ulong(&mut self) -> u64 {
-        self.ensure_read(1);
+        self.try_read_ulong().unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    pub fn try_read_ulong(&mut self) -> Result<u64, LubanError> {
+        self.try_ensure_read(1)?;
         let h = self.bytes[self.reader_index];
         if h < 0x80 {
             self.reader_index += 1;
-            return h as u64;
+            return Ok(h as u64);
         }
         if h < 0xc0 {
-            self.ensure_read(2);
+            self.try_ensure_read(2)?;
             let x = (((h & 0x3f) as u64) << 8) | (self.bytes[self.reader_index + 1] as u64);
             self.reader_index += 2;
-            return x;
+            return Ok(x);
         }
         if h < 0xe0 {
-            self.ensure_read(3);
+            self.try_ensure_read(3)?;
             let x = (((h & 0x1f) as u64) << 16) | ((self.bytes[self.reader_index + 1] as u64) << 8) | (self.bytes[self.reader_index + 2] as u64);
             self.reader_index += 3;
-            return x;
+            return Ok(x);
         }
         if h < 0xf0 {
-            self.ensure_read(4);
+            self.try_ensure_read(4)?;
             let x = (((h & 0x0f) as u64) << 24) | ((self.bytes[self.reader_index + 1] as u64) << 16) | ((self.bytes[self.reader_index + 2] as u64) << 8) | (self.bytes[self.reader_index + 3] as u64);
             self.reader_index += 4;
-            return x;
+            return Ok(x);
         }
         if h < 0xf8 {
-            self.ensure_read(5);
+            self.try_ensure_read(5)?;
             let xl = ((self.bytes[self.reader_index + 1] as u64) << 24) | ((self.bytes[self.reader_index + 2] as u64) << 16) | ((self.bytes[self.reader_index + 3] as u64) << 8) | (self.bytes[self.reader_index + 4] as u64);
             let xh = (h & 0x07) as u64;
             self.reader_index += 5;
-            return (xh << 32) | xl;
+            return Ok((xh << 32) | xl);
         }
         if h < 0xfc {
-            self.ensure_read(6);
+            self.try_ensure_read(6)?;
             let xl = ((self.bytes[self.reader_index + 2] as u64) << 24) | ((self.bytes[self.reader_index + 3] as u64) << 16) | ((self.bytes[self.reader_index + 4] as u64) << 8) | (self.bytes[self.reader_index + 5] as u64);
             let xh = (((h & 0x03) as u64) << 8) | (self.bytes[self.reader_index + 1] as u64);
             self.reader_index += 6;
-            return (xh << 32) | xl;
+            return Ok((xh << 32) | xl);
         }
         if h < 0xfe {
-            self.ensure_read(7);
+            self.try_ensure_read(7)?;
             let xl = ((self.bytes[self.reader_index + 3] as u64) << 24) | ((self.bytes[self.reader_index + 4] as u64) << 16) | ((self.bytes[self.reader_index + 5] as u64) << 8) | (self.bytes[self.reader_index + 6] as u64);
-            let xh = (((h & 0x01) as u64) << 16) | ((self.bytes[self.reader_index + 1] as u64) << 8) | (self.bytes[self.reader_index + 1] as u64);
+            let xh = (((h & 0x01) as u64) << 16) | ((self.bytes[self.reader_index + 1] as u64) << 8) | (self.bytes[self.reader_index + 2] as u64);
             self.reader_index += 7;
-            return (xh << 32) | xl;
+            return Ok((xh << 32) | xl);
         }
         if h < 0xff {
-            self.ensure_read(8);
+            self.try_ensure_read(8)?;
             let xl = ((self.bytes[self.reader_index + 4] as u64) << 24) | ((self.bytes[self.reader_index + 5] as u64) << 16) | ((self.bytes[self.reader_index + 6] as u64) << 8) | (self.bytes[self.reader_index + 7] as u64);
             let xh = ((self.bytes[self.reader_index + 1] as u64) << 16) | ((self.bytes[self.reader_index + 2] as u64) << 8) | (self.bytes[self.reader_index + 3] as u64);
             self.reader_index += 8;
-            return (xh << 32) | xl;
+            return Ok((xh << 32) | xl);
         } else {
-            self.ensure_read(9);
+            self.try_ensure_read(9)?;
             let xl = ((self.bytes[self.reader_index + 5] as u64) << 24) | ((self.bytes[self.reader_index + 6] as u64) << 16) | ((self.bytes[self.reader_index + 7] as u64) << 8) | (self.bytes[self.reader_index + 8] as u64);
             let xh = ((self.bytes[self.reader_index + 1] as u64) << 24) | ((self.bytes[self.reader_index + 2] as u64) << 16) | ((self.bytes[self.reader_index + 3] as u64) << 8) | (self.bytes[self.reader_index + 4] as u64);
             self.reader_index += 9;
-            return (xh << 32) | xl;
+            return Ok((xh << 32) | xl);
         }
     }
 
@@ -183,60 +261,133 @@ impl ByteBuf {
         self.read_ulong() as i64
     }
 
+    pub fn try_read_long(&mut self) -> Result<i64, LubanError> {
+        Ok(self.try_read_ulong()? as i64)
+    }
+
     pub fn read_float(&mut self) -> f32 {
-        self.ensure_read(4);
-        let b = &self.bytes[self.reader_index] as *const u8;
-        let mut x = UnsafeCell::new(0_f32);
-        unsafe {
-            if (b as u64) % 8 == 0 {
-                *x.get() = *(b as *const f32)
-            } else {
-                let a1 = (*b.offset(0) as u32);
-                let a2 = ((*b.offset(1) as u32) << 8);
-                let a3 = ((*b.offset(2) as u32) << 16);
-                let a4 = ((*b.offset(3) as u32) << 24);
-                let a5 = (a1 | a2 | a3 | a4);
-                *x.get() = *(Box::into_raw(Box::new(a5)) as *const f32);
-            }
-        }
+        self.try_read_float().unwrap_or_else(|e| panic!("{}", e))
+    }
 
-        self.reader_index += 4;
-        return x.into_inner();
+    pub fn try_read_float(&mut self) -> Result<f32, LubanError> {
+        Ok(f32::from_le_bytes(self.try_read_fixed()?))
     }
 
     pub fn read_double(&mut self) -> f64 {
-        self.ensure_read(8);
-        let b = &self.bytes[self.reader_index] as *const u8;
-        let mut x = UnsafeCell::new(0_f64);
-        unsafe {
-            if (b as u64) % 8 == 0 {
-                *x.get() = *(b as *const f64)
-            } else {
-                let low = (*b.offset(0) as u64) | ((*b.offset(1) as u64) << 8) | ((*b.offset(2) as u64) << 16) | ((*b.offset(3) as u64) << 24);
-                let high = (*b.offset(4) as u64) | ((*b.offset(5) as u64) << 8) | ((*b.offset(6) as u64) << 16) | ((*b.offset(7) as u64) << 24);
-                *x.get() = *(Box::into_raw(Box::new(((high) << 32) | (low))) as *const f64);
-            }
-        }
+        self.try_read_double().unwrap_or_else(|e| panic!("{}", e))
+    }
 
-        self.reader_index += 8;
-        return x.into_inner();
+    pub fn try_read_double(&mut self) -> Result<f64, LubanError> {
+        Ok(f64::from_le_bytes(self.try_read_fixed()?))
     }
 
     pub fn read_size(&mut self) -> usize {
         self.read_uint() as usize
     }
 
+    pub fn try_read_size(&mut self) -> Result<usize, LubanError> {
+        Ok(self.try_read_uint()? as usize)
+    }
+
     pub fn read_string(&mut self) -> String {
-        let n = self.read_size();
+        self.try_read_string().unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    pub fn try_read_string(&mut self) -> Result<String, LubanError> {
+        let n = self.try_read_size()?;
         if n > 0 {
-            self.ensure_read(n);
+            self.try_ensure_read(n)?;
             let s = String::from_utf8_lossy(&self.bytes[self.reader_index..self.reader_index + n]);
             self.reader_index += n;
-            return s.to_string();
+            return Ok(s.to_string());
+        }
+
+        Ok("".to_string())
+    }
+
+    //region write
+
+    pub fn write_bool(&mut self, x: bool) {
+        self.write_raw(&[x as u8]);
+    }
+
+    pub fn write_byte(&mut self, x: u8) {
+        self.write_raw(&[x]);
+    }
+
+    pub fn write_short(&mut self, x: i16) {
+        if (0..0x80).contains(&x) {
+            self.write_raw(&[x as u8]);
+        } else if (0..0x4000).contains(&x) {
+            self.write_raw(&[((x >> 8) | 0x80) as u8, x as u8]);
+        } else {
+            self.write_raw(&[0xff, (x >> 8) as u8, x as u8]);
         }
+    }
+
+    pub fn write_uint(&mut self, x: u32) {
+        if x < 0x80 {
+            self.write_raw(&[x as u8]);
+        } else if x < 0x4000 {
+            self.write_raw(&[((x >> 8) | 0x80) as u8, x as u8]);
+        } else if x < 0x200000 {
+            self.write_raw(&[((x >> 16) | 0xc0) as u8, (x >> 8) as u8, x as u8]);
+        } else if x < 0x10000000 {
+            self.write_raw(&[((x >> 24) | 0xe0) as u8, (x >> 16) as u8, (x >> 8) as u8, x as u8]);
+        } else {
+            self.write_raw(&[0xf0, (x >> 24) as u8, (x >> 16) as u8, (x >> 8) as u8, x as u8]);
+        }
+    }
+
+    pub fn write_int(&mut self, x: i32) {
+        self.write_uint(x as u32);
+    }
+
+    pub fn write_ulong(&mut self, x: u64) {
+        if x < 0x80 {
+            self.write_raw(&[x as u8]);
+        } else if x < 0x4000 {
+            self.write_compact(0x80 | (x >> 8) as u8, x, 1);
+        } else if x < 0x200000 {
+            self.write_compact(0xc0 | (x >> 16) as u8, x, 2);
+        } else if x < 0x10000000 {
+            self.write_compact(0xe0 | (x >> 24) as u8, x, 3);
+        } else if x < 0x800000000 {
+            self.write_compact(0xf0 | (x >> 32) as u8, x, 4);
+        } else if x < 0x40000000000 {
+            self.write_compact(0xf8 | (x >> 40) as u8, x, 5);
+        } else if x < 0x2000000000000 {
+            self.write_compact(0xfc | (x >> 48) as u8, x, 6);
+        } else if x < 0x100000000000000 {
+            self.write_compact(0xfe, x, 7);
+        } else {
+            self.write_compact(0xff, x, 8);
+        }
+    }
+
+    pub fn write_long(&mut self, x: i64) {
+        self.write_ulong(x as u64);
+    }
+
+    pub fn write_float(&mut self, x: f32) {
+        self.write_raw(&x.to_le_bytes());
+    }
 
-        "".to_string()
+    pub fn write_double(&mut self, x: f64) {
+        self.write_raw(&x.to_le_bytes());
     }
+
+    pub fn write_size(&mut self, x: usize) {
+        self.write_uint(x as u32);
+    }
+
+    pub fn write_string(&mut self, x: &str) {
+        self.write_size(x.len());
+        self.write_raw(x.as_bytes());
+    }
+
+    //endregion
+
     //region internal
 
     fn prop_size(init_size: usize, need_size: usize) -> usize {
@@ -251,15 +402,49 @@ impl ByteBuf {
     }
 
     #[inline]
-    fn ensure_read(&self, size: usize) {
-        if self.reader_index + size > self.writer_index {
-            panic!("Not enough data")
+    fn try_ensure_read(&self, size: usize) -> Result<(), LubanError> {
+        if size > self.size() {
+            return Err(self.decode_error(format!("not enough data, need {} bytes, {} left", size, self.size())));
         }
+        Ok(())
     }
 
-    #[inline]
-    fn can_read(&self, size: usize) -> bool {
-        self.reader_index + size <= self.writer_index
+    fn decode_error(&self, message: String) -> LubanError {
+        LubanError::Decode {
+            table: String::new(),
+            offset: self.reader_index,
+            message,
+        }
+    }
+
+    //float和double按小端读取,不要求对齐
+    fn try_read_fixed<const N: usize>(&mut self) -> Result<[u8; N], LubanError> {
+        self.try_ensure_read(N)?;
+        let mut x = [0; N];
+        x.copy_from_slice(&self.bytes[self.reader_index..self.reader_index + N]);
+        self.reader_index += N;
+        Ok(x)
+    }
+
+    //bytes的长度不够时按prop_size扩容,writer_index之后的内容无效
+    fn ensure_write(&mut self, size: usize) {
+        let need_size = self.writer_index + size;
+        if need_size > self.bytes.len() {
+            let new_size = Self::prop_size(self.bytes.len(), need_size);
+            self.bytes.resize(new_size, 0);
+        }
+    }
+
+    //header之后按大端写x的低n个字节
+    fn write_compact(&mut self, header: u8, x: u64, n: usize) {
+        self.write_raw(&[header]);
+        self.write_raw(&x.to_be_bytes()[8 - n..]);
+    }
+
+    fn write_raw(&mut self, x: &[u8]) {
+        self.ensure_write(x.len());
+        self.bytes[self.writer_index..self.writer_index + x.len()].copy_from_slice(x);
+        self.writer_index += x.len();
     }
 
     //endregion
@@ -281,4 +466,267 @@ impl PartialEq<Self> for ByteBuf {
     }
 }
 
-impl Eq for ByteBuf {}
\ No newline at end of file
+impl Eq for ByteBuf {}
+#[cfg(test)]
+mod test {
+    use super::*;
+    use proptest::prelude::*;
+
+    #[test]
+    fn try_read_truncated() {
+        //2字节的uint只有1字节
+        let mut buf = ByteBuf::new(vec![1, 0x80]);
+        assert_eq!(buf.try_read_int().unwrap(), 1);
+        match buf.try_read_uint().unwrap_err().with_table("item_tbitem") {
+            LubanError::Decode { table, offset, .. } => assert_eq!((table.as_str(), offset), ("item_tbitem", 1)),
+            e => panic!("unexpected error:{}", e),
+        }
+        //出错时不移动读位置
+        assert_eq!(buf.reader_index, 1);
+
+        let mut buf = ByteBuf::new(vec![0xff]);
+        assert!(matches!(buf.try_read_short(), Err(LubanError::Decode { offset: 0, .. })));
+        //字符串长度超过剩余数据
+        let mut buf = ByteBuf::new(vec![5, b'a', b'b']);
+        assert!(buf.try_read_string().is_err());
+        let mut buf = ByteBuf::new(vec![1, 2, 3]);
+        assert!(buf.try_read_float().is_err());
+        assert!(buf.try_read_double().is_err());
+    }
+
+    #[test]
+    fn short_header() {
+        //0xff开头的3字节形式
+        let mut buf = ByteBuf::new(vec![0xff, 0xff, 0xfe, 0xff, 0x40, 0x00, 0x81, 0x02]);
+        assert_eq!(buf.try_read_short().unwrap(), -2);
+        assert_eq!(buf.try_read_short().unwrap(), 0x4000);
+        assert_eq!(buf.try_read_short().unwrap(), 0x102);
+        //0xc0..0xfe不是合法的header
+        let mut buf = ByteBuf::new(vec![0xc0, 0, 0]);
+        assert!(matches!(buf.try_read_short(), Err(LubanError::Decode { offset: 0, .. })));
+    }
+
+    #[test]
+    fn float_double() {
+        let mut buf = ByteBuf::new(vec![0, 0, 0, 0x80, 0x3f]);
+        //不对齐的位置
+        buf.reader_index = 1;
+        assert_eq!(buf.read_float(), 1.0);
+
+        let mut buf = ByteBuf::with_capacity(0);
+        buf.write_byte(7);
+        buf.write_float(-2.5);
+        buf.write_double(f64::MIN_POSITIVE);
+        buf.write_float(f32::NAN);
+        buf.write_double(0.1);
+        assert_eq!(buf.size(), 1 + 4 + 8 + 4 + 8);
+        assert_eq!(buf.read_byte(), 7);
+        assert_eq!(buf.read_float(), -2.5);
+        assert_eq!(buf.read_double(), f64::MIN_POSITIVE);
+        assert!(buf.read_float().is_nan());
+        assert_eq!(buf.read_double(), 0.1);
+        assert_eq!(buf.size(), 0);
+    }
+
+    #[test]
+    fn write_read() {
+        let mut buf = ByteBuf::with_capacity(4);
+        buf.write_bool(true);
+        buf.write_int(-1);
+        buf.write_uint(0x3fff);
+        buf.write_size(0x200000);
+        buf.write_string("");
+        buf.write_string("道具");
+        assert!(buf.read_bool());
+        assert_eq!(buf.read_int(), -1);
+        assert_eq!(buf.read_uint(), 0x3fff);
+        assert_eq!(buf.read_size(), 0x200000);
+        assert_eq!(buf.read_string(), "");
+        assert_eq!(buf.read_string(), "道具");
+        assert_eq!(buf.size(), 0);
+    }
+
+    //按bit数计算编码长度
+    fn uint_len(x: u32) -> usize {
+        match 32 - x.leading_zeros() {
+            0..=7 => 1,
+            8..=14 => 2,
+            15..=21 => 3,
+            22..=28 => 4,
+            _ => 5,
+        }
+    }
+
+    fn ulong_len(x: u64) -> usize {
+        match 64 - x.leading_zeros() {
+            0..=7 => 1,
+            8..=14 => 2,
+            15..=21 => 3,
+            22..=28 => 4,
+            29..=35 => 5,
+            36..=42 => 6,
+            43..=49 => 7,
+            50..=56 => 8,
+            _ => 9,
+        }
+    }
+
+    //每个bit数的最大值以及相邻的值,再加上交替的bit,全是1时读错字节位置也看不出来
+    fn boundaries(bits: u32) -> Vec<u64> {
+        let mut values = vec![];
+        for n in 0..=bits {
+            let max = if n == 64 { u64::MAX } else { (1u64 << n) - 1 };
+            values.extend([max, max.saturating_sub(1), max.saturating_add(1), max & 0xaaaa_aaaa_aaaa_aaaa, max & 0x5555_5555_5555_5555]);
+        }
+        values.retain(|x| bits == 64 || *x < 1 << bits);
+        values
+    }
+
+    #[test]
+    fn uint_boundaries() {
+        for x in boundaries(32) {
+            let x = x as u32;
+            let mut buf = ByteBuf::with_capacity(0);
+            buf.write_uint(x);
+            assert_eq!(buf.size(), uint_len(x), "{:#x}", x);
+            assert_eq!(buf.read_uint(), x, "{:#x}", x);
+            //少一个字节时返回错误
+            let mut truncated = ByteBuf::new(buf.bytes[..uint_len(x) - 1].to_vec());
+            assert!(truncated.try_read_uint().is_err() || uint_len(x) == 1, "{:#x}", x);
+        }
+    }
+
+    #[test]
+    fn ulong_boundaries() {
+        for x in boundaries(64) {
+            let mut buf = ByteBuf::with_capacity(0);
+            buf.write_ulong(x);
+            assert_eq!(buf.size(), ulong_len(x), "{:#x}", x);
+            assert_eq!(buf.read_ulong(), x, "{:#x}", x);
+            let mut truncated = ByteBuf::new(buf.bytes[..ulong_len(x) - 1].to_vec());
+            assert!(truncated.try_read_ulong().is_err() || ulong_len(x) == 1, "{:#x}", x);
+        }
+    }
+
+    #[test]
+    fn short_exhaustive() {
+        for x in i16::MIN..=i16::MAX {
+            let mut buf = ByteBuf::with_capacity(0);
+            buf.write_short(x);
+            let len = match x {
+                0..0x80 => 1,
+                0x80..0x4000 => 2,
+                _ => 3,
+            };
+            assert_eq!(buf.size(), len, "{}", x);
+            assert_eq!(buf.read_short(), x);
+        }
+        //0xc0到0xfe不是合法的开头
+        for h in 0xc0..0xff {
+            assert!(ByteBuf::new(vec![h, 0, 0]).try_read_short().is_err());
+        }
+    }
+
+    //和Luban生成的二进制对照,不依赖write_*
+    #[test]
+    fn varint_known_bytes() {
+        let cases: &[(&[u8], u64)] = &[
+            (&[0x7f], 0x7f),
+            (&[0x80, 0x80], 0x80),
+            (&[0xbf, 0xff], 0x3fff),
+            (&[0xc0, 0x40, 0x00], 0x4000),
+            (&[0xdf, 0xff, 0xff], 0x1fffff),
+            (&[0xe0, 0x20, 0x00, 0x00], 0x200000),
+            (&[0xef, 0xff, 0xff, 0xff], 0xfffffff),
+            (&[0xf0, 0x10, 0x00, 0x00, 0x00], 0x10000000),
+            (&[0xf7, 0xff, 0xff, 0xff, 0xff], 0x7ffffffff),
+            (&[0xf8, 0x08, 0x00, 0x00, 0x00, 0x00], 0x800000000),
+            (&[0xfb, 0xff, 0xff, 0xff, 0xff, 0xff], 0x3ffffffffff),
+            (&[0xfc, 0x04, 0x00, 0x00, 0x00, 0x00, 0x00], 0x40000000000),
+            (&[0xfd, 0x23, 0x45, 0x67, 0x89, 0xab, 0xcd], 0x123456789abcd),
+            (&[0xfd, 0xff, 0xfe, 0xff, 0xff, 0xff, 0xff], 0x1fffeffffffff),
+            (&[0xfe, 0x02, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], 0x2000000000000),
+            (&[0xfe, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff], 0xffffffffffffff),
+            (&[0xff, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], 0x100000000000000),
+            (&[0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff], u64::MAX),
+        ];
+        for (bytes, x) in cases {
+            assert_eq!(ByteBuf::new(bytes.to_vec()).read_ulong(), *x, "{:x?}", bytes);
+            if let Ok(x) = u32::try_from(*x) {
+                assert_eq!(ByteBuf::new(bytes.to_vec()).read_uint(), x, "{:x?}", bytes);
+            }
+            let mut buf = ByteBuf::with_capacity(0);
+            buf.write_ulong(*x);
+            assert_eq!(buf.copy_data(), *bytes);
+        }
+        assert_eq!(ByteBuf::new(vec![0xf0, 0xff, 0xff, 0xff, 0xff]).read_uint(), u32::MAX);
+        assert_eq!(ByteBuf::new(vec![0xff, 0x80, 0x00]).read_short(), i16::MIN);
+        assert_eq!(ByteBuf::new(vec![0xbf, 0xff]).read_short(), 0x3fff);
+    }
+
+    proptest! {
+        #[test]
+        fn round_trip_varint(short: i16, uint: u32, int: i32, ulong: u64, long: i64, size in 0..=u32::MAX as usize) {
+            let mut buf = ByteBuf::with_capacity(0);
+            buf.write_short(short);
+            buf.write_uint(uint);
+            buf.write_int(int);
+            buf.write_ulong(ulong);
+            buf.write_long(long);
+            buf.write_size(size);
+            prop_assert_eq!(buf.try_read_short()?, short);
+            prop_assert_eq!(buf.try_read_uint()?, uint);
+            prop_assert_eq!(buf.try_read_int()?, int);
+            prop_assert_eq!(buf.try_read_ulong()?, ulong);
+            prop_assert_eq!(buf.try_read_long()?, long);
+            prop_assert_eq!(buf.try_read_size()?, size);
+            prop_assert_eq!(buf.size(), 0);
+        }
+
+        #[test]
+        fn round_trip_fixed(b: bool, byte: u8, float: f32, double: f64) {
+            let mut buf = ByteBuf::with_capacity(0);
+            buf.write_bool(b);
+            buf.write_byte(byte);
+            buf.write_float(float);
+            buf.write_double(double);
+            prop_assert_eq!(buf.try_read_bool()?, b);
+            prop_assert_eq!(buf.try_read_byte()?, byte);
+            //NaN按位比较
+            prop_assert_eq!(buf.try_read_float()?.to_bits(), float.to_bits());
+            prop_assert_eq!(buf.try_read_double()?.to_bits(), double.to_bits());
+            prop_assert_eq!(buf.size(), 0);
+        }
+
+        #[test]
+        fn round_trip_string(strings in proptest::collection::vec(any::<String>(), 0..8)) {
+            let mut buf = ByteBuf::with_capacity(0);
+            buf.write_size(strings.len());
+            for x in &strings {
+                buf.write_string(x);
+            }
+            let n = buf.try_read_size()?;
+            let mut read = vec![];
+            for _ in 0..n {
+                read.push(buf.try_read_string()?);
+            }
+            prop_assert_eq!(read, strings);
+            prop_assert_eq!(buf.size(), 0);
+        }
+
+        #[test]
+        fn fuzz_try_read(input in proptest::collection::vec(any::<u8>(), 0..32)) {
+            //任意输入只返回错误,不会panic
+            let mut buf = ByteBuf::new(input);
+            while buf.size() > 0 {
+                let _ = buf.try_read_short();
+                let _ = buf.try_read_ulong();
+                let _ = buf.try_read_string();
+                let _ = buf.try_read_double();
+                if buf.try_read_byte().is_err() {
+                    break;
+                }
+            }
+        }
+    }
+}
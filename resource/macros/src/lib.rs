@@ -55,9 +55,11 @@ pub fn enum_from_num(input: TokenStream) -> TokenStream {
     ];
     quote! {
         #(
-            impl From<#tokens> for #ty_name {
-                fn from(value: #tokens) -> Self {
-                    (value as i32).into()
+            impl TryFrom<#tokens> for #ty_name {
+                type Error = luban_lib::LubanError;
+
+                fn try_from(value: #tokens) -> Result<Self, Self::Error> {
+                    Self::try_from(value as i32)
                 }
             }            
         )*
@@ -0,0 +1,351 @@
+//! 网关压测机器人
+//!
+//! 每个机器人按场景执行:连接握手 -> 登录 -> 保持心跳并随机请求商店,直到压测结束。
+//! 所有机器人共用一个网络线程,按连接分发收到的数据。
+//! 定时输出各阶段的吞吐和延迟分位数,结束时输出汇总和按错误码统计的错误。
+//!
+//! ```text
+//! bots --addr 127.0.0.1:5678 --bots 2000 --ramp 200 --duration 300
+//! ```
+use clap::{Parser, ValueEnum};
+use client::{Client, ClientConfig, ClientError, Driver, Event, Transport};
+use common::config::KcpConfig;
+use lib::gate::crypto::{self, PUBLIC_KEY_LEN};
+use protocol::base_cmd::{BaseError, KickReason};
+use protocol::login_cmd::{LoginError, LoginReq};
+use protocol::store_cmd::{StoreError, StoreInfoReq};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::time::Instant;
+
+#[derive(Parser, Debug)]
+#[command(author, version, about = "gate load tester", long_about = None)]
+struct Args {
+    #[arg(short, long, default_value = "127.0.0.1:5678")]
+    addr: String,
+    #[arg(short, long, value_enum, default_value_t = Proto::Tcp)]
+    transport: Proto,
+    //udp使用可靠会话,需要网关开启kcp
+    #[arg(long)]
+    kcp: bool,
+    #[arg(long)]
+    encryption: bool,
+    //网关的长期公钥(十六进制),加密时必须设置,网关启动时输出到日志
+    #[arg(long, value_parser = parse_key)]
+    server_key: Option<[u8; PUBLIC_KEY_LEN]>,
+    //机器人数量
+    #[arg(short, long, default_value_t = 100)]
+    bots: usize,
+    //每秒启动的机器人数量,0表示同时启动
+    #[arg(long, default_value_t = 100)]
+    ramp: usize,
+    //压测时长(秒),从第一个机器人启动开始计算
+    #[arg(short, long, default_value_t = 60)]
+    duration: u64,
+    #[arg(long, default_value_t = 1)]
+    server_id: i32,
+    //账号为前缀加机器人编号
+    #[arg(long, default_value = "bot")]
+    account_prefix: String,
+    //两次商店请求的平均间隔(毫秒),实际在0.5到1.5倍之间随机
+    #[arg(long, default_value_t = 1000)]
+    think: u64,
+    #[arg(long, value_delimiter = ',', default_value = "1,2,3")]
+    store_ids: Vec<i32>,
+    //统计输出间隔(秒)
+    #[arg(long, default_value_t = 5)]
+    report_interval: u64,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum Proto {
+    Tcp,
+    Ws,
+    Udp,
+}
+
+impl Args {
+    fn client_config(&self) -> ClientConfig {
+        let transport = match self.transport {
+            Proto::Tcp => Transport::Tcp,
+            Proto::Ws => Transport::Ws,
+            Proto::Udp => Transport::Udp,
+        };
+        let mut config = ClientConfig::new(self.addr.clone(), transport);
+        config.encryption = self.encryption;
+        config.server_key = self.server_key;
+        if self.kcp {
+            config.kcp = Some(KcpConfig {
+                enable: true,
+                ..Default::default()
+            });
+        }
+        config
+    }
+}
+
+fn parse_key(key: &str) -> Result<[u8; PUBLIC_KEY_LEN], String> {
+    crypto::decode_key(key).map_err(|e| e.to_string())
+}
+
+//一个阶段的统计,延迟单位微秒
+#[derive(Default)]
+struct Metric {
+    ok: u64,
+    errors: BTreeMap<String, u64>,
+    //本次输出周期内的延迟
+    window: Vec<u32>,
+    total: Vec<u32>,
+    window_errors: u64,
+}
+
+#[derive(Default)]
+struct Stats {
+    metrics: Mutex<BTreeMap<&'static str, Metric>>,
+    online: AtomicUsize,
+    pushes: AtomicU64,
+    kicks: Mutex<BTreeMap<&'static str, u64>>,
+}
+
+impl Stats {
+    fn record<T>(&self, name: &'static str, start: Instant, result: &Result<T, ClientError>) {
+        let elapsed = start.elapsed().as_micros().min(u32::MAX as u128) as u32;
+        let mut metrics = self.metrics.lock().unwrap();
+        let metric = metrics.entry(name).or_default();
+        match result {
+            Ok(_) => {
+                metric.ok += 1;
+                metric.window.push(elapsed);
+            }
+            Err(e) => {
+                metric.window_errors += 1;
+                *metric.errors.entry(error_name(e)).or_default() += 1;
+            }
+        }
+    }
+
+    fn kick(&self, reason: KickReason) {
+        *self
+            .kicks
+            .lock()
+            .unwrap()
+            .entry(reason.as_str_name())
+            .or_default() += 1;
+    }
+
+    //输出本周期的吞吐和延迟,周期数据并入总数据
+    fn report(&self, elapsed: Duration, window: Duration) {
+        let mut metrics = self.metrics.lock().unwrap();
+        println!(
+            "[{:>6.1}s] online:{} pushes:{}",
+            elapsed.as_secs_f64(),
+            self.online.load(Ordering::Relaxed),
+            self.pushes.load(Ordering::Relaxed)
+        );
+        for (name, metric) in metrics.iter_mut() {
+            let mut latencies = std::mem::take(&mut metric.window);
+            latencies.sort_unstable();
+            println!(
+                "  {:<8} {:>8.1}/s err:{:<6} {}",
+                name,
+                latencies.len() as f64 / window.as_secs_f64(),
+                metric.window_errors,
+                percentiles(&latencies)
+            );
+            metric.window_errors = 0;
+            metric.total.extend(latencies);
+        }
+    }
+
+    fn summary(&self, elapsed: Duration) {
+        let mut metrics = self.metrics.lock().unwrap();
+        println!("==== summary {:.1}s ====", elapsed.as_secs_f64());
+        for (name, metric) in metrics.iter_mut() {
+            let window = std::mem::take(&mut metric.window);
+            metric.total.extend(window);
+            metric.total.sort_unstable();
+            let errors: u64 = metric.errors.values().sum();
+            println!(
+                "  {:<8} ok:{} err:{} {:>8.1}/s {}",
+                name,
+                metric.ok,
+                errors,
+                metric.ok as f64 / elapsed.as_secs_f64(),
+                percentiles(&metric.total)
+            );
+            for (error, count) in &metric.errors {
+                println!("    {:<32} {}", error, count);
+            }
+        }
+        println!("  pushes:{}", self.pushes.load(Ordering::Relaxed));
+        for (reason, count) in self.kicks.lock().unwrap().iter() {
+            println!("  kicked {:<32} {}", reason, count);
+        }
+    }
+}
+
+fn percentiles(sorted: &[u32]) -> String {
+    let at = |p: f64| match sorted.len() {
+        0 => 0.0,
+        len => sorted[((len - 1) as f64 * p).round() as usize] as f64 / 1000.0,
+    };
+    format!(
+        "p50:{:.2}ms p90:{:.2}ms p99:{:.2}ms max:{:.2}ms",
+        at(0.5),
+        at(0.9),
+        at(0.99),
+        at(1.0)
+    )
+}
+
+//错误码按BaseError/LoginError/StoreError的名字统计
+fn error_name(e: &ClientError) -> String {
+    match e {
+        ClientError::Rsp { code, .. } => code_name(*code),
+        ClientError::Kicked(kick) => kick.reason().as_str_name().to_string(),
+        ClientError::Timeout { .. } => "Timeout".to_string(),
+        ClientError::HandshakeTimeout => "HandshakeTimeout".to_string(),
+        ClientError::Handshake(_) => "Handshake".to_string(),
+        ClientError::Closed => "Closed".to_string(),
+        ClientError::Io(e) => format!("Io({:?})", e.kind()),
+        ClientError::Send(_) => "Send".to_string(),
+        ClientError::Decode(_) | ClientError::Protobuf(_) => "Decode".to_string(),
+        ClientError::UnexpectedCmd { actual, .. } => format!("UnexpectedCmd({})", actual),
+    }
+}
+
+fn code_name(code: i32) -> String {
+    BaseError::try_from(code)
+        .map(|x| x.as_str_name())
+        .or_else(|_| LoginError::try_from(code).map(|x| x.as_str_name()))
+        .or_else(|_| StoreError::try_from(code).map(|x| x.as_str_name()))
+        .map(str::to_string)
+        .unwrap_or_else(|_| format!("Error({})", code))
+}
+
+//处理推送和断开,返回连接是否还可用
+fn on_event(stats: &Stats, event: Option<Event>) -> bool {
+    match event {
+        Some(Event::Push { .. }) => {
+            stats.pushes.fetch_add(1, Ordering::Relaxed);
+            true
+        }
+        Some(Event::Kick(kick)) => {
+            stats.kick(kick.reason());
+            false
+        }
+        Some(Event::Closed) | None => false,
+    }
+}
+
+async fn run_bot(id: usize, driver: Driver, args: Arc<Args>, stats: Arc<Stats>, deadline: Instant) {
+    let start = Instant::now();
+    let connected = Client::connect_with(&driver, args.client_config()).await;
+    stats.record("connect", start, &connected);
+    let Ok((client, mut events)) = connected else {
+        return;
+    };
+    stats.online.fetch_add(1, Ordering::Relaxed);
+
+    let start = Instant::now();
+    let login = client
+        .request(LoginReq {
+            server_id: args.server_id,
+            account: format!("{}{}", args.account_prefix, id),
+        })
+        .await;
+    stats.record("login", start, &login);
+
+    let mut rng = StdRng::from_entropy();
+    let think = args.think.max(1);
+    'bot: while login.is_ok() && Instant::now() < deadline {
+        let wake = Instant::now() + Duration::from_millis(rng.gen_range(think / 2..=think * 3 / 2));
+        //等待期间处理推送,心跳由客户端自动维持
+        loop {
+            tokio::select! {
+                _ = tokio::time::sleep_until(wake.min(deadline)) => break,
+                event = events.recv() => {
+                    if !on_event(&stats, event) {
+                        break 'bot;
+                    }
+                }
+            }
+        }
+        if Instant::now() >= deadline {
+            break;
+        }
+        let Some(store_id) = args
+            .store_ids
+            .get(rng.gen_range(0..args.store_ids.len().max(1)))
+        else {
+            continue;
+        };
+        let start = Instant::now();
+        let result = client
+            .request(StoreInfoReq {
+                store_id: *store_id,
+            })
+            .await;
+        stats.record("store", start, &result);
+    }
+    client.close();
+    stats.online.fetch_sub(1, Ordering::Relaxed);
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    let args = Arc::new(Args::parse());
+    let stats = Arc::new(Stats::default());
+    let begin = Instant::now();
+    let deadline = begin + Duration::from_secs(args.duration);
+    println!(
+        "bots:{} addr:{} transport:{:?} duration:{}s",
+        args.bots, args.addr, args.transport, args.duration
+    );
+
+    //按ramp均匀启动机器人
+    let mut bots = {
+        let args = args.clone();
+        let stats = stats.clone();
+        let driver = Driver::new();
+        tokio::spawn(async move {
+            let mut handles = Vec::with_capacity(args.bots);
+            for id in 0..args.bots {
+                if args.ramp > 0 && id > 0 {
+                    tokio::time::sleep(Duration::from_secs(1) / args.ramp as u32).await;
+                }
+                handles.push(tokio::spawn(run_bot(
+                    id,
+                    driver.clone(),
+                    args.clone(),
+                    stats.clone(),
+                    deadline,
+                )));
+            }
+            futures::future::join_all(handles).await;
+        })
+    };
+
+    let report_interval = Duration::from_secs(args.report_interval.max(1));
+    let mut ticker = tokio::time::interval_at(begin + report_interval, report_interval);
+    let mut last = begin;
+    loop {
+        tokio::select! {
+            now = ticker.tick() => {
+                stats.report(now - begin, now - last);
+                last = now;
+            }
+            _ = &mut bots => break,
+            _ = tokio::signal::ctrl_c() => {
+                println!("interrupted");
+                break;
+            }
+        }
+    }
+    stats.summary(begin.elapsed());
+    Ok(())
+}
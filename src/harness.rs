@@ -0,0 +1,340 @@
+//! 进程内集群,用于端到端测试
+//!
+//! 在回环地址上依次启动 Center、Login、World、Game、Gate,登录使用内存存储,
+//! 通过 [`Cluster::connect`] 得到连接网关的客户端,[`Cluster::stop`] 按停服流程倒序停止所有节点。
+//! [`Cluster::connect_raw`] 得到不自动握手的连接,用于测试协议细节和异常的客户端。
+//!
+//! ActorSwarm 是进程内全局的,第一次启动时创建并运行在当前的 tokio 运行时中,
+//! 所以一个进程内同一时间只运行一个集群,并且都在同一个运行时中启动。
+use client::{Client, ClientConfig, ClientError, Event, Transport};
+use common::config::{GlobalConfig, ServerRole, ServerRoleId};
+use lib::gate::packet::{Codec, Packet};
+use lib::node::{Node, Signal};
+use lib::prelude::{CenterNode, GameNode, GateNode, LoginNode, WorldNode};
+use lib::store::Store;
+use message_io::network::{self, Endpoint, NetEvent};
+use message_io::node::{self, NodeEvent, NodeHandler, NodeTask};
+use std::fmt::Write;
+use std::net::{TcpListener, UdpSocket};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc::{self, UnboundedReceiver};
+
+pub struct ClusterBuilder {
+    games: u32,
+    worlds: u32,
+    //网关的心跳超时,单位秒
+    heartbeat_timeout: u64,
+    //追加到[[gate]]的配置,例如 encrypt = { tcp = true, key = "<十六进制私钥>" }
+    gate_options: Vec<String>,
+}
+
+impl Default for ClusterBuilder {
+    fn default() -> Self {
+        Self {
+            games: 1,
+            worlds: 1,
+            heartbeat_timeout: 30,
+            gate_options: vec![],
+        }
+    }
+}
+
+impl ClusterBuilder {
+    pub fn games(mut self, games: u32) -> Self {
+        self.games = games.max(1);
+        self
+    }
+
+    pub fn worlds(mut self, worlds: u32) -> Self {
+        self.worlds = worlds.max(1);
+        self
+    }
+
+    pub fn heartbeat_timeout(mut self, secs: u64) -> Self {
+        self.heartbeat_timeout = secs;
+        self
+    }
+
+    pub fn gate_option(mut self, option: impl Into<String>) -> Self {
+        self.gate_options.push(option.into());
+        self
+    }
+
+    pub async fn start(self) -> anyhow::Result<Cluster> {
+        let ports = GatePorts {
+            tcp: free_tcp_port()?,
+            ws: free_tcp_port()?,
+            udp: free_udp_port()?,
+        };
+        let config = Arc::new(GlobalConfig::from_toml(&self.config_toml(&ports)?)?);
+        let store = Store::memory();
+        let mut nodes: Vec<Box<dyn Node>> = vec![
+            Box::new(CenterNode::new(config.clone())),
+            Box::new(
+                LoginNode::new(config.clone(), ServerRoleId(ServerRole::Login, 1))
+                    .with_store(store.clone()),
+            ),
+        ];
+        for id in 1..=self.worlds {
+            nodes.push(Box::new(WorldNode::new(
+                config.clone(),
+                ServerRoleId(ServerRole::World, id),
+            )));
+        }
+        for id in 1..=self.games {
+            nodes.push(Box::new(GameNode::new(
+                config.clone(),
+                ServerRoleId(ServerRole::Game, id),
+            )));
+        }
+        //网关最后启动,客户端连上时其他节点都已注册到Center
+        nodes.push(Box::new(GateNode::new(
+            config.clone(),
+            ServerRoleId(ServerRole::Gate, 1),
+        )));
+
+        let mut cluster = Cluster {
+            config,
+            nodes: vec![],
+            store,
+            ports,
+        };
+        for mut node in nodes {
+            if let Err(e) = node.start().await {
+                let role_id = node.server_role_id();
+                //已经启动的节点一起停止
+                cluster.stop().await?;
+                return Err(e.context(format!("start {} failed", role_id)));
+            }
+            cluster.nodes.push(node);
+        }
+        Ok(cluster)
+    }
+
+    fn config_toml(&self, ports: &GatePorts) -> anyhow::Result<String> {
+        let mut toml = String::new();
+        writeln!(toml, "center_in_address = \"{}\"", quic_address()?)?;
+        toml.push_str(
+            r#"[config]
+type = "bin"
+[config.source_type.aws]
+region = ""
+access_key_id = ""
+secret_access_key = ""
+endpoint = ""
+bucket = ""
+[log]
+console = true
+level = "info"
+dir = "./logs"
+max_file = 1
+[shutdown]
+drain_wait = 1
+stop_timeout = 5
+"#,
+        );
+        //登录使用内存存储,keydb不会连接
+        let keydb =
+            r#"keydb = { host = "localhost", port = 6379, password = "", db = 0, pool_size = 1 }"#;
+        writeln!(
+            toml,
+            "[[login]]\nid = 1\nin_address = \"{}\"\n{}",
+            quic_address()?,
+            keydb
+        )?;
+        writeln!(
+            toml,
+            "[[gate]]\nid = 1\nin_address = \"{}\"\nout_tcp_port = {}\nout_ws_port = {}\nout_udp_port = {}\nheartbeat_timeout = {}\ncompress_threshold = 1024",
+            quic_address()?,
+            ports.tcp,
+            ports.ws,
+            ports.udp,
+            self.heartbeat_timeout
+        )?;
+        for option in &self.gate_options {
+            writeln!(toml, "{}", option)?;
+        }
+        for id in 1..=self.worlds {
+            writeln!(
+                toml,
+                "[[world]]\nid = {}\nin_address = \"{}\"",
+                id,
+                quic_address()?
+            )?;
+        }
+        for id in 1..=self.games {
+            writeln!(
+                toml,
+                "[[game]]\nid = {}\nin_address = \"{}\"\n{}",
+                id,
+                quic_address()?,
+                keydb
+            )?;
+        }
+        Ok(toml)
+    }
+}
+
+struct GatePorts {
+    tcp: u16,
+    ws: u16,
+    udp: u16,
+}
+
+pub struct Cluster {
+    config: Arc<GlobalConfig>,
+    //按启动顺序排列,停止时倒序
+    nodes: Vec<Box<dyn Node>>,
+    store: Store,
+    ports: GatePorts,
+}
+
+impl Cluster {
+    pub fn builder() -> ClusterBuilder {
+        ClusterBuilder::default()
+    }
+
+    pub async fn start() -> anyhow::Result<Cluster> {
+        ClusterBuilder::default().start().await
+    }
+
+    pub fn config(&self) -> &Arc<GlobalConfig> {
+        &self.config
+    }
+
+    //登录节点使用的存储,测试中可以直接检查或预置数据
+    pub fn store(&self) -> Store {
+        self.store.clone()
+    }
+
+    pub fn gate_addr(&self, transport: Transport) -> String {
+        let port = match transport {
+            Transport::Tcp => self.ports.tcp,
+            Transport::Ws => self.ports.ws,
+            Transport::Udp => self.ports.udp,
+        };
+        format!("127.0.0.1:{}", port)
+    }
+
+    pub fn client_config(&self, transport: Transport) -> ClientConfig {
+        ClientConfig::new(self.gate_addr(transport), transport)
+    }
+
+    pub async fn connect(
+        &self,
+        transport: Transport,
+    ) -> Result<(Client, UnboundedReceiver<Event>), ClientError> {
+        Client::connect(self.client_config(transport)).await
+    }
+
+    //握手和心跳都由调用方发送的连接,udp不使用可靠会话
+    pub async fn connect_raw(&self, transport: Transport) -> anyhow::Result<RawConnection> {
+        RawConnection::connect(self.gate_addr(transport), transport).await
+    }
+
+    //与进程退出相同的停服流程:全部Drain,等待drain_wait后全部Flush,再倒序停止
+    pub async fn stop(mut self) -> anyhow::Result<()> {
+        for node in self.nodes.iter_mut().rev() {
+            node.shutdown_phase(Signal::Drain).await;
+        }
+        tokio::time::sleep(Duration::from_secs(self.config.shutdown.drain_wait)).await;
+        for node in self.nodes.iter_mut().rev() {
+            node.shutdown_phase(Signal::Flush).await;
+        }
+        while let Some(mut node) = self.nodes.pop() {
+            node.stop().await?;
+        }
+        Ok(())
+    }
+}
+
+//不握手的连接,收发的包都不压缩不加密
+pub struct RawConnection {
+    handler: NodeHandler<()>,
+    endpoint: Endpoint,
+    codec: Codec,
+    packets: UnboundedReceiver<Packet>,
+    task: Option<NodeTask>,
+}
+
+impl RawConnection {
+    async fn connect(addr: String, transport: Transport) -> anyhow::Result<RawConnection> {
+        let (handler, listener) = node::split::<()>();
+        let transport = match transport {
+            Transport::Tcp => network::Transport::FramedTcp,
+            Transport::Ws => network::Transport::Ws,
+            Transport::Udp => network::Transport::Udp,
+        };
+        let connector = handler.clone();
+        let connected =
+            tokio::task::spawn_blocking(move || connector.network().connect_sync(transport, addr))
+                .await?;
+        let (endpoint, _) = match connected {
+            Ok(connected) => connected,
+            Err(e) => {
+                handler.stop();
+                return Err(e.into());
+            }
+        };
+        let (tx, packets) = mpsc::unbounded_channel();
+        let stopper = handler.clone();
+        let mut codec = Codec::default();
+        //断开后停止网络线程,发送端随之释放,recv返回None
+        let task = listener.for_each_async(move |event| match event {
+            NodeEvent::Network(NetEvent::Message(_, data)) => match codec.decode(data) {
+                Ok(packet) => {
+                    let _ = tx.send(packet);
+                }
+                Err(e) => {
+                    tracing::warn!("raw connection decoding failed:{}", e);
+                    stopper.stop();
+                }
+            },
+            NodeEvent::Network(NetEvent::Disconnected(_)) => stopper.stop(),
+            _ => {}
+        });
+        Ok(RawConnection {
+            handler,
+            endpoint,
+            codec: Codec::default(),
+            packets,
+            task: Some(task),
+        })
+    }
+
+    pub fn send(&mut self, packet: Packet) {
+        let bytes = self.codec.encode(packet);
+        self.handler.network().send(self.endpoint, &bytes);
+    }
+
+    //连接关闭或超时返回None
+    pub async fn recv(&mut self, timeout: Duration) -> Option<Packet> {
+        tokio::time::timeout(timeout, self.packets.recv())
+            .await
+            .ok()
+            .flatten()
+    }
+}
+
+impl Drop for RawConnection {
+    fn drop(&mut self) {
+        self.handler.stop();
+        //等待网络线程退出会阻塞,放到单独的线程中
+        if let Some(task) = self.task.take() {
+            std::thread::spawn(move || drop(task));
+        }
+    }
+}
+
+fn free_tcp_port() -> std::io::Result<u16> {
+    Ok(TcpListener::bind("127.0.0.1:0")?.local_addr()?.port())
+}
+
+fn free_udp_port() -> std::io::Result<u16> {
+    Ok(UdpSocket::bind("127.0.0.1:0")?.local_addr()?.port())
+}
+
+fn quic_address() -> std::io::Result<String> {
+    Ok(format!("/ip4/127.0.0.1/udp/{}/quic-v1", free_udp_port()?))
+}
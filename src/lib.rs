@@ -1,13 +1,15 @@
 use anyhow::Error;
 use common::config::{ARGS, Args, GlobalConfig, ServerRole, ServerRoleId, init_config};
 use common::logging::init_log;
-use lib::node::{Node, Signal};
+use lib::node::{Node, Signal, reload_config, signal_sender};
 use lib::prelude::{CenterNode, GameNode, GateNode, LoginNode, WorldNode};
 use std::sync::Arc;
-use tokio::sync::watch;
+use std::time::Duration;
 use tokio::sync::watch::Sender;
 use tokio::task::JoinHandle;
 
+pub mod harness;
+
 pub async fn start() -> Result<(), Error> {
     //1.初始化命令行参数
     init_config();
@@ -31,8 +33,9 @@ pub async fn start() -> Result<(), Error> {
     let _log_guards = init_log(config.log.clone(), log_name)?;
     tracing::info!("config:{:?}", config);
 
-    //发送退出信号
-    let (tx, rx) = watch::channel(Signal::None);
+    //发送退出和重新加载信号
+    let tx = signal_sender();
+    let rx = tx.subscribe();
     let config = Arc::new(config);
     //启动节点
     let mut join_handles = vec![];
@@ -52,7 +55,9 @@ pub async fn start() -> Result<(), Error> {
         join_handles.push(join_handle)
     }
 
-    listen_stop(tx, &mut join_handles);
+    let drain_wait = Duration::from_secs(config.shutdown.drain_wait);
+    listen_stop(tx, drain_wait, &mut join_handles);
+    listen_reload();
 
     tracing::info!("server starting");
     let result = futures::future::join_all(join_handles).await;
@@ -66,7 +71,13 @@ pub async fn start() -> Result<(), Error> {
     Ok(())
 }
 
-fn listen_stop(tx: Sender<Signal>, join_handles: &mut Vec<JoinHandle<()>>) {
+//收到退出信号后先Drain,等待drain_wait后Stop,节点在Stop前执行Flush
+//等待期间再次ctrl-c立即Stop
+fn listen_stop(
+    tx: &'static Sender<Signal>,
+    drain_wait: Duration,
+    join_handles: &mut Vec<JoinHandle<()>>,
+) {
     join_handles.push(tokio::spawn(async move {
         let ctrl_c = async {
             tokio::signal::ctrl_c()
@@ -86,16 +97,37 @@ fn listen_stop(tx: Sender<Signal>, join_handles: &mut Vec<JoinHandle<()>>) {
         tokio::select! {
             _ = ctrl_c => {
                 tracing::info!("shutting down on ctrl-c handler");
-                tx.send(Signal::Stop).expect("failed to send signal:Stop");
             },
             _ = terminate => {
                 tracing::info!("shutting down on termination handler");
-                tx.send(Signal::Stop).expect("failed to send signal:Stop");
             },
         }
+        tx.send(Signal::Drain).expect("failed to send signal:Drain");
+        tokio::select! {
+            _ = tokio::time::sleep(drain_wait) => {},
+            _ = tokio::signal::ctrl_c() => {
+                tracing::info!("skip draining on ctrl-c handler");
+            },
+        }
+        tx.send(Signal::Stop).expect("failed to send signal:Stop");
     }));
 }
 
+//SIGHUP重新加载配置表
+fn listen_reload() {
+    #[cfg(unix)]
+    tokio::spawn(async move {
+        use tokio::signal::unix::{SignalKind, signal};
+        let mut sighup = signal(SignalKind::hangup()).expect("Failed to install SIGHUP handler");
+        while sighup.recv().await.is_some() {
+            tracing::info!("reload config on hangup handler");
+            if !reload_config() {
+                tracing::warn!("server is shutting down, reload ignored");
+            }
+        }
+    });
+}
+
 fn create_node(
     role: &ServerRole,
     config: Arc<GlobalConfig>,
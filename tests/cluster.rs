@@ -0,0 +1,277 @@
+//! 五个角色在同一进程中启动的端到端测试
+//!
+//! ActorSwarm 是进程内全局的,所有场景共用一个集群,在同一个测试中依次执行
+use client::{ClientError, Event, Transport};
+use common::config::{ServerRole, ServerRoleId};
+use kameo::actor::RemoteActorRef;
+use kameo::prelude::ActorSwarm;
+use lib::admin::{AdminActor, AdminMessage};
+use lib::gate::packet::{Packet, Type};
+use lib::gate::{GateActor, GateMessage, KickTarget, PushTarget};
+use lib::node::{Signal, signal_sender};
+use prost::Message;
+use protocol::base_cmd::BaseError::{
+    ErrorFunctionNotImpliment, ErrorNotLogin, ErrorUnknownCommand,
+};
+use protocol::base_cmd::{ConfigVersionNtf, ErrorRsp, HandshakeReq, KickReason};
+use protocol::login_cmd::{LoginReq, LoginRsp, RegisterReq};
+use protocol::store_cmd::StoreInfoReq;
+use qs001_server::harness::{Cluster, RawConnection};
+use std::time::Duration;
+use tokio::sync::mpsc::UnboundedReceiver;
+
+fn login_req(account: &str) -> LoginReq {
+    LoginReq {
+        server_id: 1,
+        account: account.to_string(),
+    }
+}
+
+fn assert_code<T: std::fmt::Debug>(result: Result<T, ClientError>, expected: i32) {
+    match result {
+        Err(ClientError::Rsp { code, .. }) => assert_eq!(code, expected),
+        other => panic!("expected error code:{} got:{:?}", expected, other),
+    }
+}
+
+//等待踢下线通知,跳过推送
+async fn wait_kick(events: &mut UnboundedReceiver<Event>) -> KickReason {
+    tokio::time::timeout(Duration::from_secs(5), async {
+        loop {
+            match events.recv().await {
+                Some(Event::Kick(kick)) => return kick.reason(),
+                Some(Event::Push { .. }) => continue,
+                other => panic!("expected kick got:{:?}", other),
+            }
+        }
+    })
+    .await
+    .expect("kick timeout")
+}
+
+async fn register(cluster: &Cluster) {
+    let (client, _events) = cluster.connect(Transport::Tcp).await.unwrap();
+    client.request(RegisterReq {}).await.unwrap();
+    client.close();
+}
+
+async fn login(cluster: &Cluster) {
+    let (first, mut first_events) = cluster.connect(Transport::Tcp).await.unwrap();
+    let alice = first.request(login_req("alice")).await.unwrap().player_id;
+    assert!(alice > 0);
+    let mut store = cluster.store();
+    assert_eq!(
+        store.hget("account_player:1", "alice").await.unwrap(),
+        Some(alice)
+    );
+
+    //同一账号在另一个连接登录,玩家id不变,旧连接被踢下线
+    let (second, _events) = cluster.connect(Transport::Ws).await.unwrap();
+    assert_eq!(
+        second.request(login_req("alice")).await.unwrap().player_id,
+        alice
+    );
+    assert_eq!(
+        wait_kick(&mut first_events).await,
+        KickReason::KickDuplicateLogin
+    );
+
+    let (udp, _events) = cluster.connect(Transport::Udp).await.unwrap();
+    let bob = udp.request(login_req("bob")).await.unwrap().player_id;
+    assert_ne!(bob, alice);
+}
+
+async fn route(cluster: &Cluster) {
+    let (client, _events) = cluster.connect(Transport::Tcp).await.unwrap();
+    //未登录不转发到game
+    assert_code(
+        client.request(StoreInfoReq { store_id: 1 }).await,
+        ErrorNotLogin as i32,
+    );
+    client.request(login_req("carol")).await.unwrap();
+    //登录后转发到绑定的game节点,由game返回错误码
+    assert_code(
+        client.request(StoreInfoReq { store_id: 1 }).await,
+        ErrorFunctionNotImpliment as i32,
+    );
+
+    //不等登录响应紧跟着发出的请求,在绑定会话后转发
+    let (client, _events) = cluster.connect(Transport::Ws).await.unwrap();
+    let (login, store) = tokio::join!(
+        client.request(login_req("frank")),
+        client.request(StoreInfoReq { store_id: 1 })
+    );
+    assert!(login.unwrap().player_id > 0);
+    assert_code(store, ErrorFunctionNotImpliment as i32);
+}
+
+//按账号或玩家id踢下线,不在本网关的目标返回false
+async fn kick(cluster: &Cluster) {
+    let gate = RemoteActorRef::<GateActor>::lookup(&ServerRoleId(ServerRole::Gate, 1).to_string())
+        .await
+        .unwrap()
+        .unwrap();
+    let kick = |target| GateMessage::Kick {
+        target,
+        reason: KickReason::KickBanned as i32,
+        message: "banned".to_string(),
+        reconnect_after: 0,
+    };
+
+    let (client, mut events) = cluster.connect(Transport::Tcp).await.unwrap();
+    client.request(login_req("grace")).await.unwrap();
+    assert!(
+        gate.ask(&kick(KickTarget::Account("grace".to_string())))
+            .await
+            .unwrap()
+    );
+    assert_eq!(wait_kick(&mut events).await, KickReason::KickBanned);
+
+    let (client, mut events) = cluster.connect(Transport::Ws).await.unwrap();
+    let player_id = client.request(login_req("heidi")).await.unwrap().player_id;
+    assert!(
+        gate.ask(&kick(KickTarget::Player(player_id)))
+            .await
+            .unwrap()
+    );
+    assert_eq!(wait_kick(&mut events).await, KickReason::KickBanned);
+    assert!(
+        !gate
+            .ask(&kick(KickTarget::Account("nobody".to_string())))
+            .await
+            .unwrap()
+    );
+}
+
+//全服推送也发给还没有登录的连接,不在本网关的玩家不能加入频道
+async fn push(cluster: &Cluster) {
+    let gate = RemoteActorRef::<GateActor>::lookup(&ServerRoleId(ServerRole::Gate, 1).to_string())
+        .await
+        .unwrap()
+        .unwrap();
+    let (_client, mut events) = cluster.connect(Transport::Ws).await.unwrap();
+    let push = GateMessage::Push {
+        target: PushTarget::All,
+        cmd: ConfigVersionNtf::CMD,
+        data: Default::default(),
+    };
+    assert!(gate.ask(&push).await.unwrap());
+    let event = tokio::time::timeout(Duration::from_secs(5), events.recv())
+        .await
+        .expect("push timeout");
+    assert!(matches!(
+        event,
+        Some(Event::Push {
+            cmd: ConfigVersionNtf::CMD,
+            ..
+        })
+    ));
+
+    let join = GateMessage::JoinChannel {
+        channel: "guild".to_string(),
+        players: vec![-1],
+    };
+    assert!(!gate.ask(&join).await.unwrap());
+}
+
+//跳过心跳等控制包,等待下一个响应
+async fn recv_response(conn: &mut RawConnection) -> Packet {
+    loop {
+        let packet = conn
+            .recv(Duration::from_secs(5))
+            .await
+            .expect("response timeout");
+        if packet.r#type == Type::Response {
+            return packet;
+        }
+    }
+}
+
+fn request<M: Message>(cmd: i32, seq: u32, msg: M) -> Packet {
+    Packet::new_data(Type::Request, cmd, msg.encode_to_vec().into()).with_seq(seq)
+}
+
+//响应带回请求的seq,同一连接上的请求并发处理,先完成的先返回
+async fn pipeline(cluster: &Cluster) {
+    let mut conn = cluster.connect_raw(Transport::Tcp).await.unwrap();
+    let handshake = HandshakeReq {
+        client_version: "1.0.0".to_string(),
+        ..Default::default()
+    };
+    conn.send(Packet::new_data(
+        Type::Handshake,
+        HandshakeReq::CMD,
+        handshake.encode_to_vec().into(),
+    ));
+    let ack = conn.recv(Duration::from_secs(5)).await.unwrap();
+    assert_eq!(ack.r#type, Type::HandshakeAck);
+
+    conn.send(request(LoginReq::CMD, 7, login_req("erin")));
+    let rsp = recv_response(&mut conn).await;
+    assert_eq!((rsp.cmd, rsp.seq), (LoginRsp::CMD, 7));
+
+    //先发出的请求转发到game,后发出的未知命令由网关直接返回
+    conn.send(request(StoreInfoReq::CMD, 9, StoreInfoReq { store_id: 1 }));
+    conn.send(request(5000, 8, StoreInfoReq::default()));
+    let mut replies = vec![];
+    for _ in 0..2 {
+        let rsp = recv_response(&mut conn).await;
+        assert_eq!(rsp.cmd, ErrorRsp::CMD);
+        let error = ErrorRsp::decode(rsp.data).unwrap();
+        replies.push((rsp.seq, error.cmd, error.code));
+    }
+    assert_eq!(
+        replies,
+        vec![
+            (8, 5000, ErrorUnknownCommand as i32),
+            (9, StoreInfoReq::CMD, ErrorFunctionNotImpliment as i32),
+        ]
+    );
+}
+
+//运维指令转为本进程的ReloadConfig信号
+async fn reload() {
+    let mut signal_rx = signal_sender().subscribe();
+    let peer_id = *ActorSwarm::get().unwrap().local_peer_id();
+    let admin = RemoteActorRef::<AdminActor>::lookup(&AdminActor::name(&peer_id))
+        .await
+        .unwrap()
+        .unwrap();
+    //没有加载过的版本不能回滚
+    let rollback = AdminMessage::RollbackConfig {
+        hash: "0123abcd".to_string(),
+    };
+    assert!(!admin.ask(&rollback).await.unwrap());
+    assert!(admin.ask(&AdminMessage::ReloadConfig).await.unwrap());
+    signal_rx.changed().await.unwrap();
+    assert_eq!(*signal_rx.borrow(), Signal::ReloadConfig);
+}
+
+//停服时在线的连接以维护原因被踢下线,之后不再接受新连接
+async fn shutdown(cluster: Cluster) {
+    let (client, mut events) = cluster.connect(Transport::Tcp).await.unwrap();
+    client.request(login_req("dave")).await.unwrap();
+    let (_idle, mut idle_events) = cluster.connect(Transport::Ws).await.unwrap();
+    let addr = cluster.gate_addr(Transport::Tcp);
+    let stop = tokio::spawn(cluster.stop());
+    assert_eq!(wait_kick(&mut events).await, KickReason::KickMaintenance);
+    assert_eq!(
+        wait_kick(&mut idle_events).await,
+        KickReason::KickMaintenance
+    );
+    assert!(tokio::net::TcpStream::connect(addr).await.is_err());
+    stop.await.unwrap().unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn cluster() {
+    let cluster = Cluster::builder().games(2).start().await.unwrap();
+    register(&cluster).await;
+    login(&cluster).await;
+    route(&cluster).await;
+    pipeline(&cluster).await;
+    kick(&cluster).await;
+    push(&cluster).await;
+    reload().await;
+    shutdown(cluster).await;
+}
@@ -0,0 +1,139 @@
+//! 网关的握手检查
+//!
+//! 需要限制客户端版本并要求tcp加密,与其他场景分开启动集群
+use client::{ClientError, Transport};
+use lib::gate::crypto::{ServerKey, decode_key};
+use lib::gate::packet::{Packet, Type};
+use prost::Message;
+use protocol::base_cmd::{HandshakeReq, KickNtf, KickReason};
+use protocol::login_cmd::LoginReq;
+use qs001_server::harness::{Cluster, RawConnection};
+use std::time::Duration;
+
+//测试用的网关长期私钥
+const GATE_KEY: &str = "0707070707070707070707070707070707070707070707070707070707070707";
+
+async fn recv_kick(conn: &mut RawConnection) -> KickReason {
+    let packet = conn
+        .recv(Duration::from_secs(5))
+        .await
+        .expect("kick timeout");
+    assert_eq!(packet.r#type, Type::Kick);
+    let reason = KickNtf::decode(packet.data).unwrap().reason();
+    //踢下线后连接关闭
+    assert!(conn.recv(Duration::from_secs(5)).await.is_none());
+    reason
+}
+
+fn login() -> Packet {
+    let req = LoginReq {
+        server_id: 1,
+        account: "mallory".to_string(),
+    };
+    Packet::new_data(Type::Request, LoginReq::CMD, req.encode_to_vec().into()).with_seq(1)
+}
+
+//不握手直接发请求或通知,不能绕过最低版本的检查
+async fn handshake_required(cluster: &Cluster) {
+    let mut conn = cluster.connect_raw(Transport::Ws).await.unwrap();
+    conn.send(login());
+    assert_eq!(recv_kick(&mut conn).await, KickReason::KickBadHandshake);
+
+    let mut conn = cluster.connect_raw(Transport::Ws).await.unwrap();
+    conn.send(Packet::new_data(
+        Type::Notify,
+        LoginReq::CMD,
+        Default::default(),
+    ));
+    assert_eq!(recv_kick(&mut conn).await, KickReason::KickBadHandshake);
+
+    let mut conn = cluster.connect_raw(Transport::Ws).await.unwrap();
+    let req = HandshakeReq {
+        client_version: "0.9.0".to_string(),
+        ..Default::default()
+    };
+    conn.send(Packet::new_data(
+        Type::Handshake,
+        HandshakeReq::CMD,
+        req.encode_to_vec().into(),
+    ));
+    assert_eq!(
+        recv_kick(&mut conn).await,
+        KickReason::KickVersionIncompatible
+    );
+
+    let mut config = cluster.client_config(Transport::Ws);
+    config.client_version = "0.9.0".to_string();
+    match client::Client::connect(config).await {
+        Err(ClientError::Kicked(kick)) => {
+            assert_eq!(kick.reason(), KickReason::KickVersionIncompatible)
+        }
+        other => panic!("expected kick got:{:?}", other.map(|_| ())),
+    }
+    cluster.connect(Transport::Ws).await.unwrap();
+}
+
+//要求加密的监听上,明文的请求和心跳都不处理
+async fn encryption_required(cluster: &Cluster) {
+    let mut conn = cluster.connect_raw(Transport::Tcp).await.unwrap();
+    conn.send(login());
+    assert_eq!(
+        recv_kick(&mut conn).await,
+        KickReason::KickEncryptionRequired
+    );
+
+    let mut conn = cluster.connect_raw(Transport::Tcp).await.unwrap();
+    conn.send(Packet::new_control(Type::Heartbeat).with_seq(100));
+    assert_eq!(
+        recv_kick(&mut conn).await,
+        KickReason::KickEncryptionRequired
+    );
+
+    //不支持加密的客户端握手失败
+    match client::Client::connect(cluster.client_config(Transport::Tcp)).await {
+        Err(ClientError::Kicked(kick)) => {
+            assert_eq!(kick.reason(), KickReason::KickEncryptionRequired)
+        }
+        other => panic!("expected kick got:{:?}", other.map(|_| ())),
+    }
+    //固定的网关公钥不对时算不出同样的会话密钥,网关解不开握手后的请求
+    let mut config = cluster.client_config(Transport::Tcp);
+    config.encryption = true;
+    config.server_key = Some(ServerKey::new([9; 32]).public_key());
+    assert!(client::Client::connect(config.clone()).await.is_err());
+
+    config.server_key = Some(ServerKey::new(decode_key(GATE_KEY).unwrap()).public_key());
+    let (client, _events) = client::Client::connect(config.clone()).await.unwrap();
+    client
+        .request(LoginReq {
+            server_id: 1,
+            account: "trent".to_string(),
+        })
+        .await
+        .unwrap();
+    assert!(!client.resume().resumed);
+
+    //重连凭证在加密通道中下发,断线后凭它恢复会话
+    config.resume_token = client.resume().resume_token.clone();
+    config.last_push_seq = client.last_push_seq();
+    client.close();
+    tokio::time::sleep(Duration::from_millis(500)).await;
+    let (client, _events) = client::Client::connect(config).await.unwrap();
+    assert!(client.resume().resumed);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn handshake() {
+    let cluster = Cluster::builder()
+        .gate_option(r#"min_client_version = "1.0.0""#)
+        .gate_option(format!(
+            r#"encrypt = {{ tcp = true, key = "{}" }}"#,
+            GATE_KEY
+        ))
+        .start()
+        .await
+        .unwrap();
+    handshake_required(&cluster).await;
+    encryption_required(&cluster).await;
+    cluster.stop().await.unwrap();
+}
@@ -0,0 +1,42 @@
+//! 心跳超时的连接被踢下线
+//!
+//! 需要较短的心跳超时,与其他场景分开启动集群
+use client::Transport;
+use lib::gate::packet::{Packet, Type};
+use prost::Message;
+use protocol::base_cmd::{HandshakeReq, KickNtf, KickReason};
+use qs001_server::harness::Cluster;
+use std::time::Duration;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn idle_timeout() {
+    let cluster = Cluster::builder()
+        .heartbeat_timeout(1)
+        .start()
+        .await
+        .unwrap();
+    let mut conn = cluster.connect_raw(Transport::Tcp).await.unwrap();
+    let handshake = HandshakeReq {
+        client_version: "1.0.0".to_string(),
+        ..Default::default()
+    };
+    conn.send(Packet::new_data(
+        Type::Handshake,
+        HandshakeReq::CMD,
+        handshake.encode_to_vec().into(),
+    ));
+    let ack = conn.recv(Duration::from_secs(5)).await.unwrap();
+    assert_eq!(ack.r#type, Type::HandshakeAck);
+
+    //握手后不再发送心跳,下一次检测时被踢下线
+    let kick = conn
+        .recv(Duration::from_secs(10))
+        .await
+        .expect("kick timeout");
+    assert_eq!(kick.r#type, Type::Kick);
+    let kick = KickNtf::decode(kick.data).unwrap();
+    assert_eq!(kick.reason(), KickReason::KickHeartbeatTimeout);
+    //随后连接关闭
+    assert!(conn.recv(Duration::from_secs(5)).await.is_none());
+    cluster.stop().await.unwrap();
+}
@@ -0,0 +1,87 @@
+//! Throttle策略下超出限流的请求延后处理,不丢弃也不阻塞连接
+//!
+//! 需要单独的限流配置和较短的心跳超时,与其他场景分开启动集群
+use client::Transport;
+use common::config::{ServerRole, ServerRoleId};
+use kameo::actor::RemoteActorRef;
+use lib::gate::packet::{Packet, Type};
+use lib::gate::{GateActor, GateMessage, PushTarget};
+use prost::Message;
+use protocol::base_cmd::{ConfigVersionNtf, ErrorRsp, HandshakeReq};
+use qs001_server::harness::Cluster;
+use std::time::{Duration, Instant};
+
+#[tokio::test(flavor = "multi_thread")]
+async fn throttle() {
+    let cluster = Cluster::builder()
+        .heartbeat_timeout(1)
+        .gate_option(
+            r#"rate_limit = { policy = "throttle", cmds = [{ cmd = 5000, per_sec = 1, burst = 1 }] }"#,
+        )
+        .start()
+        .await
+        .unwrap();
+    let mut conn = cluster.connect_raw(Transport::Tcp).await.unwrap();
+    let handshake = HandshakeReq {
+        client_version: "1.0.0".to_string(),
+        ..Default::default()
+    };
+    conn.send(Packet::new_data(
+        Type::Handshake,
+        HandshakeReq::CMD,
+        handshake.encode_to_vec().into(),
+    ));
+    let ack = conn.recv(Duration::from_secs(5)).await.unwrap();
+    assert_eq!(ack.r#type, Type::HandshakeAck);
+
+    let start = Instant::now();
+    for seq in 1..=4 {
+        conn.send(Packet::new_data(Type::Request, 5000, Default::default()).with_seq(seq));
+    }
+    //等待令牌期间推送照常发出
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    let gate = RemoteActorRef::<GateActor>::lookup(&ServerRoleId(ServerRole::Gate, 1).to_string())
+        .await
+        .unwrap()
+        .unwrap();
+    let push = GateMessage::Push {
+        target: PushTarget::All,
+        cmd: ConfigVersionNtf::CMD,
+        data: Default::default(),
+    };
+    assert!(gate.ask(&push).await.unwrap());
+    let pushed_at = Instant::now();
+    let mut pushed = false;
+    //心跳超时检测照常进行,排队的心跳不会让连接被判定超时
+    let mut seqs = vec![];
+    while seqs.len() < 4 {
+        assert!(
+            start.elapsed() < Duration::from_secs(10),
+            "response timeout"
+        );
+        conn.send(Packet::new_control(Type::Heartbeat));
+        let Some(packet) = conn.recv(Duration::from_millis(300)).await else {
+            continue;
+        };
+        match packet.r#type {
+            Type::Response => {
+                assert_eq!(packet.cmd, ErrorRsp::CMD);
+                assert_eq!(ErrorRsp::decode(packet.data).unwrap().cmd, 5000);
+                seqs.push(packet.seq);
+            }
+            Type::Push => {
+                assert!(pushed_at.elapsed() < Duration::from_millis(500));
+                assert!(seqs.len() < 2, "push delayed by throttled requests");
+                pushed = true;
+            }
+            Type::Heartbeat => {}
+            other => panic!("unexpected packet:{}", other),
+        }
+    }
+    seqs.sort();
+    assert_eq!(seqs, vec![1, 2, 3, 4]);
+    assert!(pushed);
+    //每秒1个,后3个请求各等待约1秒
+    assert!(start.elapsed() >= Duration::from_millis(2500));
+    cluster.stop().await.unwrap();
+}